@@ -0,0 +1,401 @@
+//! REST/JSON API
+//!
+//! Plain HTTP endpoints over the same index the MCP server uses, for scripts,
+//! web UIs and editor plugins that do not speak MCP:
+//! - `GET /search` (query string) or `POST /search` (JSON body) - search memos
+//! - `GET /memos/{id}` - fetch a single memo
+//! - `GET /tags` - list tags with document counts
+//! - `GET /recent` - most recent memos
+//! - `GET /health` - liveness and index status
+//!
+//! Requests and responses reuse the types in [`crate::mcp`]. The API serves
+//! a single index; `digrag http` rejects configs declaring several
+//! `[[indexes]]` (use `digrag serve` for those). The index is reloaded when
+//! it is rebuilt, like the MCP server's.
+
+use crate::config::app_config::{AppConfig, DEFAULT_INDEX_NAME};
+use crate::config::SearchMode;
+use crate::extract::{ContentExtractor, ExtractionStrategy, TruncationConfig};
+use crate::mcp::{
+    require_bearer, ErrorResponse, GetMemoRequest, GetRecentMemosRequest, GetRecentMemosResponse,
+    HealthResponse, ListTagsResponse, MemoDetail, MemoResult, QueryMemosRequest,
+    QueryMemosResponse, TagInfo,
+};
+use crate::search::{Searcher, SharedIndexes};
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+/// Shared state for API handlers
+#[derive(Clone)]
+pub struct ApiState {
+    indexes: SharedIndexes,
+    config: Arc<AppConfig>,
+}
+
+impl ApiState {
+    /// Create API state from the served index and configuration
+    ///
+    /// Requests use the primary index of `indexes`, so a reloader storing a
+    /// rebuilt index into them is picked up by the next request.
+    pub fn new(indexes: SharedIndexes, config: Arc<AppConfig>) -> Self {
+        Self { indexes, config }
+    }
+}
+
+/// Error returned by API handlers as a JSON body
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(ErrorResponse { error: self.1 })).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<Json<T>, ApiError>;
+
+/// Build the API router
+///
+/// When `http_bearer_token` is configured, every endpoint except `/health`
+/// requires an `Authorization: Bearer <token>` header.
+pub fn router(state: ApiState) -> Router {
+    let bearer_token: Option<Arc<str>> = state.config.http_bearer_token().map(Arc::from);
+
+    Router::new()
+        .route("/search", get(search_get).post(search_post))
+        .route("/memos/{id}", get(get_memo))
+        .route("/tags", get(list_tags))
+        .route("/recent", get(recent))
+        .layer(middleware::from_fn_with_state(bearer_token, require_bearer))
+        .route("/health", get(health))
+        .with_state(state)
+}
+
+/// Serve the API until the listener fails
+pub async fn serve_api(listener: TcpListener, state: ApiState) -> Result<()> {
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+async fn search_get(
+    State(state): State<ApiState>,
+    Query(request): Query<QueryMemosRequest>,
+) -> ApiResult<QueryMemosResponse> {
+    search(&state, request)
+}
+
+async fn search_post(
+    State(state): State<ApiState>,
+    Json(request): Json<QueryMemosRequest>,
+) -> ApiResult<QueryMemosResponse> {
+    search(&state, request)
+}
+
+fn search(state: &ApiState, request: QueryMemosRequest) -> ApiResult<QueryMemosResponse> {
+    let indexes = state.indexes.load();
+    let searcher = indexes.primary();
+    let default_mode = state.config.default_search_mode();
+    let config = request.search_config(default_mode);
+    let (page, facets) = if request.facets {
+        searcher
+            .search_page_with_facets(&request.query, &config)
+            .map(|(page, facets)| (page, Some(facets)))
+    } else {
+        searcher
+            .search_page(&request.query, &config)
+            .map(|page| (page, None))
+    }
+    .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let mut warnings = Vec::new();
+    if config.search_mode != SearchMode::Bm25 && !searcher.has_vector_index() {
+        warnings.push(
+            "Vector index is not available; semantic results are empty. Rebuild with --with-embeddings.".to_string(),
+        );
+    }
+
+    // Snippet mode needs no extraction beyond the snippet itself
+    let extraction_mode = request
+        .extraction_mode
+        .as_deref()
+        .unwrap_or_else(|| state.config.extraction_mode());
+    let extractor = (extraction_mode != "snippet")
+        .then(|| extractor(state, Some(extraction_mode), request.max_chars));
+
+    let docstore = searcher.docstore();
+    let results = page
+        .items
+        .iter()
+        .filter_map(|result| {
+            let doc = docstore.get(&result.doc_id)?;
+            let memo = MemoResult::from_document(doc, Some(result.score));
+            Some(match &extractor {
+                Some(extractor) => memo.with_extracted(extractor.extract(&doc.text).into()),
+                None => memo,
+            })
+        })
+        .collect();
+
+    Ok(Json(QueryMemosResponse {
+        mode: request.effective_mode(default_mode).to_string(),
+        query: request.query,
+        results,
+        total: page.total,
+        total_exact: page.total_exact,
+        offset: page.offset,
+        next_cursor: page.next_cursor,
+        facets,
+        digest: None,
+        warnings,
+        indexed_at: indexed_at(searcher),
+    }))
+}
+
+async fn get_memo(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    Query(request): Query<GetMemoRequest>,
+) -> ApiResult<MemoDetail> {
+    let indexes = state.indexes.load();
+    let searcher = indexes.primary();
+    let docstore = searcher.docstore();
+    let doc = docstore
+        .get(&id)
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("Memo not found: {}", id)))?;
+
+    let extractor = extractor(
+        &state,
+        Some(request.extraction_mode.as_deref().unwrap_or("full")),
+        request.max_chars,
+    );
+    Ok(Json(MemoDetail::from_document(
+        docstore,
+        doc,
+        &extractor,
+        request.neighbors,
+    )))
+}
+
+async fn list_tags(State(state): State<ApiState>) -> Json<ListTagsResponse> {
+    let tags = state
+        .indexes
+        .load()
+        .primary()
+        .docstore()
+        .tag_counts()
+        .into_iter()
+        .map(TagInfo::from_count)
+        .collect();
+    Json(ListTagsResponse { tags })
+}
+
+async fn recent(
+    State(state): State<ApiState>,
+    Query(request): Query<GetRecentMemosRequest>,
+) -> ApiResult<GetRecentMemosResponse> {
+    let indexes = state.indexes.load();
+    let searcher = indexes.primary();
+    let page = searcher
+        .get_recent_memos_page(request.offset, request.cursor.as_deref(), request.limit)
+        .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    Ok(Json(GetRecentMemosResponse {
+        memos: page
+            .items
+            .iter()
+            .map(|doc| MemoResult::from_document(doc, None))
+            .collect(),
+        total: page.total,
+        total_exact: page.total_exact,
+        offset: page.offset,
+        next_cursor: page.next_cursor.clone(),
+        indexed_at: indexed_at(searcher),
+    }))
+}
+
+async fn health(State(state): State<ApiState>) -> Json<HealthResponse> {
+    let indexes = state.indexes.load();
+    let searcher = indexes.primary();
+    Json(HealthResponse {
+        status: "ok".to_string(),
+        documents: searcher.docstore().len(),
+        vector_index: searcher.has_vector_index(),
+    })
+}
+
+/// Build timestamp of the served index, keyed like the MCP responses
+fn indexed_at(searcher: &Searcher) -> BTreeMap<String, String> {
+    searcher
+        .created_at()
+        .map(|created_at| (DEFAULT_INDEX_NAME.to_string(), created_at.to_string()))
+        .into_iter()
+        .collect()
+}
+
+/// Build an extractor for a mode, defaulting to the configured mode and limit
+fn extractor(state: &ApiState, mode: Option<&str>, max_chars: Option<usize>) -> ContentExtractor {
+    let mode = mode.unwrap_or_else(|| state.config.extraction_mode());
+    let truncation = TruncationConfig {
+        max_chars: Some(max_chars.unwrap_or_else(|| state.config.extraction_max_chars())),
+        max_lines: None,
+        max_sections: None,
+    };
+    ContentExtractor::new(ExtractionStrategy::from_mode(mode), truncation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::Docstore;
+    use crate::loader::Document;
+    use crate::search::IndexSet;
+    use chrono::{TimeZone, Utc};
+    use serde_json::Value;
+
+    /// Write an index with three memos and start the API on an ephemeral port
+    async fn start_api(config: AppConfig) -> (String, tempfile::TempDir) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("bm25_index.json"),
+            r#"{"version": "1.0", "doc_ids": ["doc1", "doc2", "doc3"],
+                "corpus": [["rust", "mcp"], ["rust", "api"], ["python"]]}"#,
+        )
+        .unwrap();
+
+        let mut docstore = Docstore::new();
+        for (i, id) in ["doc1", "doc2", "doc3"].iter().enumerate() {
+            docstore.add(Document::with_id(
+                id.to_string(),
+                format!("Memo {}", id),
+                Utc.with_ymd_and_hms(2025, 1, i as u32 + 1, 9, 0, 0)
+                    .unwrap(),
+                vec!["memo".to_string()],
+                format!("Body of {}", id),
+            ));
+        }
+        docstore
+            .save_to_file(&temp_dir.path().join("docstore.json"))
+            .unwrap();
+
+        let searcher = Searcher::new(temp_dir.path()).unwrap();
+        let state = ApiState::new(
+            SharedIndexes::new(IndexSet::new(DEFAULT_INDEX_NAME, searcher)),
+            Arc::new(config),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve_api(listener, state));
+        (base, temp_dir)
+    }
+
+    async fn get_json(url: &str) -> (u16, Value) {
+        let response = reqwest::get(url).await.unwrap();
+        let status = response.status().as_u16();
+        (status, response.json().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_health_and_tags() {
+        let (base, _dir) = start_api(AppConfig::default()).await;
+
+        let (status, body) = get_json(&format!("{}/health", base)).await;
+        assert_eq!(status, 200);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["documents"], 3);
+        assert_eq!(body["vector_index"], false);
+
+        let (status, body) = get_json(&format!("{}/tags", base)).await;
+        assert_eq!(status, 200);
+        assert_eq!(body["tags"][0]["name"], "memo");
+        assert_eq!(body["tags"][0]["count"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_memo() {
+        let (base, _dir) = start_api(AppConfig::default()).await;
+
+        let (status, body) = get_json(&format!("{}/memos/doc2?neighbors=1", base)).await;
+        assert_eq!(status, 200);
+        assert_eq!(body["id"], "doc2");
+        assert_eq!(body["extracted"]["raw_content"], "Body of doc2");
+        assert_eq!(body["previous"][0]["id"], "doc1");
+        assert_eq!(body["next"][0]["id"], "doc3");
+
+        let (status, body) = get_json(&format!("{}/memos/missing", base)).await;
+        assert_eq!(status, 404);
+        assert_eq!(body["error"], "Memo not found: missing");
+    }
+
+    #[tokio::test]
+    async fn test_recent_pagination() {
+        let (base, _dir) = start_api(AppConfig::default()).await;
+
+        let (status, body) = get_json(&format!("{}/recent?limit=2", base)).await;
+        assert_eq!(status, 200);
+        assert_eq!(body["memos"][0]["id"], "doc3");
+        assert_eq!(body["total"], 3);
+        let cursor = body["next_cursor"].as_str().unwrap().to_string();
+
+        let (_, body) = get_json(&format!("{}/recent?limit=2&cursor={}", base, cursor)).await;
+        assert_eq!(body["memos"][0]["id"], "doc1");
+        assert!(body.get("next_cursor").is_none());
+
+        let (status, _) = get_json(&format!("{}/recent?cursor=bogus", base)).await;
+        assert_eq!(status, 400);
+    }
+
+    #[tokio::test]
+    async fn test_search_get_and_post() {
+        let (base, _dir) = start_api(AppConfig::default()).await;
+
+        let (status, body) = get_json(&format!("{}/search?query=rust&top_k=5", base)).await;
+        assert_eq!(status, 200);
+        assert_eq!(body["query"], "rust");
+        assert_eq!(body["mode"], "bm25");
+        assert!(body["results"].is_array());
+
+        let response = reqwest::Client::new()
+            .post(format!("{}/search", base))
+            .json(&serde_json::json!({"query": "rust", "search_mode": "semantic"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["mode"], "semantic");
+        assert_eq!(body["warnings"].as_array().unwrap().len(), 1);
+
+        let (status, _) = get_json(&format!("{}/search?query=rust&cursor=bogus", base)).await;
+        assert_eq!(status, 400);
+    }
+
+    #[tokio::test]
+    async fn test_bearer_token_guards_all_but_health() {
+        let config: AppConfig = toml::from_str(r#"http_bearer_token = "secret""#).unwrap();
+        let (base, _dir) = start_api(config).await;
+        let client = reqwest::Client::new();
+
+        let response = client.get(format!("{}/tags", base)).send().await.unwrap();
+        assert_eq!(response.status(), 401);
+
+        let response = client
+            .get(format!("{}/tags", base))
+            .bearer_auth("secret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        let response = client.get(format!("{}/health", base)).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+    }
+}
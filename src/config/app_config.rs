@@ -3,10 +3,44 @@
 //! Provides TOML-based configuration with environment variable override support.
 //! Priority: CLI args > Environment variables > Config file > Defaults
 
+use crate::extract::prompt::DEFAULT_PROMPT_NAME;
+use crate::loader::WritePosition;
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+/// Name of the index loaded from `index_dir` when no named indexes are declared
+pub const DEFAULT_INDEX_NAME: &str = "default";
+
+/// A named index directory declared with `[[indexes]]` in config.toml
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedIndex {
+    /// Name used to select the index (e.g. "changelog", "wiki")
+    pub name: String,
+    /// Path to the index directory
+    pub path: String,
+}
+
+/// A summarization prompt template declared with `[[summary_prompts]]` in
+/// config.toml
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SummaryPrompt {
+    /// Name used to select the template (e.g. "brief", "english")
+    pub name: String,
+    /// System prompt
+    pub system: String,
+    /// User message (default: "{content}")
+    #[serde(default = "default_prompt_user")]
+    pub user: String,
+    /// Language to write summaries in (overrides `summarization_language`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+fn default_prompt_user() -> String {
+    "{content}".to_string()
+}
+
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -64,6 +98,50 @@ pub struct AppConfig {
     #[serde(default = "default_summarization_temperature")]
     summarization_temperature: f32,
 
+    /// Summarizer used without the LLM and when it fails:
+    /// "rule-based" (default) or "extractive"
+    #[serde(default = "default_summarization_strategy")]
+    summarization_strategy: String,
+
+    /// Prompt template used for LLM summaries (default: "default")
+    #[serde(default = "default_summarization_prompt")]
+    summarization_prompt: String,
+
+    /// Language LLM summaries are written in (default: the prompt's language)
+    #[serde(default)]
+    summarization_language: Option<String>,
+
+    /// Models a request may pick besides `summarization_model`
+    #[serde(default)]
+    summarization_allowed_models: Vec<String>,
+
+    /// Highest `max_tokens` a request may ask for (default: 2000)
+    #[serde(default = "default_summarization_max_tokens_limit")]
+    summarization_max_tokens_limit: usize,
+
+    /// Highest temperature a request may ask for (default: 1.0)
+    #[serde(default = "default_summarization_max_temperature")]
+    summarization_max_temperature: f32,
+
+    // =========================================================================
+    // Summary Cache Settings
+    // =========================================================================
+    /// Keep LLM summaries in an on-disk cache (default: true)
+    #[serde(default = "default_true")]
+    summary_cache_enabled: bool,
+
+    /// Cache file (default: summaries.db in the digrag cache directory)
+    #[serde(default)]
+    summary_cache_path: Option<String>,
+
+    /// Days before a cached summary expires (default: 30)
+    #[serde(default = "default_summary_cache_ttl_days")]
+    summary_cache_ttl_days: u64,
+
+    /// Maximum number of cached summaries (default: 10000)
+    #[serde(default = "default_summary_cache_max_entries")]
+    summary_cache_max_entries: usize,
+
     // =========================================================================
     // OpenRouter Provider Settings
     // =========================================================================
@@ -90,6 +168,40 @@ pub struct AppConfig {
     /// Require full parameter support from provider
     #[serde(default)]
     provider_require_parameters: bool,
+
+    // =========================================================================
+    // HTTP Server Settings
+    // =========================================================================
+    /// Bearer token required by `serve --transport http` (None = no auth)
+    #[serde(default)]
+    http_bearer_token: Option<String>,
+
+    // =========================================================================
+    // Write Settings
+    // =========================================================================
+    /// Allow `add_memo` and `digrag add` to write new memos
+    #[serde(default)]
+    allow_write: bool,
+
+    /// Changelog file new memos are written to
+    #[serde(default)]
+    changelog_path: Option<String>,
+
+    /// Where new memos go in the changelog: "prepend" or "append"
+    #[serde(default = "default_write_position")]
+    write_position: String,
+
+    // =========================================================================
+    // Multiple Indexes
+    // =========================================================================
+    /// Named indexes served together; the first one is the default
+    /// (empty = serve `index_dir` alone)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    indexes: Vec<NamedIndex>,
+
+    /// Prompt templates selectable for LLM summaries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    summary_prompts: Vec<SummaryPrompt>,
 }
 
 fn default_index_dir() -> String {
@@ -124,10 +236,38 @@ fn default_summarization_max_tokens() -> usize {
     500
 }
 
+fn default_summarization_strategy() -> String {
+    "rule-based".to_string()
+}
+
+fn default_summarization_max_tokens_limit() -> usize {
+    2000
+}
+
+fn default_summarization_max_temperature() -> f32 {
+    1.0
+}
+
+fn default_summarization_prompt() -> String {
+    DEFAULT_PROMPT_NAME.to_string()
+}
+
+fn default_summary_cache_ttl_days() -> u64 {
+    30
+}
+
+fn default_summary_cache_max_entries() -> usize {
+    10_000
+}
+
 fn default_summarization_temperature() -> f32 {
     0.3
 }
 
+fn default_write_position() -> String {
+    "prepend".to_string()
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
@@ -145,6 +285,17 @@ impl Default for AppConfig {
             summarization_model: default_summarization_model(),
             summarization_max_tokens: default_summarization_max_tokens(),
             summarization_temperature: default_summarization_temperature(),
+            summarization_strategy: default_summarization_strategy(),
+            summarization_prompt: default_summarization_prompt(),
+            summarization_language: None,
+            summarization_allowed_models: Vec::new(),
+            summarization_max_tokens_limit: default_summarization_max_tokens_limit(),
+            summarization_max_temperature: default_summarization_max_temperature(),
+            // Summary cache settings
+            summary_cache_enabled: default_true(),
+            summary_cache_path: None,
+            summary_cache_ttl_days: default_summary_cache_ttl_days(),
+            summary_cache_max_entries: default_summary_cache_max_entries(),
             // Provider settings
             provider_order: None,
             provider_allow_fallbacks: default_true(),
@@ -152,6 +303,15 @@ impl Default for AppConfig {
             provider_ignore: None,
             provider_sort: None,
             provider_require_parameters: false,
+            // HTTP server settings
+            http_bearer_token: None,
+            // Write settings
+            allow_write: false,
+            changelog_path: None,
+            write_position: default_write_position(),
+            // Multiple indexes
+            indexes: Vec::new(),
+            summary_prompts: Vec::new(),
         }
     }
 }
@@ -210,6 +370,22 @@ impl AppConfig {
             config.summarization_model = model;
         }
 
+        if let Ok(strategy) = std::env::var("DIGRAG_SUMMARIZATION_STRATEGY") {
+            config.summarization_strategy = strategy;
+        }
+
+        if let Ok(prompt) = std::env::var("DIGRAG_SUMMARIZATION_PROMPT") {
+            config.summarization_prompt = prompt;
+        }
+
+        if let Ok(language) = std::env::var("DIGRAG_SUMMARIZATION_LANGUAGE") {
+            config.summarization_language = Some(language);
+        }
+
+        if let Ok(path) = std::env::var("DIGRAG_SUMMARY_CACHE_PATH") {
+            config.summary_cache_path = Some(path);
+        }
+
         // Provider settings from env
         if let Ok(order) = std::env::var("DIGRAG_PROVIDER_ORDER") {
             config.provider_order = Some(order.split(',').map(|s| s.trim().to_string()).collect());
@@ -220,6 +396,20 @@ impl AppConfig {
                 fallbacks.to_lowercase() == "true" || fallbacks == "1";
         }
 
+        // HTTP server settings from env
+        if let Ok(token) = std::env::var("DIGRAG_HTTP_BEARER_TOKEN") {
+            config.http_bearer_token = Some(token);
+        }
+
+        // Write settings from env
+        if let Ok(allow) = std::env::var("DIGRAG_ALLOW_WRITE") {
+            config.allow_write = allow.to_lowercase() == "true" || allow == "1";
+        }
+
+        if let Ok(path) = std::env::var("DIGRAG_CHANGELOG_PATH") {
+            config.changelog_path = Some(path);
+        }
+
         config
     }
 
@@ -281,6 +471,63 @@ impl AppConfig {
             } else {
                 self.summarization_temperature
             },
+            summarization_strategy: if other.summarization_strategy
+                != default_summarization_strategy()
+            {
+                other.summarization_strategy.clone()
+            } else {
+                self.summarization_strategy.clone()
+            },
+            summarization_prompt: if other.summarization_prompt != default_summarization_prompt() {
+                other.summarization_prompt.clone()
+            } else {
+                self.summarization_prompt.clone()
+            },
+            summarization_language: other
+                .summarization_language
+                .clone()
+                .or_else(|| self.summarization_language.clone()),
+            summarization_allowed_models: if other.summarization_allowed_models.is_empty() {
+                self.summarization_allowed_models.clone()
+            } else {
+                other.summarization_allowed_models.clone()
+            },
+            summarization_max_tokens_limit: if other.summarization_max_tokens_limit
+                != default_summarization_max_tokens_limit()
+            {
+                other.summarization_max_tokens_limit
+            } else {
+                self.summarization_max_tokens_limit
+            },
+            summarization_max_temperature: if (other.summarization_max_temperature
+                - default_summarization_max_temperature())
+            .abs()
+                > 0.001
+            {
+                other.summarization_max_temperature
+            } else {
+                self.summarization_max_temperature
+            },
+            // Summary cache settings
+            summary_cache_enabled: other.summary_cache_enabled && self.summary_cache_enabled,
+            summary_cache_path: other
+                .summary_cache_path
+                .clone()
+                .or_else(|| self.summary_cache_path.clone()),
+            summary_cache_ttl_days: if other.summary_cache_ttl_days
+                != default_summary_cache_ttl_days()
+            {
+                other.summary_cache_ttl_days
+            } else {
+                self.summary_cache_ttl_days
+            },
+            summary_cache_max_entries: if other.summary_cache_max_entries
+                != default_summary_cache_max_entries()
+            {
+                other.summary_cache_max_entries
+            } else {
+                self.summary_cache_max_entries
+            },
             // Provider settings
             provider_order: other
                 .provider_order
@@ -301,6 +548,33 @@ impl AppConfig {
                 .or_else(|| self.provider_sort.clone()),
             provider_require_parameters: other.provider_require_parameters
                 || self.provider_require_parameters,
+            // HTTP server settings
+            http_bearer_token: other
+                .http_bearer_token
+                .clone()
+                .or_else(|| self.http_bearer_token.clone()),
+            // Write settings
+            allow_write: other.allow_write || self.allow_write,
+            changelog_path: other
+                .changelog_path
+                .clone()
+                .or_else(|| self.changelog_path.clone()),
+            write_position: if other.write_position != default_write_position() {
+                other.write_position.clone()
+            } else {
+                self.write_position.clone()
+            },
+            // Multiple indexes
+            indexes: if other.indexes.is_empty() {
+                self.indexes.clone()
+            } else {
+                other.indexes.clone()
+            },
+            summary_prompts: if other.summary_prompts.is_empty() {
+                self.summary_prompts.clone()
+            } else {
+                other.summary_prompts.clone()
+            },
         }
     }
 
@@ -341,6 +615,58 @@ impl AppConfig {
             ));
         }
 
+        let valid_strategies = ["rule-based", "extractive"];
+        if !valid_strategies.contains(&self.summarization_strategy.as_str()) {
+            return Err(anyhow!(
+                "Invalid summarization strategy '{}'. Valid strategies: {:?}",
+                self.summarization_strategy,
+                valid_strategies
+            ));
+        }
+
+        if self.summarization_max_tokens_limit == 0 {
+            return Err(anyhow!(
+                "summarization_max_tokens_limit must be greater than 0"
+            ));
+        }
+        if self.summarization_max_temperature < 0.0 {
+            return Err(anyhow!(
+                "summarization_max_temperature must not be negative"
+            ));
+        }
+
+        self.write_position.parse::<WritePosition>()?;
+
+        // Validate named indexes
+        let mut names = std::collections::HashSet::new();
+        for index in &self.indexes {
+            if index.name.is_empty() || index.name == "all" {
+                return Err(anyhow!("Invalid index name '{}'", index.name));
+            }
+            if !names.insert(index.name.as_str()) {
+                return Err(anyhow!("Duplicate index name '{}'", index.name));
+            }
+        }
+
+        // Validate prompt templates
+        let mut prompts = std::collections::HashSet::new();
+        for prompt in &self.summary_prompts {
+            if prompt.name.is_empty() {
+                return Err(anyhow!("Summary prompt name must not be empty"));
+            }
+            if !prompts.insert(prompt.name.as_str()) {
+                return Err(anyhow!("Duplicate summary prompt name '{}'", prompt.name));
+            }
+        }
+        prompts.insert(DEFAULT_PROMPT_NAME);
+        if !prompts.contains(self.summarization_prompt.as_str()) {
+            return Err(anyhow!(
+                "Unknown summarization prompt '{}'. Declared prompts: {:?}",
+                self.summarization_prompt,
+                prompts
+            ));
+        }
+
         Ok(())
     }
 
@@ -400,6 +726,51 @@ impl AppConfig {
         self.summarization_temperature
     }
 
+    pub fn summarization_strategy(&self) -> &str {
+        &self.summarization_strategy
+    }
+
+    pub fn summarization_prompt(&self) -> &str {
+        &self.summarization_prompt
+    }
+
+    pub fn summarization_language(&self) -> Option<&str> {
+        self.summarization_language.as_deref()
+    }
+
+    pub fn summarization_allowed_models(&self) -> &[String] {
+        &self.summarization_allowed_models
+    }
+
+    pub fn summarization_max_tokens_limit(&self) -> usize {
+        self.summarization_max_tokens_limit
+    }
+
+    pub fn summarization_max_temperature(&self) -> f32 {
+        self.summarization_max_temperature
+    }
+
+    pub fn summary_prompts(&self) -> &[SummaryPrompt] {
+        &self.summary_prompts
+    }
+
+    // Getters - Summary cache settings
+    pub fn summary_cache_enabled(&self) -> bool {
+        self.summary_cache_enabled
+    }
+
+    pub fn summary_cache_path(&self) -> Option<&str> {
+        self.summary_cache_path.as_deref()
+    }
+
+    pub fn summary_cache_ttl_days(&self) -> u64 {
+        self.summary_cache_ttl_days
+    }
+
+    pub fn summary_cache_max_entries(&self) -> usize {
+        self.summary_cache_max_entries
+    }
+
     // Getters - Provider settings
     pub fn provider_order(&self) -> Option<Vec<String>> {
         self.provider_order.clone()
@@ -424,6 +795,42 @@ impl AppConfig {
     pub fn provider_require_parameters(&self) -> bool {
         self.provider_require_parameters
     }
+
+    // Getters - HTTP server settings
+    pub fn http_bearer_token(&self) -> Option<&str> {
+        self.http_bearer_token.as_deref()
+    }
+
+    // Getters - Write settings
+    pub fn allow_write(&self) -> bool {
+        self.allow_write
+    }
+
+    pub fn changelog_path(&self) -> Option<&str> {
+        self.changelog_path.as_deref()
+    }
+
+    pub fn write_position(&self) -> WritePosition {
+        self.write_position.parse().unwrap_or_default()
+    }
+
+    // Getters - Multiple indexes
+    pub fn indexes(&self) -> &[NamedIndex] {
+        &self.indexes
+    }
+
+    /// Indexes to serve: the declared named indexes, or `index_dir` as
+    /// [`DEFAULT_INDEX_NAME`] when none are declared
+    pub fn resolved_indexes(&self) -> Vec<NamedIndex> {
+        if self.indexes.is_empty() {
+            vec![NamedIndex {
+                name: DEFAULT_INDEX_NAME.to_string(),
+                path: self.index_dir.clone(),
+            }]
+        } else {
+            self.indexes.clone()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -444,6 +851,111 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_http_bearer_token_from_toml() {
+        let config: AppConfig = toml::from_str(r#"http_bearer_token = "secret""#).unwrap();
+        assert_eq!(config.http_bearer_token(), Some("secret"));
+
+        let merged = config.merge_with(&AppConfig::default());
+        assert_eq!(merged.http_bearer_token(), Some("secret"));
+        assert_eq!(AppConfig::default().http_bearer_token(), None);
+    }
+
+    #[test]
+    fn test_named_indexes_from_toml() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [[indexes]]
+            name = "changelog"
+            path = "~/.rag"
+
+            [[indexes]]
+            name = "wiki"
+            path = "~/wiki/.rag"
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        let names: Vec<String> = config
+            .resolved_indexes()
+            .into_iter()
+            .map(|i| i.name)
+            .collect();
+        assert_eq!(names, vec!["changelog", "wiki"]);
+        assert!(config.to_toml().unwrap().contains("[[indexes]]"));
+
+        let default = AppConfig::default().resolved_indexes();
+        assert_eq!(default.len(), 1);
+        assert_eq!(default[0].name, DEFAULT_INDEX_NAME);
+        assert_eq!(default[0].path, ".rag");
+    }
+
+    #[test]
+    fn test_validate_duplicate_index_names() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            indexes = [{ name = "wiki", path = "a" }, { name = "wiki", path = "b" }]
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_summary_prompts_from_toml() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            summarization_prompt = "brief"
+            summarization_language = "English"
+
+            [[summary_prompts]]
+            name = "brief"
+            system = "Summarize '{title}' in one sentence."
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.summarization_prompt(), "brief");
+        assert_eq!(config.summarization_language(), Some("English"));
+        assert_eq!(config.summary_prompts()[0].user, "{content}");
+        assert!(config.to_toml().unwrap().contains("[[summary_prompts]]"));
+
+        let config: AppConfig = toml::from_str(r#"summarization_prompt = "missing""#).unwrap();
+        assert!(config.validate().is_err());
+
+        let config: AppConfig = toml::from_str(
+            r#"
+            summary_prompts = [{ name = "a", system = "x" }, { name = "a", system = "y" }]
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_write_settings_from_toml() {
+        let config = AppConfig::default();
+        assert!(!config.allow_write());
+        assert!(config.changelog_path().is_none());
+        assert_eq!(config.write_position(), WritePosition::Prepend);
+
+        let config: AppConfig = toml::from_str(
+            r#"
+allow_write = true
+changelog_path = "~/memo/changelog.md"
+write_position = "append"
+"#,
+        )
+        .unwrap();
+        assert!(config.allow_write());
+        assert_eq!(config.changelog_path(), Some("~/memo/changelog.md"));
+        assert_eq!(config.write_position(), WritePosition::Append);
+        assert!(config.validate().is_ok());
+
+        let config: AppConfig = toml::from_str(r#"write_position = "middle""#).unwrap();
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_validate_invalid_top_k() {
         let config = AppConfig::default().with_default_top_k(0);
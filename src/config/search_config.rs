@@ -17,6 +17,17 @@ pub enum SearchMode {
     Hybrid,
 }
 
+impl SearchMode {
+    /// Parse a mode name ("bm25", "semantic" or "hybrid"), falling back to BM25
+    pub fn parse(mode: &str) -> Self {
+        match mode {
+            "semantic" => Self::Semantic,
+            "hybrid" => Self::Hybrid,
+            _ => Self::Bm25,
+        }
+    }
+}
+
 /// Search configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchConfig {
@@ -26,12 +37,27 @@ pub struct SearchConfig {
     pub top_k: usize,
     /// Optional tag filter
     pub tag_filter: Option<String>,
+    /// Optional topic filter (topic ID from `digrag topics`)
+    #[serde(default)]
+    pub topic_filter: Option<usize>,
     /// Enable query rewriting
     pub enable_rewrite: bool,
     /// BM25 weight for hybrid search (0.0 to 1.0)
     pub bm25_weight: f32,
     /// Semantic weight for hybrid search (0.0 to 1.0)
     pub semantic_weight: f32,
+    /// MMR lambda for result diversification (None = disabled, 1.0 = pure relevance)
+    #[serde(default)]
+    pub mmr_lambda: Option<f32>,
+    /// Number of results to skip (ignored when a cursor is given)
+    #[serde(default)]
+    pub offset: usize,
+    /// Opaque cursor returned with the previous page
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Return only the best-ranked memo of each group of near-duplicates
+    #[serde(default)]
+    pub collapse_duplicates: bool,
 }
 
 impl Default for SearchConfig {
@@ -40,9 +66,14 @@ impl Default for SearchConfig {
             search_mode: SearchMode::default(),
             top_k: 10,
             tag_filter: None,
+            topic_filter: None,
             enable_rewrite: true,
             bm25_weight: 0.5,
             semantic_weight: 0.5,
+            mmr_lambda: None,
+            offset: 0,
+            cursor: None,
+            collapse_duplicates: false,
         }
     }
 }
@@ -71,11 +102,41 @@ impl SearchConfig {
         self
     }
 
+    /// Set the topic filter
+    pub fn with_topic_filter(mut self, topic: Option<usize>) -> Self {
+        self.topic_filter = topic;
+        self
+    }
+
     /// Set whether to enable query rewriting
     pub fn with_rewrite(mut self, enable: bool) -> Self {
         self.enable_rewrite = enable;
         self
     }
+
+    /// Set the MMR lambda (None disables diversification)
+    pub fn with_mmr_lambda(mut self, lambda: Option<f32>) -> Self {
+        self.mmr_lambda = lambda;
+        self
+    }
+
+    /// Set the number of results to skip
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Set the page cursor
+    pub fn with_cursor(mut self, cursor: Option<String>) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    /// Set whether near-duplicate memos are collapsed
+    pub fn with_collapse_duplicates(mut self, collapse: bool) -> Self {
+        self.collapse_duplicates = collapse;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -94,6 +155,9 @@ mod tests {
         assert_eq!(config.top_k, 10);
         assert!(config.tag_filter.is_none());
         assert!(config.enable_rewrite);
+        assert!(config.mmr_lambda.is_none());
+        assert_eq!(config.offset, 0);
+        assert!(config.cursor.is_none());
     }
 
     #[test]
@@ -102,12 +166,18 @@ mod tests {
             .with_mode(SearchMode::Bm25)
             .with_top_k(5)
             .with_tag_filter(Some("worklog".to_string()))
-            .with_rewrite(false);
+            .with_rewrite(false)
+            .with_mmr_lambda(Some(0.5))
+            .with_offset(10)
+            .with_cursor(Some("abc".to_string()));
 
         assert_eq!(config.search_mode, SearchMode::Bm25);
         assert_eq!(config.top_k, 5);
         assert_eq!(config.tag_filter, Some("worklog".to_string()));
         assert!(!config.enable_rewrite);
+        assert_eq!(config.mmr_lambda, Some(0.5));
+        assert_eq!(config.offset, 10);
+        assert_eq!(config.cursor, Some("abc".to_string()));
     }
 
     #[test]
@@ -192,6 +192,30 @@ pub struct CachedSummary {
     pub tokens_used: Option<usize>,
 }
 
+/// LLM settings a summary is generated with, part of its cache key
+///
+/// A request for longer summaries or another temperature must not get back
+/// a summary made with different settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationParams<'a> {
+    /// Model identifier
+    pub model: &'a str,
+    /// Maximum tokens of the summary
+    pub max_tokens: usize,
+    /// Temperature for generation
+    pub temperature: f32,
+}
+
+impl GenerationParams<'_> {
+    /// The settings as one key component
+    pub fn fingerprint(&self) -> String {
+        format!(
+            "{}\0max_tokens={}\0temperature={}",
+            self.model, self.max_tokens, self.temperature
+        )
+    }
+}
+
 /// Summarization cache
 pub type SummaryCache = LruCache<CachedSummary>;
 
@@ -213,6 +237,38 @@ impl SummaryCache {
         let key = Self::generate_key(content, model);
         self.insert(key, summary);
     }
+
+    /// Get the cached digest of a set of sources
+    ///
+    /// Sources are identified by their [`DigestSource::cache_key`]: the
+    /// document ID and a hash of the extracted text. Their order does not
+    /// matter, and an edited memo or another extraction budget yields a
+    /// new key.
+    ///
+    /// [`DigestSource::cache_key`]: super::summarizer::DigestSource::cache_key
+    pub fn get_digest(&self, sources: &[&str], params: &GenerationParams) -> Option<CachedSummary> {
+        self.get(&Self::digest_key(sources, params))
+    }
+
+    /// Cache the digest of a set of sources
+    pub fn cache_digest(
+        &self,
+        sources: &[&str],
+        params: &GenerationParams,
+        summary: CachedSummary,
+    ) {
+        self.insert(Self::digest_key(sources, params), summary);
+    }
+
+    fn digest_key(sources: &[&str], params: &GenerationParams) -> String {
+        let mut sources = sources.to_vec();
+        sources.sort_unstable();
+        sources.dedup();
+        Self::generate_key(
+            &format!("digest:{}", sources.join("\n")),
+            &params.fingerprint(),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -298,6 +354,36 @@ mod tests {
         assert_eq!(retrieved.unwrap().text, "This is a summary");
     }
 
+    #[test]
+    fn test_digest_key_ignores_order() {
+        let cache = SummaryCache::for_summaries();
+        let summary = CachedSummary {
+            text: "Digest".to_string(),
+            model: "test-model".to_string(),
+            tokens_used: None,
+        };
+
+        let params = GenerationParams {
+            model: "test-model",
+            max_tokens: 500,
+            temperature: 0.3,
+        };
+        cache.cache_digest(&["b", "a"], &params, summary);
+
+        assert!(cache.get_digest(&["a", "b"], &params).is_some());
+        assert!(cache.get_digest(&["a"], &params).is_none());
+        let other_model = GenerationParams {
+            model: "other-model",
+            ..params
+        };
+        assert!(cache.get_digest(&["a", "b"], &other_model).is_none());
+        let longer = GenerationParams {
+            max_tokens: 1500,
+            ..params
+        };
+        assert!(cache.get_digest(&["a", "b"], &longer).is_none());
+    }
+
     #[test]
     fn test_cache_clear() {
         let cache: LruCache<String> = LruCache::new(10, Duration::from_secs(60));
@@ -0,0 +1,214 @@
+//! Extractive summarization
+//!
+//! Summarizes a text without an API by picking its most representative
+//! sentences. Sentences are split on Japanese (`。！？`) and English
+//! sentence endings, tokenized with [`JapaneseTokenizer`], and scored by
+//! their cosine similarity to the centroid of the whole text. The best
+//! sentences that fit in a character budget are returned in their
+//! original order.
+//!
+//! Loading the dictionary is slow, so the tokenizer is created on first use
+//! and shared by every summarizer of the process.
+
+use crate::tokenizer::JapaneseTokenizer;
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Default character budget of an extractive summary
+pub const DEFAULT_EXTRACTIVE_CHARS: usize = 200;
+
+/// Picks the most central sentences of a text
+pub struct ExtractiveSummarizer {
+    tokenizer: Arc<JapaneseTokenizer>,
+    max_chars: usize,
+}
+
+impl ExtractiveSummarizer {
+    /// Create a summarizer with a character budget
+    ///
+    /// The first call loads the tokenizer; later calls reuse it.
+    pub fn new(max_chars: usize) -> Result<Self> {
+        let tokenizer = JapaneseTokenizer::shared()?;
+        Ok(Self {
+            tokenizer,
+            max_chars,
+        })
+    }
+
+    /// Character budget of a summary
+    pub fn max_chars(&self) -> usize {
+        self.max_chars
+    }
+
+    /// Top sentences within the budget, in document order
+    ///
+    /// A text whose first-ranked sentence alone exceeds the budget yields
+    /// that sentence cut to the budget.
+    pub fn summarize(&self, text: &str) -> Vec<String> {
+        let sentences = split_sentences(text);
+        let tokens: Vec<Vec<String>> = sentences
+            .iter()
+            .map(|s| self.tokenizer.tokenize_with_english(s).unwrap_or_default())
+            .collect();
+        let scores = centroid_scores(&tokens);
+
+        let mut ranked: Vec<usize> = (0..sentences.len()).collect();
+        ranked.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(a.cmp(&b)));
+
+        let mut selected = Vec::new();
+        let mut remaining = self.max_chars;
+        for i in ranked {
+            let chars = sentences[i].chars().count();
+            if chars <= remaining {
+                selected.push(i);
+                remaining -= chars;
+            } else if selected.is_empty() {
+                return vec![sentences[i].chars().take(self.max_chars).collect()];
+            }
+        }
+        selected.sort_unstable();
+        selected
+            .into_iter()
+            .map(|i| sentences[i].to_string())
+            .collect()
+    }
+}
+
+/// Split text into trimmed sentences
+///
+/// Sentences end at `。！？`, at `.!?` followed by whitespace, and at line
+/// breaks, so bullet lists and headings become sentences of their own.
+pub fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let end = match c {
+            '。' | '！' | '？' | '\n' => true,
+            '.' | '!' | '?' => chars.peek().is_none_or(|(_, next)| next.is_whitespace()),
+            _ => false,
+        };
+        if end {
+            let boundary = i + c.len_utf8();
+            push_sentence(&mut sentences, &text[start..boundary]);
+            start = boundary;
+        }
+    }
+    push_sentence(&mut sentences, &text[start..]);
+    sentences
+}
+
+fn push_sentence<'a>(sentences: &mut Vec<&'a str>, sentence: &'a str) {
+    let sentence = sentence.trim();
+    if !sentence.is_empty() {
+        sentences.push(sentence);
+    }
+}
+
+/// Cosine similarity of each sentence to the centroid of all sentences
+///
+/// Terms are weighted by their frequency in the text and by an inverse
+/// sentence frequency, so words that appear in every sentence count less.
+fn centroid_scores(sentences: &[Vec<String>]) -> Vec<f32> {
+    let n = sentences.len() as f32;
+    let mut sentence_freq: HashMap<&str, usize> = HashMap::new();
+    let mut term_freq: HashMap<&str, usize> = HashMap::new();
+    for tokens in sentences {
+        for token in tokens {
+            *term_freq.entry(token).or_insert(0) += 1;
+        }
+        for token in tokens.iter().map(String::as_str).collect::<HashSet<_>>() {
+            *sentence_freq.entry(token).or_insert(0) += 1;
+        }
+    }
+
+    let weight = |token: &str| (1.0 + n / sentence_freq[token] as f32).ln();
+    let centroid: HashMap<&str, f32> = term_freq
+        .iter()
+        .map(|(&token, &tf)| (token, tf as f32 * weight(token)))
+        .collect();
+    let centroid_norm = centroid.values().map(|w| w * w).sum::<f32>().sqrt();
+
+    sentences
+        .iter()
+        .map(|tokens| {
+            let mut vector: HashMap<&str, f32> = HashMap::new();
+            for token in tokens {
+                *vector.entry(token).or_insert(0.0) += weight(token);
+            }
+            let norm = vector.values().map(|w| w * w).sum::<f32>().sqrt();
+            if norm == 0.0 || centroid_norm == 0.0 {
+                return 0.0;
+            }
+            let dot: f32 = vector.iter().map(|(t, w)| w * centroid[t]).sum();
+            dot / (norm * centroid_norm)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(sentence: &str) -> Vec<String> {
+        sentence.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        assert_eq!(
+            split_sentences("デプロイした。失敗！ 原因は？\n- v1.2 に戻す. Done"),
+            vec![
+                "デプロイした。",
+                "失敗！",
+                "原因は？",
+                "- v1.2 に戻す.",
+                "Done"
+            ]
+        );
+        assert!(split_sentences("  \n ").is_empty());
+    }
+
+    #[test]
+    fn test_centroid_scores_prefer_central_sentences() {
+        let scores = centroid_scores(&[
+            tokens("deploy server"),
+            tokens("lunch pizza"),
+            tokens("deploy server rollback"),
+            tokens("server rollback"),
+        ]);
+        assert!(scores[2] > scores[0]);
+        assert!(scores[0] > scores[1]);
+        assert_eq!(centroid_scores(&[Vec::new()]), vec![0.0]);
+    }
+
+    #[test]
+    fn test_summarize_within_budget() {
+        let summarizer = ExtractiveSummarizer::new(80).unwrap();
+        let text = "Lunch was pizza. The deploy of the API server failed. \
+                    We rolled back the API server deploy. The API server is stable again.";
+        let summary = summarizer.summarize(text);
+
+        assert_eq!(summary.len(), 2);
+        assert!(summary.iter().map(|s| s.chars().count()).sum::<usize>() <= 80);
+        assert!(!summary.iter().any(|s| s.starts_with("Lunch")));
+        // Selected sentences keep their original order
+        let positions: Vec<usize> = summary.iter().map(|s| text.find(s).unwrap()).collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+
+        let long = ExtractiveSummarizer::new(5)
+            .unwrap()
+            .summarize("Deployed everything.");
+        assert_eq!(long, vec!["Deplo".to_string()]);
+    }
+
+    #[test]
+    fn test_tokenizer_loaded_once() {
+        let short = ExtractiveSummarizer::new(50).unwrap();
+        let long = ExtractiveSummarizer::new(500).unwrap();
+        assert!(Arc::ptr_eq(&short.tokenizer, &long.tokenizer));
+        assert_eq!(long.max_chars(), 500);
+    }
+}
@@ -7,7 +7,10 @@
 
 pub mod cache;
 pub mod changelog;
+pub mod extractive;
 pub mod openrouter_client;
+pub mod persistent_cache;
+pub mod prompt;
 pub mod summarizer;
 pub mod telemetry;
 
@@ -22,6 +25,17 @@ pub enum ExtractionStrategy {
     Full,
 }
 
+impl ExtractionStrategy {
+    /// Parse a mode name ("snippet", "entry" or "full"), falling back to a 150-char snippet
+    pub fn from_mode(mode: &str) -> Self {
+        match mode {
+            "entry" => Self::ChangelogEntry,
+            "full" => Self::Full,
+            _ => Self::Head(150),
+        }
+    }
+}
+
 /// Truncation configuration
 #[derive(Debug, Clone)]
 pub struct TruncationConfig {
@@ -0,0 +1,399 @@
+//! Persistent summary cache
+//!
+//! SQLite-based cache for LLM summaries and digests, so that restarts of
+//! `digrag serve` and separate CLI invocations do not pay for the same
+//! summary twice. Entries are keyed on a hash of the content (or the set of
+//! digest sources), the generation settings and the prompt version, expire
+//! after a TTL, and the least recently used ones are evicted above a size
+//! cap.
+
+use super::cache::{CachedSummary, GenerationParams};
+use crate::config::app_config::AppConfig;
+use crate::config::path_resolver;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default TTL for cache entries (30 days)
+pub const DEFAULT_SUMMARY_CACHE_TTL_DAYS: u64 = 30;
+
+/// Default maximum number of cached summaries
+pub const DEFAULT_SUMMARY_CACHE_MAX_ENTRIES: usize = 10_000;
+
+/// File name of the cache under the digrag cache directory
+const SUMMARY_CACHE_FILE: &str = "summaries.db";
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS summaries (
+    key TEXT PRIMARY KEY,
+    model TEXT NOT NULL,
+    prompt_version TEXT NOT NULL,
+    text TEXT NOT NULL,
+    tokens_used INTEGER,
+    created_at INTEGER NOT NULL,
+    accessed_at INTEGER NOT NULL
+)";
+
+/// Statistics of the persistent cache
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PersistentCacheStats {
+    /// Number of cached summaries, including expired ones
+    pub entries: usize,
+    /// Number of entries older than the TTL
+    pub expired: usize,
+    /// Total characters of cached summary text
+    pub text_chars: usize,
+    /// Tokens spent on the cached summaries
+    pub tokens: usize,
+    /// Creation time of the oldest entry
+    pub oldest: Option<DateTime<Utc>>,
+    /// Creation time of the newest entry
+    pub newest: Option<DateTime<Utc>>,
+    /// Entries per model, most frequent first
+    pub models: Vec<(String, usize)>,
+}
+
+/// SQLite-backed summary cache
+pub struct PersistentSummaryCache {
+    conn: Mutex<Connection>,
+    path: Option<PathBuf>,
+    ttl: Duration,
+    max_entries: usize,
+}
+
+impl PersistentSummaryCache {
+    /// Open (or create) a cache file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut cache = Self::with_connection(Connection::open(path)?)?;
+        cache.path = Some(path.to_path_buf());
+        Ok(cache)
+    }
+
+    /// Create an in-memory cache (for testing)
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute(CREATE_TABLE, [])?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            path: None,
+            ttl: Duration::from_secs(DEFAULT_SUMMARY_CACHE_TTL_DAYS * 24 * 60 * 60),
+            max_entries: DEFAULT_SUMMARY_CACHE_MAX_ENTRIES,
+        })
+    }
+
+    /// Open the cache configured by the `summary_cache_*` settings
+    ///
+    /// Returns None when `summary_cache_enabled` is false.
+    pub fn from_config(config: &AppConfig) -> Result<Option<Self>> {
+        if !config.summary_cache_enabled() {
+            return Ok(None);
+        }
+        let cache = Self::open(Self::config_path(config)?)?
+            .with_ttl(Duration::from_secs(
+                config.summary_cache_ttl_days() * 24 * 60 * 60,
+            ))
+            .with_max_entries(config.summary_cache_max_entries());
+        Ok(Some(cache))
+    }
+
+    /// Cache file of a config: `summary_cache_path`, or `summaries.db` in
+    /// the digrag cache directory
+    pub fn config_path(config: &AppConfig) -> Result<PathBuf> {
+        match config.summary_cache_path() {
+            Some(path) => path_resolver::resolve_path(path),
+            None => Ok(path_resolver::get_cache_dir().join(SUMMARY_CACHE_FILE)),
+        }
+    }
+
+    /// Set custom TTL
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the maximum number of entries
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Path of the cache file (None for in-memory caches)
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Key of a summary of `content`
+    pub fn key(content: &str, params: &GenerationParams, prompt_version: &str) -> String {
+        let mut hasher = Sha256::new();
+        for part in [content, &params.fingerprint(), prompt_version] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Key of a digest of a set of sources, independent of their order
+    ///
+    /// Sources are [`DigestSource::cache_key`]s, which cover the extracted
+    /// text as well as the document ID.
+    ///
+    /// [`DigestSource::cache_key`]: super::summarizer::DigestSource::cache_key
+    pub fn digest_key(sources: &[&str], params: &GenerationParams, prompt_version: &str) -> String {
+        let mut sources = sources.to_vec();
+        sources.sort_unstable();
+        sources.dedup();
+        Self::key(
+            &format!("digest:{}", sources.join("\n")),
+            params,
+            prompt_version,
+        )
+    }
+
+    /// Get a cached summary that has not expired
+    pub fn get(&self, key: &str) -> Result<Option<CachedSummary>> {
+        let now = now_secs();
+        let conn = self.conn.lock().unwrap();
+        let summary = conn
+            .query_row(
+                "SELECT text, model, tokens_used FROM summaries WHERE key = ? AND created_at > ?",
+                params![key, now - self.ttl_secs()],
+                |row| {
+                    Ok(CachedSummary {
+                        text: row.get(0)?,
+                        model: row.get(1)?,
+                        tokens_used: row.get::<_, Option<i64>>(2)?.map(|t| t as usize),
+                    })
+                },
+            )
+            .optional()?;
+        if summary.is_some() {
+            conn.execute(
+                "UPDATE summaries SET accessed_at = ? WHERE key = ?",
+                params![now, key],
+            )?;
+        }
+        Ok(summary)
+    }
+
+    /// Cache a summary, evicting the least recently used entries above the cap
+    pub fn insert(&self, key: &str, prompt_version: &str, summary: &CachedSummary) -> Result<()> {
+        let now = now_secs();
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO summaries
+                (key, model, prompt_version, text, tokens_used, created_at, accessed_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            params![
+                key,
+                summary.model,
+                prompt_version,
+                summary.text,
+                summary.tokens_used.map(|t| t as i64),
+                now,
+                now
+            ],
+        )?;
+        conn.execute(
+            "DELETE FROM summaries WHERE key NOT IN
+                (SELECT key FROM summaries ORDER BY accessed_at DESC, rowid DESC LIMIT ?)",
+            params![self.max_entries as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Remove expired entries
+    pub fn cleanup(&self) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn.execute(
+            "DELETE FROM summaries WHERE created_at <= ?",
+            params![now_secs() - self.ttl_secs()],
+        )?;
+        Ok(deleted)
+    }
+
+    /// Remove all entries
+    pub fn clear(&self) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn.execute("DELETE FROM summaries", [])?;
+        conn.execute("VACUUM", [])?;
+        Ok(deleted)
+    }
+
+    /// Get cache size
+    pub fn size(&self) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM summaries", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Get cache statistics
+    pub fn stats(&self) -> Result<PersistentCacheStats> {
+        let conn = self.conn.lock().unwrap();
+        let (entries, expired, text_chars, tokens, oldest, newest) = conn.query_row(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(created_at <= ?), 0),
+                    COALESCE(SUM(LENGTH(text)), 0),
+                    COALESCE(SUM(tokens_used), 0),
+                    MIN(created_at),
+                    MAX(created_at)
+             FROM summaries",
+            params![now_secs() - self.ttl_secs()],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, Option<i64>>(4)?,
+                    row.get::<_, Option<i64>>(5)?,
+                ))
+            },
+        )?;
+
+        let mut statement = conn.prepare(
+            "SELECT model, COUNT(*) AS n FROM summaries GROUP BY model ORDER BY n DESC, model",
+        )?;
+        let models = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(PersistentCacheStats {
+            entries: entries as usize,
+            expired: expired as usize,
+            text_chars: text_chars as usize,
+            tokens: tokens as usize,
+            oldest: oldest.and_then(|t| DateTime::from_timestamp(t, 0)),
+            newest: newest.and_then(|t| DateTime::from_timestamp(t, 0)),
+            models,
+        })
+    }
+
+    fn ttl_secs(&self) -> i64 {
+        self.ttl.as_secs() as i64
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(text: &str, model: &str) -> CachedSummary {
+        CachedSummary {
+            text: text.to_string(),
+            model: model.to_string(),
+            tokens_used: Some(100),
+        }
+    }
+
+    fn params(model: &str) -> GenerationParams<'_> {
+        GenerationParams {
+            model,
+            max_tokens: 500,
+            temperature: 0.3,
+        }
+    }
+
+    #[test]
+    fn test_set_and_get() {
+        let cache = PersistentSummaryCache::in_memory().unwrap();
+        let key = PersistentSummaryCache::key("content", &params("model-a"), "1");
+
+        cache
+            .insert(&key, "1", &summary("Summary", "model-a"))
+            .unwrap();
+
+        let cached = cache.get(&key).unwrap().unwrap();
+        assert_eq!(cached.text, "Summary");
+        assert_eq!(cached.tokens_used, Some(100));
+        assert!(cache.get("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_key_depends_on_model_and_prompt_version() {
+        let key = PersistentSummaryCache::key("content", &params("model-a"), "1");
+        assert_ne!(
+            key,
+            PersistentSummaryCache::key("content", &params("model-b"), "1")
+        );
+        assert_ne!(
+            key,
+            PersistentSummaryCache::key("content", &params("model-a"), "2")
+        );
+        let longer = GenerationParams {
+            max_tokens: 1500,
+            ..params("model-a")
+        };
+        assert_ne!(key, PersistentSummaryCache::key("content", &longer, "1"));
+        let warmer = GenerationParams {
+            temperature: 0.9,
+            ..params("model-a")
+        };
+        assert_ne!(key, PersistentSummaryCache::key("content", &warmer, "1"));
+        assert_eq!(
+            PersistentSummaryCache::digest_key(&["b", "a"], &params("model-a"), "1"),
+            PersistentSummaryCache::digest_key(&["a", "b"], &params("model-a"), "1")
+        );
+    }
+
+    #[test]
+    fn test_ttl_and_cleanup() {
+        let cache = PersistentSummaryCache::in_memory()
+            .unwrap()
+            .with_ttl(Duration::from_secs(0));
+
+        cache
+            .insert("k", "1", &summary("Summary", "model-a"))
+            .unwrap();
+
+        assert!(cache.get("k").unwrap().is_none());
+        assert_eq!(cache.stats().unwrap().expired, 1);
+        assert_eq!(cache.cleanup().unwrap(), 1);
+        assert_eq!(cache.size().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_max_entries_and_stats() {
+        let cache = PersistentSummaryCache::in_memory()
+            .unwrap()
+            .with_max_entries(2);
+
+        cache.insert("k1", "1", &summary("one", "model-a")).unwrap();
+        cache.insert("k2", "1", &summary("two", "model-b")).unwrap();
+        cache
+            .insert("k3", "1", &summary("three", "model-b"))
+            .unwrap();
+
+        assert_eq!(cache.size().unwrap(), 2);
+        assert!(cache.get("k1").unwrap().is_none());
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.text_chars, 8);
+        assert_eq!(stats.tokens, 200);
+        assert_eq!(stats.models, vec![("model-b".to_string(), 2)]);
+        assert!(stats.oldest.is_some());
+
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert_eq!(cache.stats().unwrap(), PersistentCacheStats::default());
+    }
+}
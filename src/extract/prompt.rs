@@ -0,0 +1,283 @@
+//! Summarization prompt templates
+//!
+//! Per-result LLM summaries are built from a [`PromptTemplate`]: a system
+//! prompt and a user message with `{title}`, `{date}`, `{tags}`,
+//! `{content}`, `{query}` and `{language}` placeholders. Templates are
+//! declared with `[[summary_prompts]]` in config.toml; the built-in
+//! "default" template is always available.
+//!
+//! Every template has a version derived from its text, which is part of the
+//! summary cache keys, so editing a template never serves summaries made
+//! with the old one.
+
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+
+use super::ExtractedContent;
+use crate::config::app_config::AppConfig;
+
+/// Name of the built-in template
+pub const DEFAULT_PROMPT_NAME: &str = "default";
+
+const DEFAULT_SYSTEM_PROMPT: &str =
+    "以下のテキストを簡潔に要約してください。重要なポイントを箇条書きで抽出してください。";
+
+const DEFAULT_USER_PROMPT: &str = "{content}";
+
+/// Document and request details available to a template
+#[derive(Debug, Clone, Default)]
+pub struct SummaryContext {
+    /// Memo title
+    pub title: String,
+    /// Memo date (YYYY-MM-DD)
+    pub date: String,
+    /// Memo tags
+    pub tags: Vec<String>,
+    /// Search query the memo was found with
+    pub query: Option<String>,
+}
+
+/// A summarization prompt with placeholders
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    name: String,
+    system: String,
+    user: String,
+    language: Option<String>,
+}
+
+impl Default for PromptTemplate {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_PROMPT_NAME,
+            DEFAULT_SYSTEM_PROMPT,
+            DEFAULT_USER_PROMPT,
+        )
+    }
+}
+
+impl PromptTemplate {
+    /// Create a template from a system prompt and a user message
+    pub fn new(name: &str, system: &str, user: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            system: system.to_string(),
+            user: user.to_string(),
+            language: None,
+        }
+    }
+
+    /// Write summaries in the given language
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language.filter(|l| !l.trim().is_empty());
+        self
+    }
+
+    /// Template name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Target language, if any
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Short hash of the template text and language
+    pub fn version(&self) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            self.system.as_str(),
+            self.user.as_str(),
+            self.language.as_deref().unwrap_or(""),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        hex::encode(&hasher.finalize()[..8])
+    }
+
+    /// System prompt and user message for a memo
+    ///
+    /// When a language is set but the system prompt has no `{language}`
+    /// placeholder, an instruction to answer in that language is appended.
+    pub fn render(&self, content: &ExtractedContent, context: &SummaryContext) -> (String, String) {
+        let tags = context.tags.join(", ");
+        let language = self.language.as_deref().unwrap_or("");
+        let vars = [
+            ("title", context.title.as_str()),
+            ("date", context.date.as_str()),
+            ("tags", tags.as_str()),
+            ("content", content.text.as_str()),
+            ("query", context.query.as_deref().unwrap_or("")),
+            ("language", language),
+        ];
+
+        let mut system = substitute(&self.system, &vars);
+        if !language.is_empty() && !self.system.contains("{language}") {
+            system.push_str(&format!("\n{}で回答してください。", language));
+        }
+        (system, substitute(&self.user, &vars))
+    }
+}
+
+/// Replace `{name}` placeholders in one pass
+///
+/// Values are not scanned again, so memo text containing braces is left
+/// untouched, and unknown placeholders are kept as written.
+fn substitute(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        output.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after.find('}').and_then(|close| {
+            let name = &after[..close];
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| (*value, close))
+        });
+        match value {
+            Some((value, close)) => {
+                output.push_str(value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                output.push('{');
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Templates available to a server or command
+#[derive(Debug, Clone)]
+pub struct PromptTemplates {
+    templates: Vec<PromptTemplate>,
+    default: String,
+}
+
+impl Default for PromptTemplates {
+    fn default() -> Self {
+        Self {
+            templates: vec![PromptTemplate::default()],
+            default: DEFAULT_PROMPT_NAME.to_string(),
+        }
+    }
+}
+
+impl PromptTemplates {
+    /// The built-in template plus `[[summary_prompts]]` from config.toml
+    ///
+    /// A declared template named "default" replaces the built-in one.
+    /// `summarization_language` applies to templates without a language.
+    pub fn from_config(config: &AppConfig) -> Self {
+        let language = config.summarization_language().map(str::to_string);
+        let mut templates = Self::default().templates;
+        for prompt in config.summary_prompts() {
+            let template = PromptTemplate::new(&prompt.name, &prompt.system, &prompt.user)
+                .with_language(prompt.language.clone());
+            templates.retain(|t| t.name != template.name);
+            templates.push(template);
+        }
+        for template in &mut templates {
+            if template.language.is_none() {
+                template.language = language.clone();
+            }
+        }
+
+        Self {
+            templates,
+            default: config.summarization_prompt().to_string(),
+        }
+    }
+
+    /// Template by name, or the configured default
+    pub fn get(&self, name: Option<&str>) -> Result<&PromptTemplate> {
+        let name = name.unwrap_or(&self.default);
+        self.templates
+            .iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown summary prompt '{}'. Available prompts: {:?}",
+                    name,
+                    self.names()
+                )
+            })
+    }
+
+    /// Names of all templates
+    pub fn names(&self) -> Vec<&str> {
+        self.templates.iter().map(|t| t.name.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extract::ContentStats;
+
+    fn content(text: &str) -> ExtractedContent {
+        ExtractedContent {
+            text: text.to_string(),
+            truncated: false,
+            stats: ContentStats {
+                total_chars: text.chars().count(),
+                total_lines: 1,
+                extracted_chars: text.chars().count(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_render_substitutes_variables() {
+        let template = PromptTemplate::new(
+            "brief",
+            "Summarize for '{query}' in {language}.",
+            "# {title} ({date}) [{tags}]\n{content} {unknown}",
+        )
+        .with_language(Some("English".to_string()));
+        let context = SummaryContext {
+            title: "Deploy".to_string(),
+            date: "2025-01-15".to_string(),
+            tags: vec!["ops".to_string(), "k8s".to_string()],
+            query: Some("rollback".to_string()),
+        };
+
+        let (system, user) = template.render(&content("Rolled back {title}"), &context);
+        assert_eq!(system, "Summarize for 'rollback' in English.");
+        assert_eq!(
+            user,
+            "# Deploy (2025-01-15) [ops, k8s]\nRolled back {title} {unknown}"
+        );
+    }
+
+    #[test]
+    fn test_language_instruction_appended() {
+        let template = PromptTemplate::default().with_language(Some("English".to_string()));
+        let (system, user) = template.render(&content("本文"), &SummaryContext::default());
+        assert!(system.starts_with(DEFAULT_SYSTEM_PROMPT));
+        assert!(system.ends_with("Englishで回答してください。"));
+        assert_eq!(user, "本文");
+    }
+
+    #[test]
+    fn test_version_changes_with_template() {
+        let default = PromptTemplate::default();
+        assert_eq!(default.version(), PromptTemplate::default().version());
+        assert_ne!(
+            default.version(),
+            PromptTemplate::new(DEFAULT_PROMPT_NAME, "Summarize.", "{content}").version()
+        );
+        assert_ne!(
+            default.version(),
+            default
+                .clone()
+                .with_language(Some("English".to_string()))
+                .version()
+        );
+    }
+}
@@ -2,14 +2,48 @@
 //!
 //! Provides summarization strategies:
 //! - RuleBased: Extract preview + statistics (no API call)
+//! - Extractive: Pick the most central sentences (no API call)
 //! - LlmBased: Use OpenRouter API for LLM summarization
+//!
+//! LLM summaries are built from a [`PromptTemplate`], selectable per call.
+//!
+//! A result set can also be condensed into one digest with
+//! [`ContentSummarizer::digest`], which splits large inputs into chunks
+//! (map) and merges the partial summaries (reduce).
 
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
 use tracing::{debug, info, warn};
 
+use super::cache::{CachedSummary, GenerationParams, SummaryCache};
+use super::extractive::{ExtractiveSummarizer, DEFAULT_EXTRACTIVE_CHARS};
 use super::openrouter_client::{ChatCompletionOptions, ChatMessage, OpenRouterClient};
+use super::persistent_cache::PersistentSummaryCache;
+use super::prompt::{PromptTemplate, PromptTemplates, SummaryContext};
 use super::{ContentStats, ExtractedContent};
+use crate::config::app_config::AppConfig;
+
+/// Version of the digest prompts, part of persistent digest cache keys
+///
+/// Bump it whenever a digest prompt changes so that old digests are not
+/// reused. Per-result summaries are versioned by their [`PromptTemplate`].
+pub const DIGEST_PROMPT_VERSION: &str = "2";
+
+/// Default character budget of one LLM request when building a digest
+pub const DEFAULT_DIGEST_CHUNK_CHARS: usize = 12_000;
+
+const DIGEST_PROMPT: &str = "以下は検索でヒットした複数のメモです。メモ全体を通した要点を簡潔にまとめ、重要なポイントを箇条書きで抽出してください。";
+
+const MERGE_PROMPT: &str = "以下は複数のメモをいくつかに分けて要約した部分要約です。重複をまとめて一つの要約に統合し、重要なポイントを箇条書きで示してください。";
+
+/// Separator between memo sections in a digest request
+const SECTION_SEPARATOR: &str = "\n\n";
+
+/// Separator between partial summaries in a merge request
+const PARTIAL_SEPARATOR: &str = "\n\n---\n\n";
 
 /// OpenRouter provider configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +86,18 @@ impl Default for ProviderConfig {
 }
 
 impl ProviderConfig {
+    /// Provider routing from the `provider_*` settings of config.toml
+    pub fn from_app_config(config: &AppConfig) -> Self {
+        Self {
+            order: config.provider_order(),
+            allow_fallbacks: config.provider_allow_fallbacks(),
+            only: config.provider_only(),
+            ignore: config.provider_ignore(),
+            sort: config.provider_sort(),
+            require_parameters: config.provider_require_parameters(),
+        }
+    }
+
     /// Convert to JSON for API request
     pub fn to_json(&self) -> serde_json::Value {
         let mut obj = serde_json::Map::new();
@@ -90,6 +136,11 @@ pub enum SummarizationStrategy {
         /// Number of preview characters
         preview_chars: usize,
     },
+    /// Extractive summarization (top-ranked sentences)
+    Extractive {
+        /// Character budget of the summary
+        max_chars: usize,
+    },
     /// LLM-based summarization via OpenRouter
     LlmBased {
         /// Model identifier (e.g., "cerebras/llama-3.3-70b")
@@ -109,21 +160,35 @@ impl Default for SummarizationStrategy {
     }
 }
 
+impl SummarizationStrategy {
+    /// Offline strategy named in config.toml: "rule-based" or "extractive"
+    pub fn offline(name: &str) -> Self {
+        match name {
+            "extractive" => Self::Extractive {
+                max_chars: DEFAULT_EXTRACTIVE_CHARS,
+            },
+            _ => Self::default(),
+        }
+    }
+}
+
 /// Summary result
 #[derive(Debug, Clone)]
 pub struct Summary {
     /// Summary text
     pub text: String,
-    /// Method used: "rule-based" or "llm"
+    /// Method used: "rule-based", "extractive" or "llm"
     pub method: String,
+    /// Model that wrote the summary (LLM summaries only)
+    pub model: Option<String>,
     /// Content statistics
     pub stats: ContentStats,
-    /// Token usage (if LLM was used)
+    /// Token usage (if LLM was called; cached summaries have none)
     pub usage: Option<SummaryUsage>,
 }
 
 /// Usage statistics for LLM summarization
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SummaryUsage {
     pub prompt_tokens: usize,
     pub completion_tokens: usize,
@@ -131,10 +196,118 @@ pub struct SummaryUsage {
     pub model: String,
 }
 
+impl SummaryUsage {
+    /// Add the usage of another request
+    fn add(&mut self, other: SummaryUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+        self.model = other.model;
+    }
+}
+
+/// Summarization settings chosen per request, e.g. by a `query_memos` call
+///
+/// Unset fields use config.toml. Overrides must stay within the bounds set
+/// there: the model must be `summarization_model` or listed in
+/// `summarization_allowed_models`, `max_tokens` at most
+/// `summarization_max_tokens_limit` and `temperature` between 0 and
+/// `summarization_max_temperature`.
+#[derive(Debug, Clone, Default)]
+pub struct SummaryOptions {
+    /// Summarize with the LLM (default: `summarization_enabled`)
+    pub use_llm: Option<bool>,
+    /// Model identifier
+    pub model: Option<String>,
+    /// Maximum tokens of each summary
+    pub max_tokens: Option<usize>,
+    /// Temperature for generation
+    pub temperature: Option<f32>,
+}
+
+impl SummaryOptions {
+    /// Check the overrides against the bounds in config.toml
+    pub fn validate(&self, config: &AppConfig) -> Result<()> {
+        if let Some(model) = &self.model {
+            if model != config.summarization_model()
+                && !config.summarization_allowed_models().contains(model)
+            {
+                return Err(anyhow!(
+                    "Model '{}' is not allowed. Allowed models: {:?}",
+                    model,
+                    std::iter::once(config.summarization_model())
+                        .chain(
+                            config
+                                .summarization_allowed_models()
+                                .iter()
+                                .map(String::as_str)
+                        )
+                        .collect::<Vec<_>>()
+                ));
+            }
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            let limit = config.summarization_max_tokens_limit();
+            if max_tokens == 0 || max_tokens > limit {
+                return Err(anyhow!("max_tokens must be between 1 and {}", limit));
+            }
+        }
+        if let Some(temperature) = self.temperature {
+            let limit = config.summarization_max_temperature();
+            if !(0.0..=limit).contains(&temperature) {
+                return Err(anyhow!("temperature must be between 0 and {}", limit));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A document contributing to a combined digest
+#[derive(Debug, Clone)]
+pub struct DigestSource {
+    /// Document ID
+    pub doc_id: String,
+    /// Document title
+    pub title: String,
+    /// Document date (YYYY-MM-DD)
+    pub date: String,
+    /// Extracted content to summarize
+    pub content: ExtractedContent,
+}
+
+impl DigestSource {
+    /// Document ID and a hash of the section given to the model
+    ///
+    /// Digest caches are keyed on these, so the same memos extracted with
+    /// another character budget or extraction mode get a digest of their own.
+    pub fn cache_key(&self) -> String {
+        let hash = Sha256::digest(self.section().as_bytes());
+        format!("{}:{}", self.doc_id, hex::encode(&hash[..8]))
+    }
+
+    /// Heading and text as given to the model
+    fn section(&self) -> String {
+        format!(
+            "## {} ({})\n{}",
+            self.title,
+            self.date,
+            self.content.text.trim()
+        )
+    }
+}
+
 /// Content summarizer
 pub struct ContentSummarizer {
     strategy: SummarizationStrategy,
     client: Option<OpenRouterClient>,
+    /// Sentence ranker for the extractive strategy and offline fallbacks
+    extractive: Option<ExtractiveSummarizer>,
+    cache: Option<Arc<SummaryCache>>,
+    /// On-disk cache of LLM summaries and digests
+    store: Option<Arc<PersistentSummaryCache>>,
+    /// Template of per-result LLM summaries
+    prompt: PromptTemplate,
+    digest_chunk_chars: usize,
 }
 
 impl ContentSummarizer {
@@ -143,15 +316,29 @@ impl ContentSummarizer {
         let client = api_key
             .as_ref()
             .map(|key| OpenRouterClient::new(key.clone()));
-        Self { strategy, client }
+        let extractive = match &strategy {
+            SummarizationStrategy::Extractive { max_chars } => load_extractive(*max_chars),
+            _ => None,
+        };
+        Self {
+            strategy,
+            client,
+            extractive,
+            cache: None,
+            store: None,
+            prompt: PromptTemplate::default(),
+            digest_chunk_chars: DEFAULT_DIGEST_CHUNK_CHARS,
+        }
     }
 
     /// Create a rule-based summarizer
     pub fn rule_based(preview_chars: usize) -> Self {
-        Self {
-            strategy: SummarizationStrategy::RuleBased { preview_chars },
-            client: None,
-        }
+        Self::new(SummarizationStrategy::RuleBased { preview_chars }, None)
+    }
+
+    /// Create an extractive summarizer
+    pub fn extractive(max_chars: usize) -> Self {
+        Self::new(SummarizationStrategy::Extractive { max_chars }, None)
     }
 
     /// Create an LLM-based summarizer
@@ -162,54 +349,140 @@ impl ContentSummarizer {
         provider_config: ProviderConfig,
         api_key: String,
     ) -> Self {
-        let client = OpenRouterClient::new(api_key);
-        Self {
-            strategy: SummarizationStrategy::LlmBased {
+        Self::new(
+            SummarizationStrategy::LlmBased {
                 model,
                 max_tokens,
                 temperature,
                 provider_config,
             },
-            client: Some(client),
-        }
+            Some(api_key),
+        )
+    }
+
+    /// Use a preconfigured OpenRouter client (e.g. with a custom base URL)
+    pub fn with_client(mut self, client: OpenRouterClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Fall back to extractive instead of rule-based summaries when the LLM
+    /// is unavailable
+    pub fn with_extractive_fallback(mut self, max_chars: usize) -> Self {
+        self.extractive = load_extractive(max_chars);
+        self
+    }
+
+    /// Reuse LLM digests from a shared cache
+    pub fn with_cache(mut self, cache: Arc<SummaryCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Keep LLM summaries and digests in an on-disk cache
+    pub fn with_persistent_cache(mut self, store: Arc<PersistentSummaryCache>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Build LLM summaries from a prompt template
+    pub fn with_prompt(mut self, prompt: PromptTemplate) -> Self {
+        self.prompt = prompt;
+        self
+    }
+
+    /// Prompt template of LLM summaries
+    pub fn prompt(&self) -> &PromptTemplate {
+        &self.prompt
+    }
+
+    /// Set the character budget of one LLM request when building a digest
+    pub fn with_digest_chunk_chars(mut self, chars: usize) -> Self {
+        self.digest_chunk_chars = chars.max(1);
+        self
     }
 
     /// Create summarizer from AppConfig
-    pub fn from_config(config: &crate::config::app_config::AppConfig) -> Self {
-        if config.summarization_enabled() {
-            if let Some(api_key) = config.openrouter_api_key() {
-                let provider_config = ProviderConfig {
-                    order: config.provider_order(),
-                    allow_fallbacks: config.provider_allow_fallbacks(),
-                    only: config.provider_only(),
-                    ignore: config.provider_ignore(),
-                    sort: config.provider_sort(),
-                    require_parameters: config.provider_require_parameters(),
-                };
+    ///
+    /// `summarization_strategy` selects the offline summarizer, used when
+    /// LLM summarization is disabled and as its fallback, and
+    /// `summarization_prompt` the prompt template.
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self::offline_or_llm(config, &SummaryOptions::default())
+    }
 
-                return Self::llm_based(
-                    config.summarization_model().to_string(),
-                    config.summarization_max_tokens(),
-                    config.summarization_temperature(),
-                    provider_config,
+    /// Create summarizer from AppConfig with per-request overrides
+    ///
+    /// Fails when an override is outside the bounds set in config.toml.
+    pub fn from_config_with(config: &AppConfig, options: &SummaryOptions) -> Result<Self> {
+        options.validate(config)?;
+        Ok(Self::offline_or_llm(config, options))
+    }
+
+    /// LLM summarizer when enabled and keyed, else the offline one
+    fn offline_or_llm(config: &AppConfig, options: &SummaryOptions) -> Self {
+        let prompt = PromptTemplates::from_config(config)
+            .get(None)
+            .cloned()
+            .unwrap_or_default();
+        let offline = SummarizationStrategy::offline(config.summarization_strategy());
+        if options.use_llm.unwrap_or(config.summarization_enabled()) {
+            if let Some(api_key) = config.openrouter_api_key() {
+                let summarizer = Self::llm_based(
+                    options
+                        .model
+                        .clone()
+                        .unwrap_or_else(|| config.summarization_model().to_string()),
+                    options
+                        .max_tokens
+                        .unwrap_or(config.summarization_max_tokens()),
+                    options
+                        .temperature
+                        .unwrap_or(config.summarization_temperature()),
+                    ProviderConfig::from_app_config(config),
                     api_key,
-                );
+                )
+                .with_prompt(prompt);
+                return match offline {
+                    SummarizationStrategy::Extractive { max_chars } => {
+                        summarizer.with_extractive_fallback(max_chars)
+                    }
+                    _ => summarizer,
+                };
             } else {
                 warn!(
-                    "Summarization enabled but no API key configured, falling back to rule-based"
+                    strategy = config.summarization_strategy(),
+                    "Summarization enabled but no API key configured, falling back to offline summaries"
                 );
             }
         }
 
-        Self::rule_based(200)
+        Self::new(offline, None).with_prompt(prompt)
     }
 
     /// Generate summary (async for LLM, sync-compatible for rule-based)
     pub async fn summarize(&self, content: &ExtractedContent) -> Summary {
+        self.summarize_with(content, &SummaryContext::default())
+            .await
+    }
+
+    /// Generate summary, filling the prompt template with the memo details
+    ///
+    /// LLM summaries are cached by the rendered prompt, the generation
+    /// settings (model, max tokens, temperature) and the template version.
+    pub async fn summarize_with(
+        &self,
+        content: &ExtractedContent,
+        context: &SummaryContext,
+    ) -> Summary {
         match &self.strategy {
             SummarizationStrategy::RuleBased { preview_chars } => {
                 self.rule_based_summary(content, *preview_chars)
             }
+            SummarizationStrategy::Extractive { max_chars } => match &self.extractive {
+                Some(extractive) => self.extractive_summary(extractive, content),
+                None => self.rule_based_summary(content, *max_chars),
+            },
             SummarizationStrategy::LlmBased {
                 model,
                 max_tokens,
@@ -217,16 +490,39 @@ impl ContentSummarizer {
                 provider_config,
             } => {
                 if let Some(ref client) = self.client {
+                    let (system, user) = self.prompt.render(content, context);
+                    let version = self.prompt.version();
+                    let params = GenerationParams {
+                        model,
+                        max_tokens: *max_tokens,
+                        temperature: *temperature,
+                    };
+                    let key = PersistentSummaryCache::key(
+                        &format!("{}\n\n{}", system, user),
+                        &params,
+                        &version,
+                    );
+                    if let Some(cached) = self.stored(&key) {
+                        debug!(model = %model, "Summary cache hit");
+                        return Summary {
+                            text: cached.text,
+                            method: "llm".to_string(),
+                            model: Some(cached.model),
+                            stats: content.stats.clone(),
+                            usage: None,
+                        };
+                    }
+
                     let start = std::time::Instant::now();
+                    let messages = vec![ChatMessage::system(system), ChatMessage::user(user)];
+                    let options = ChatCompletionOptions {
+                        max_tokens: Some(*max_tokens),
+                        temperature: Some(*temperature),
+                        top_p: None,
+                        provider_config: Some(provider_config.clone()),
+                    };
                     match self
-                        .llm_summary(
-                            client,
-                            content,
-                            model,
-                            *max_tokens,
-                            *temperature,
-                            provider_config,
-                        )
+                        .llm_summary(client, content, messages, model, options)
                         .await
                     {
                         Ok(summary) => {
@@ -236,21 +532,320 @@ impl ContentSummarizer {
                                 duration_ms = %elapsed.as_millis(),
                                 "LLM summarization completed"
                             );
+                            self.store(
+                                &key,
+                                &version,
+                                &CachedSummary {
+                                    text: summary.text.clone(),
+                                    model: model.clone(),
+                                    tokens_used: summary.usage.as_ref().map(|u| u.total_tokens),
+                                },
+                            );
                             summary
                         }
                         Err(e) => {
-                            warn!(error = %e, "LLM summarization failed, falling back to rule-based");
-                            self.rule_based_summary(content, 200)
+                            warn!(error = %e, "LLM summarization failed, falling back to offline summary");
+                            self.offline_summary(content)
                         }
                     }
                 } else {
-                    debug!("No API client configured, using rule-based summary");
-                    self.rule_based_summary(content, 200)
+                    debug!("No API client configured, using offline summary");
+                    self.offline_summary(content)
                 }
             }
         }
     }
 
+    /// Generate one digest for a set of documents
+    ///
+    /// LLM digests are cached by the set of sources (document IDs and the
+    /// text extracted from them, see [`DigestSource::cache_key`]) and the
+    /// generation settings, in memory and, when configured, on disk, so the
+    /// same result set is only summarized once. When the content does not
+    /// fit in one request it is summarized in chunks whose summaries are
+    /// then merged.
+    pub async fn digest(&self, sources: &[DigestSource]) -> Summary {
+        let stats = combined_stats(sources);
+        match (&self.strategy, &self.client) {
+            (SummarizationStrategy::RuleBased { preview_chars }, _) => {
+                self.rule_based_digest(sources, *preview_chars)
+            }
+            (SummarizationStrategy::Extractive { max_chars }, _) => match &self.extractive {
+                Some(extractive) => self.extractive_digest(extractive, sources),
+                None => self.rule_based_digest(sources, *max_chars),
+            },
+            (SummarizationStrategy::LlmBased { .. }, _) if sources.is_empty() => {
+                self.offline_digest(sources)
+            }
+            (
+                SummarizationStrategy::LlmBased {
+                    model,
+                    max_tokens,
+                    temperature,
+                    provider_config,
+                },
+                Some(client),
+            ) => {
+                let source_keys: Vec<String> =
+                    sources.iter().map(DigestSource::cache_key).collect();
+                let source_keys: Vec<&str> = source_keys.iter().map(String::as_str).collect();
+                let params = GenerationParams {
+                    model,
+                    max_tokens: *max_tokens,
+                    temperature: *temperature,
+                };
+                let key = PersistentSummaryCache::digest_key(
+                    &source_keys,
+                    &params,
+                    DIGEST_PROMPT_VERSION,
+                );
+                let cached = self
+                    .cache
+                    .as_ref()
+                    .and_then(|cache| cache.get_digest(&source_keys, &params))
+                    .or_else(|| {
+                        let cached = self.stored(&key)?;
+                        if let Some(cache) = &self.cache {
+                            cache.cache_digest(&source_keys, &params, cached.clone());
+                        }
+                        Some(cached)
+                    });
+                if let Some(cached) = cached {
+                    debug!(model = %model, docs = sources.len(), "Digest cache hit");
+                    return Summary {
+                        text: cached.text,
+                        method: "llm".to_string(),
+                        model: Some(cached.model),
+                        stats,
+                        usage: None,
+                    };
+                }
+
+                let start = std::time::Instant::now();
+                let options = ChatCompletionOptions {
+                    max_tokens: Some(*max_tokens),
+                    temperature: Some(*temperature),
+                    top_p: None,
+                    provider_config: Some(provider_config.clone()),
+                };
+                match self.llm_digest(client, sources, model, &options).await {
+                    Ok((text, usage)) => {
+                        info!(
+                            model = %model,
+                            docs = sources.len(),
+                            duration_ms = %start.elapsed().as_millis(),
+                            "LLM digest completed"
+                        );
+                        let cached = CachedSummary {
+                            text: text.clone(),
+                            model: model.clone(),
+                            tokens_used: usage.as_ref().map(|u| u.total_tokens),
+                        };
+                        self.store(&key, DIGEST_PROMPT_VERSION, &cached);
+                        if let Some(cache) = &self.cache {
+                            cache.cache_digest(&source_keys, &params, cached);
+                        }
+                        Summary {
+                            text,
+                            method: "llm".to_string(),
+                            model: Some(model.clone()),
+                            stats,
+                            usage,
+                        }
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "LLM digest failed, falling back to offline digest");
+                        self.offline_digest(sources)
+                    }
+                }
+            }
+            (SummarizationStrategy::LlmBased { .. }, None) => {
+                debug!("No API client configured, using offline digest");
+                self.offline_digest(sources)
+            }
+        }
+    }
+
+    /// Look up an unexpired summary in the on-disk cache
+    fn stored(&self, key: &str) -> Option<CachedSummary> {
+        let store = self.store.as_ref()?;
+        store.get(key).unwrap_or_else(|e| {
+            warn!(error = %e, "Failed to read summary cache");
+            None
+        })
+    }
+
+    /// Save a summary to the on-disk cache
+    fn store(&self, key: &str, prompt_version: &str, summary: &CachedSummary) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.insert(key, prompt_version, summary) {
+                warn!(error = %e, "Failed to write summary cache");
+            }
+        }
+    }
+
+    /// Summary without the LLM: extractive when configured, else rule-based
+    fn offline_summary(&self, content: &ExtractedContent) -> Summary {
+        match &self.extractive {
+            Some(extractive) => self.extractive_summary(extractive, content),
+            None => self.rule_based_summary(content, 200),
+        }
+    }
+
+    /// Digest without the LLM: extractive when configured, else rule-based
+    fn offline_digest(&self, sources: &[DigestSource]) -> Summary {
+        match &self.extractive {
+            Some(extractive) => self.extractive_digest(extractive, sources),
+            None => self.rule_based_digest(sources, 200),
+        }
+    }
+
+    /// List each document with its top sentences
+    fn extractive_digest(
+        &self,
+        extractive: &ExtractiveSummarizer,
+        sources: &[DigestSource],
+    ) -> Summary {
+        let text = sources
+            .iter()
+            .map(|source| {
+                format!(
+                    "- {} ({}): {}",
+                    source.title,
+                    source.date,
+                    extractive.summarize(&source.content.text).join(" ")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Summary {
+            text,
+            method: "extractive".to_string(),
+            model: None,
+            stats: combined_stats(sources),
+            usage: None,
+        }
+    }
+
+    /// List each document with a one-line preview
+    fn rule_based_digest(&self, sources: &[DigestSource], preview_chars: usize) -> Summary {
+        let text = sources
+            .iter()
+            .map(|source| {
+                let flat = source
+                    .content
+                    .text
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let mut preview: String = flat.chars().take(preview_chars).collect();
+                if flat.chars().count() > preview_chars {
+                    preview.push_str("...");
+                }
+                format!("- {} ({}): {}", source.title, source.date, preview)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Summary {
+            text,
+            method: "rule-based".to_string(),
+            model: None,
+            stats: combined_stats(sources),
+            usage: None,
+        }
+    }
+
+    /// Summarize all sources in one request, or map-reduce over chunks
+    ///
+    /// Partial summaries are merged in groups that fit the chunk budget,
+    /// round after round, until one request can merge them all. Text is
+    /// only cut (with a warning) when a single partial summary exceeds the
+    /// budget on its own.
+    async fn llm_digest(
+        &self,
+        client: &OpenRouterClient,
+        sources: &[DigestSource],
+        model: &str,
+        options: &ChatCompletionOptions,
+    ) -> Result<(String, Option<SummaryUsage>), Box<dyn std::error::Error + Send + Sync>> {
+        let budget = self.digest_chunk_chars;
+        let sections: Vec<String> = sources.iter().map(DigestSource::section).collect();
+        let chunks = chunk_sections(&sections, budget, SECTION_SEPARATOR);
+        let mut usage: Option<SummaryUsage> = None;
+
+        if chunks.len() == 1 {
+            let (text, call_usage) =
+                complete(client, model, DIGEST_PROMPT, &chunks[0], options).await?;
+            return Ok((text, call_usage));
+        }
+
+        debug!(model = %model, chunks = chunks.len(), "Digest exceeds budget, summarizing in chunks");
+        let mut partials = Vec::with_capacity(chunks.len());
+        for chunk in &chunks {
+            let (text, call_usage) = complete(client, model, DIGEST_PROMPT, chunk, options).await?;
+            accumulate(&mut usage, call_usage);
+            partials.push(text);
+        }
+
+        loop {
+            let longest = partials.iter().map(|p| p.chars().count()).max();
+            if let Some(chars) = longest.filter(|&chars| chars > budget) {
+                warn!(
+                    chars,
+                    budget, "Partial digest exceeds the chunk budget and is truncated"
+                );
+            }
+
+            let groups = chunk_sections(&partials, budget, PARTIAL_SEPARATOR);
+            if groups.len() == 1 {
+                let (text, call_usage) =
+                    complete(client, model, MERGE_PROMPT, &groups[0], options).await?;
+                accumulate(&mut usage, call_usage);
+                return Ok((text, usage));
+            }
+            if groups.len() == partials.len() {
+                // No two partials fit in one request, so another round
+                // would not shrink the input
+                warn!(
+                    partials = partials.len(),
+                    budget,
+                    "Partial digests do not fit the chunk budget, truncating the merge input"
+                );
+                let merged = truncate_chars(&groups.join(PARTIAL_SEPARATOR), budget);
+                let (text, call_usage) =
+                    complete(client, model, MERGE_PROMPT, &merged, options).await?;
+                accumulate(&mut usage, call_usage);
+                return Ok((text, usage));
+            }
+
+            debug!(model = %model, groups = groups.len(), "Partial digests exceed budget, merging in groups");
+            partials = Vec::with_capacity(groups.len());
+            for group in &groups {
+                let (text, call_usage) =
+                    complete(client, model, MERGE_PROMPT, group, options).await?;
+                accumulate(&mut usage, call_usage);
+                partials.push(text);
+            }
+        }
+    }
+
+    /// Generate extractive summary, one sentence per line
+    fn extractive_summary(
+        &self,
+        extractive: &ExtractiveSummarizer,
+        content: &ExtractedContent,
+    ) -> Summary {
+        Summary {
+            text: extractive.summarize(&content.text).join("\n"),
+            method: "extractive".to_string(),
+            model: None,
+            stats: content.stats.clone(),
+            usage: None,
+        }
+    }
+
     /// Generate rule-based summary
     fn rule_based_summary(&self, content: &ExtractedContent, preview_chars: usize) -> Summary {
         let preview: String = content.text.chars().take(preview_chars).collect();
@@ -268,6 +863,7 @@ impl ContentSummarizer {
         Summary {
             text: summary_text,
             method: "rule-based".to_string(),
+            model: None,
             stats: content.stats.clone(),
             usage: None,
         }
@@ -278,26 +874,10 @@ impl ContentSummarizer {
         &self,
         client: &OpenRouterClient,
         content: &ExtractedContent,
+        messages: Vec<ChatMessage>,
         model: &str,
-        max_tokens: usize,
-        temperature: f32,
-        provider_config: &ProviderConfig,
+        options: ChatCompletionOptions,
     ) -> Result<Summary, Box<dyn std::error::Error + Send + Sync>> {
-        let system_prompt =
-            "以下のテキストを簡潔に要約してください。重要なポイントを箇条書きで抽出してください。";
-
-        let messages = vec![
-            ChatMessage::system(system_prompt),
-            ChatMessage::user(&content.text),
-        ];
-
-        let options = ChatCompletionOptions {
-            max_tokens: Some(max_tokens),
-            temperature: Some(temperature),
-            top_p: None,
-            provider_config: Some(provider_config.clone()),
-        };
-
         debug!(model = %model, content_len = content.text.len(), "Calling LLM API");
 
         let response = client.chat_completion(model, messages, options).await?;
@@ -312,12 +892,103 @@ impl ContentSummarizer {
         Ok(Summary {
             text: response.content,
             method: "llm".to_string(),
+            model: Some(model.to_string()),
             stats: content.stats.clone(),
             usage,
         })
     }
 }
 
+fn load_extractive(max_chars: usize) -> Option<ExtractiveSummarizer> {
+    match ExtractiveSummarizer::new(max_chars) {
+        Ok(extractive) => Some(extractive),
+        Err(e) => {
+            warn!(error = %e, "Failed to load tokenizer, using rule-based summaries");
+            None
+        }
+    }
+}
+
+/// Run one chat completion and return its text and usage
+async fn complete(
+    client: &OpenRouterClient,
+    model: &str,
+    system_prompt: &str,
+    text: &str,
+    options: &ChatCompletionOptions,
+) -> Result<(String, Option<SummaryUsage>), Box<dyn std::error::Error + Send + Sync>> {
+    let messages = vec![ChatMessage::system(system_prompt), ChatMessage::user(text)];
+    debug!(model = %model, content_len = text.len(), "Calling LLM API");
+    let response = client
+        .chat_completion(model, messages, options.clone())
+        .await?;
+    let usage = response.usage.map(|u| SummaryUsage {
+        prompt_tokens: u.prompt_tokens,
+        completion_tokens: u.completion_tokens,
+        total_tokens: u.total_tokens,
+        model: response.model.clone(),
+    });
+    Ok((response.content, usage))
+}
+
+fn accumulate(total: &mut Option<SummaryUsage>, usage: Option<SummaryUsage>) {
+    match (total.as_mut(), usage) {
+        (Some(total), Some(usage)) => total.add(usage),
+        (None, usage) => *total = usage,
+        (Some(_), None) => {}
+    }
+}
+
+/// Group sections into chunks of at most `budget` characters
+///
+/// A section longer than the budget is truncated into a chunk of its own.
+fn chunk_sections(sections: &[String], budget: usize, separator: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+    let separator_chars = separator.chars().count();
+
+    for section in sections {
+        let section = truncate_chars(section, budget);
+        let chars = section.chars().count();
+        if !current.is_empty() && current_chars + separator_chars + chars > budget {
+            chunks.push(std::mem::take(&mut current));
+            current_chars = 0;
+        }
+        if !current.is_empty() {
+            current.push_str(separator);
+            current_chars += separator_chars;
+        }
+        current.push_str(&section);
+        current_chars += chars;
+    }
+    if !current.is_empty() || chunks.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
+
+/// Statistics of all digest sources together
+fn combined_stats(sources: &[DigestSource]) -> ContentStats {
+    sources.iter().fold(
+        ContentStats {
+            total_chars: 0,
+            total_lines: 0,
+            extracted_chars: 0,
+        },
+        |mut stats, source| {
+            stats.total_chars += source.content.stats.total_chars;
+            stats.total_lines += source.content.stats.total_lines;
+            stats.extracted_chars += source.content.stats.extracted_chars;
+            stats
+        },
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -399,6 +1070,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_chunk_sections() {
+        let sections = vec!["a".repeat(4), "b".repeat(4), "c".repeat(12)];
+        let chunks = chunk_sections(&sections, 10, SECTION_SEPARATOR);
+        assert_eq!(chunks, vec!["aaaa\n\nbbbb".to_string(), "c".repeat(10)]);
+        assert_eq!(
+            chunk_sections(&sections, 10, PARTIAL_SEPARATOR),
+            vec!["a".repeat(4), "b".repeat(4), "c".repeat(10)]
+        );
+        assert_eq!(
+            chunk_sections(&[], 10, SECTION_SEPARATOR),
+            vec![String::new()]
+        );
+    }
+
+    #[test]
+    fn test_rule_based_digest() {
+        let source = |id: &str, text: &str| DigestSource {
+            doc_id: id.to_string(),
+            title: format!("Memo {}", id),
+            date: "2025-03-01".to_string(),
+            content: ExtractedContent {
+                text: text.to_string(),
+                truncated: false,
+                stats: ContentStats {
+                    total_chars: text.chars().count(),
+                    total_lines: text.lines().count(),
+                    extracted_chars: text.chars().count(),
+                },
+            },
+        };
+        let summarizer = ContentSummarizer::rule_based(10);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let digest = rt.block_on(
+            summarizer.digest(&[source("a", "short"), source("b", "first line\nsecond line")]),
+        );
+
+        assert_eq!(digest.method, "rule-based");
+        assert_eq!(
+            digest.text,
+            "- Memo a (2025-03-01): short\n- Memo b (2025-03-01): first line..."
+        );
+        assert_eq!(digest.stats.total_lines, 3);
+    }
+
     #[test]
     fn test_llm_based_factory() {
         let summarizer = ContentSummarizer::llm_based(
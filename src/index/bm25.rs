@@ -9,7 +9,7 @@ use crate::tokenizer::JapaneseTokenizer;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 /// BM25 parameters
@@ -69,7 +69,7 @@ impl Bm25Index {
 
     /// Build an index from documents
     pub fn build(docs: &[Document]) -> Result<Self> {
-        let tokenizer = JapaneseTokenizer::new()?;
+        let tokenizer = JapaneseTokenizer::shared()?;
         let mut index = Self::new();
 
         index.num_docs = docs.len();
@@ -120,7 +120,7 @@ impl Bm25Index {
             return Ok(Vec::new());
         }
 
-        let tokenizer = JapaneseTokenizer::new()?;
+        let tokenizer = JapaneseTokenizer::shared()?;
         // Use tokenize_with_english for query to match English acronyms
         let query_tokens = tokenizer.tokenize_with_english(query)?;
 
@@ -151,47 +151,120 @@ impl Bm25Index {
         Ok(results)
     }
 
+    /// Search the index with pre-weighted query terms
+    ///
+    /// Each term's BM25 contribution is multiplied by its weight, which allows
+    /// "more like this" queries built from a document's most distinctive terms.
+    pub fn search_weighted(
+        &self,
+        weighted_terms: &[(String, f32)],
+        top_k: usize,
+    ) -> Vec<SearchResult> {
+        if self.num_docs == 0 || weighted_terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scores: Vec<(usize, f32)> = (0..self.num_docs)
+            .map(|doc_idx| {
+                let score = weighted_terms
+                    .iter()
+                    .map(|(term, weight)| weight * self.term_score(doc_idx, term))
+                    .sum::<f32>();
+                (doc_idx, score)
+            })
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+
+        scores.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        scores
+            .into_iter()
+            .take(top_k)
+            .map(|(doc_idx, score)| SearchResult::new(self.doc_ids[doc_idx].clone(), score))
+            .collect()
+    }
+
+    /// Get a document's highest tf-idf terms, sorted by weight descending
+    pub fn top_terms(&self, doc_id: &str, limit: usize) -> Vec<(String, f32)> {
+        let Some(doc_idx) = self.doc_ids.iter().position(|id| id == doc_id) else {
+            return Vec::new();
+        };
+        let tokens = &self.doc_tokens[doc_idx];
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+
+        let mut term_freqs: HashMap<&str, usize> = HashMap::new();
+        for token in tokens {
+            *term_freqs.entry(token.as_str()).or_insert(0) += 1;
+        }
+
+        let doc_len = tokens.len() as f32;
+        let mut terms: Vec<(String, f32)> = term_freqs
+            .into_iter()
+            .map(|(term, freq)| {
+                let tf = freq as f32 / doc_len;
+                (term.to_string(), tf * self.idf(term))
+            })
+            .filter(|(_, weight)| *weight > 0.0)
+            .collect();
+
+        // Ties are broken by term so the query is deterministic
+        terms.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        terms.truncate(limit);
+        terms
+    }
+
     /// Calculate BM25 score for a document given query tokens
     fn calculate_bm25_score(&self, doc_idx: usize, query_tokens: &[String]) -> f32 {
+        query_tokens
+            .iter()
+            .map(|token| self.term_score(doc_idx, token))
+            .sum()
+    }
+
+    /// Calculate the BM25 contribution of a single term to a document
+    fn term_score(&self, doc_idx: usize, token: &str) -> f32 {
         let doc_len = self.doc_lengths[doc_idx] as f32;
-        let mut score = 0.0;
-
-        for token in query_tokens {
-            // Get term frequency in this document
-            let tf = self
-                .inverted_index
-                .get(token)
-                .and_then(|postings| {
-                    postings
-                        .iter()
-                        .find(|(idx, _)| *idx == doc_idx)
-                        .map(|(_, freq)| *freq as f32)
-                })
-                .unwrap_or(0.0);
-
-            if tf == 0.0 {
-                continue;
-            }
 
-            // Get document frequency
-            let df = *self.doc_frequencies.get(token).unwrap_or(&0) as f32;
-            if df == 0.0 {
-                continue;
-            }
+        // Get term frequency in this document
+        let tf = self
+            .inverted_index
+            .get(token)
+            .and_then(|postings| {
+                postings
+                    .iter()
+                    .find(|(idx, _)| *idx == doc_idx)
+                    .map(|(_, freq)| *freq as f32)
+            })
+            .unwrap_or(0.0);
+
+        if tf == 0.0 {
+            return 0.0;
+        }
 
-            // Calculate IDF (inverse document frequency)
-            let idf = ((self.num_docs as f32 - df + 0.5) / (df + 0.5) + 1.0).ln();
+        let idf = self.idf(token);
+        if idf == 0.0 {
+            return 0.0;
+        }
 
-            // Calculate BM25 term score
-            let numerator = tf * (BM25_K1 + 1.0);
-            let denominator =
-                tf + BM25_K1 * (1.0 - BM25_B + BM25_B * (doc_len / self.avg_doc_length));
-            let term_score = idf * (numerator / denominator);
+        // Calculate BM25 term score
+        let numerator = tf * (BM25_K1 + 1.0);
+        let denominator = tf + BM25_K1 * (1.0 - BM25_B + BM25_B * (doc_len / self.avg_doc_length));
+        idf * (numerator / denominator)
+    }
 
-            score += term_score;
+    /// Calculate IDF (inverse document frequency) for a term
+    fn idf(&self, token: &str) -> f32 {
+        let df = *self.doc_frequencies.get(token).unwrap_or(&0) as f32;
+        if df == 0.0 {
+            return 0.0;
         }
-
-        score
+        ((self.num_docs as f32 - df + 0.5) / (df + 0.5) + 1.0).ln()
     }
 
     /// Save index to file
@@ -231,9 +304,89 @@ impl Bm25Index {
         let python_format: PythonBm25Format =
             serde_json::from_str(content).with_context(|| "Failed to parse Python BM25 format")?;
 
-        let num_docs = python_format.doc_ids.len();
-        let doc_ids = python_format.doc_ids;
-        let doc_tokens = python_format.corpus;
+        let index = Self::from_corpus(python_format.doc_ids, python_format.corpus);
+
+        tracing::info!(
+            "Converted Python BM25 format: {} docs, {} unique terms",
+            index.num_docs,
+            index.doc_frequencies.len()
+        );
+
+        Ok(index)
+    }
+
+    /// Rebuild the index without `removed` documents and with `added` ones
+    ///
+    /// Tokens of retained documents are reused, so only the added documents
+    /// are tokenized. Added documents replace retained ones with the same ID.
+    pub fn updated(&self, removed: &[String], added: &[Document]) -> Result<Self> {
+        let replaced: HashSet<&str> = removed
+            .iter()
+            .map(String::as_str)
+            .chain(added.iter().map(|doc| doc.id.as_str()))
+            .collect();
+
+        let mut doc_ids = Vec::with_capacity(self.doc_ids.len() + added.len());
+        let mut doc_tokens = Vec::with_capacity(self.doc_ids.len() + added.len());
+        for (id, tokens) in self.doc_ids.iter().zip(&self.doc_tokens) {
+            if !replaced.contains(id.as_str()) {
+                doc_ids.push(id.clone());
+                doc_tokens.push(tokens.clone());
+            }
+        }
+
+        if !added.is_empty() {
+            let tokenizer = JapaneseTokenizer::shared()?;
+            for doc in added {
+                let combined_text = format!("{} {}", doc.title(), doc.text);
+                doc_ids.push(doc.id.clone());
+                doc_tokens.push(tokenizer.tokenize_with_english(&combined_text)?);
+            }
+        }
+
+        Ok(Self::from_corpus(doc_ids, doc_tokens))
+    }
+
+    /// Add a single document to the index
+    ///
+    /// Only the new document is tokenized. A document with an ID already in
+    /// the index replaces the old entry.
+    pub fn insert(&mut self, doc: &Document) -> Result<()> {
+        if self.doc_ids.contains(&doc.id) {
+            *self = self.updated(&[], std::slice::from_ref(doc))?;
+            return Ok(());
+        }
+
+        let tokenizer = JapaneseTokenizer::shared()?;
+        let combined_text = format!("{} {}", doc.title(), doc.text);
+        let tokens = tokenizer.tokenize_with_english(&combined_text)?;
+
+        let doc_idx = self.doc_ids.len();
+        let mut term_freqs: HashMap<String, usize> = HashMap::new();
+        for token in &tokens {
+            *term_freqs.entry(token.clone()).or_insert(0) += 1;
+        }
+        for (term, freq) in term_freqs {
+            *self.doc_frequencies.entry(term.clone()).or_insert(0) += 1;
+            self.inverted_index
+                .entry(term)
+                .or_default()
+                .push((doc_idx, freq));
+        }
+
+        let total_length = self.avg_doc_length * self.num_docs as f32 + tokens.len() as f32;
+        self.num_docs += 1;
+        self.avg_doc_length = total_length / self.num_docs as f32;
+        self.doc_ids.push(doc.id.clone());
+        self.doc_lengths.push(tokens.len());
+        self.doc_tokens.push(tokens);
+
+        Ok(())
+    }
+
+    /// Build the inverted index and statistics from tokenized documents
+    fn from_corpus(doc_ids: Vec<String>, doc_tokens: Vec<Vec<String>>) -> Self {
+        let num_docs = doc_ids.len();
 
         // Calculate doc_lengths
         let doc_lengths: Vec<usize> = doc_tokens.iter().map(|tokens| tokens.len()).collect();
@@ -269,13 +422,7 @@ impl Bm25Index {
             }
         }
 
-        tracing::info!(
-            "Converted Python BM25 format: {} docs, {} unique terms",
-            num_docs,
-            doc_frequencies.len()
-        );
-
-        Ok(Self {
+        Self {
             doc_ids,
             doc_tokens,
             inverted_index,
@@ -283,7 +430,23 @@ impl Bm25Index {
             avg_doc_length,
             doc_frequencies,
             num_docs,
-        })
+        }
+    }
+
+    /// Get the indexed tokens for a document
+    pub fn doc_tokens(&self, doc_id: &str) -> Option<&[String]> {
+        self.doc_ids
+            .iter()
+            .position(|id| id == doc_id)
+            .map(|idx| self.doc_tokens[idx].as_slice())
+    }
+
+    /// Iterate over document IDs and their indexed tokens
+    pub fn documents(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.doc_ids
+            .iter()
+            .map(String::as_str)
+            .zip(self.doc_tokens.iter().map(Vec::as_slice))
     }
 
     /// Get document count
@@ -363,6 +526,25 @@ mod tests {
         assert!(index.avg_doc_length() > 0.0);
     }
 
+    #[test]
+    fn test_bm25_insert_matches_build() {
+        let docs = create_test_documents();
+        let mut index = Bm25Index::build(&docs[..4]).unwrap();
+        index.insert(&docs[4]).unwrap();
+        let built = Bm25Index::build(&docs).unwrap();
+
+        assert_eq!(index.len(), built.len());
+        assert_eq!(index.doc_ids, built.doc_ids);
+        assert_eq!(index.doc_lengths, built.doc_lengths);
+        assert_eq!(index.inverted_index, built.inverted_index);
+        assert_eq!(index.doc_frequencies, built.doc_frequencies);
+        assert!((index.avg_doc_length() - built.avg_doc_length()).abs() < 1e-4);
+
+        // Re-inserting an existing ID replaces it instead of duplicating it
+        index.insert(&docs[0]).unwrap();
+        assert_eq!(index.len(), 5);
+    }
+
     #[test]
     fn test_bm25_search() {
         let docs = create_test_documents();
@@ -443,6 +625,42 @@ mod tests {
         assert!(!results.is_empty());
     }
 
+    fn create_pretokenized_index() -> Bm25Index {
+        Bm25Index::load_from_python_format(
+            r#"{"doc_ids": ["doc1", "doc2", "doc3"],
+                "corpus": [["rust", "mcp", "mcp", "server"],
+                           ["rust", "mcp", "client"],
+                           ["python", "web"]]}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_top_terms() {
+        let index = create_pretokenized_index();
+        let terms = index.top_terms("doc1", 2);
+
+        assert_eq!(terms.len(), 2);
+        // "server" only appears in doc1, "mcp" is frequent in doc1
+        let names: Vec<&str> = terms.iter().map(|(t, _)| t.as_str()).collect();
+        assert!(names.contains(&"server"));
+        assert!(names.contains(&"mcp"));
+        assert!(terms[0].1 >= terms[1].1);
+
+        assert!(index.top_terms("missing", 5).is_empty());
+    }
+
+    #[test]
+    fn test_search_weighted() {
+        let index = create_pretokenized_index();
+        let query = vec![("mcp".to_string(), 1.0), ("rust".to_string(), 0.5)];
+        let results = index.search_weighted(&query, 3);
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.doc_id != "doc3"));
+        assert!(index.search_weighted(&[], 3).is_empty());
+    }
+
     // ============================================
     // TDD: English Acronym Search Tests
     // ============================================
@@ -2,10 +2,14 @@
 //!
 //! Provides the pipeline for building all indices from changelog files.
 
-use super::{Bm25Index, Docstore, IndexMetadata, VectorIndex};
+use super::{
+    Bm25Index, Docstore, DuplicateDetector, IncrementalDiff, IndexMetadata, TopicModel,
+    VectorIndex, TOPICS_FILE,
+};
 use crate::embedding::OpenRouterEmbedding;
 use crate::loader::{ChangelogLoader, Document};
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 /// Create embedding input text from a document
@@ -22,7 +26,7 @@ use std::path::Path;
 ///
 /// # Returns
 /// A formatted string suitable for embedding generation
-fn create_embedding_text(doc: &Document) -> String {
+pub(crate) fn create_embedding_text(doc: &Document) -> String {
     let tags = doc.tags().join(", ");
     let title = doc.title();
 
@@ -37,6 +41,8 @@ fn create_embedding_text(doc: &Document) -> String {
 pub struct IndexBuilder {
     /// Optional embedding client for vector index
     embedding_client: Option<OpenRouterEmbedding>,
+    /// When set, near-duplicates share one embedding instead of each being embedded
+    duplicate_detector: Option<DuplicateDetector>,
 }
 
 impl Default for IndexBuilder {
@@ -50,6 +56,7 @@ impl IndexBuilder {
     pub fn new() -> Self {
         Self {
             embedding_client: None,
+            duplicate_detector: None,
         }
     }
 
@@ -57,6 +64,7 @@ impl IndexBuilder {
     pub fn with_embeddings(api_key: String) -> Self {
         Self {
             embedding_client: Some(OpenRouterEmbedding::new(api_key)),
+            duplicate_detector: None,
         }
     }
 
@@ -64,9 +72,19 @@ impl IndexBuilder {
     pub fn with_embeddings_and_base_url(api_key: String, base_url: String) -> Self {
         Self {
             embedding_client: Some(OpenRouterEmbedding::with_base_url(api_key, base_url)),
+            duplicate_detector: None,
         }
     }
 
+    /// Embed only one document of each group of near-duplicates
+    ///
+    /// The other documents in the group reuse its vector, saving embedding
+    /// requests for copy-pasted memos.
+    pub fn with_duplicate_detector(mut self, detector: DuplicateDetector) -> Self {
+        self.duplicate_detector = Some(detector);
+        self
+    }
+
     /// Check if this builder has an embedding client configured
     pub fn has_embedding_client(&self) -> bool {
         self.embedding_client.is_some()
@@ -98,6 +116,127 @@ impl IndexBuilder {
         Self::load_existing_metadata(output_dir).is_some()
     }
 
+    /// Replace document tags in an existing index without a rebuild
+    ///
+    /// Tags are not part of the document ID or the BM25 corpus, so only the
+    /// docstore changes. They are part of the embedding text, though: use
+    /// [`refresh_embeddings`](Self::refresh_embeddings) afterwards, or the
+    /// vectors of [`embedded_documents`](Self::embedded_documents) keep the
+    /// old tags. Metadata gets a new build timestamp so running servers
+    /// reload the index. Returns the number of documents updated.
+    pub fn update_tags(output_dir: &Path, tags: &HashMap<String, Vec<String>>) -> Result<usize> {
+        Self::update_docstore(output_dir, |docstore| {
+            tags.iter()
+                .filter(|(id, tags)| docstore.set_tags(id, tags.to_vec()))
+                .count()
+        })
+    }
+
+    /// Replace the inferred tags of documents in an existing index
+    ///
+    /// Like [`update_tags`](Self::update_tags), but leaves authored tags
+    /// alone. Returns the number of documents updated.
+    pub fn update_inferred_tags(
+        output_dir: &Path,
+        tags: &HashMap<String, Vec<String>>,
+    ) -> Result<usize> {
+        Self::update_docstore(output_dir, |docstore| {
+            tags.iter()
+                .filter(|(id, tags)| docstore.set_inferred_tags(id, tags.to_vec()))
+                .count()
+        })
+    }
+
+    /// Edit the docstore in place and bump the build timestamp if anything
+    /// changed
+    fn update_docstore<F>(output_dir: &Path, edit: F) -> Result<usize>
+    where
+        F: FnOnce(&mut Docstore) -> usize,
+    {
+        let docstore_path = output_dir.join("docstore.json");
+        let mut docstore = Docstore::load_from_file(&docstore_path)?;
+        let updated = edit(&mut docstore);
+        if updated == 0 {
+            return Ok(0);
+        }
+        docstore.save_to_file(&docstore_path)?;
+        Self::touch_metadata(output_dir)?;
+
+        Ok(updated)
+    }
+
+    /// Documents among `doc_ids` that have a vector in an existing index
+    pub fn embedded_documents(output_dir: &Path, doc_ids: &[String]) -> Result<Vec<String>> {
+        let vector_path = output_dir.join("faiss_index.json");
+        if !vector_path.exists() {
+            return Ok(Vec::new());
+        }
+        let vector_index = VectorIndex::load_from_file(&vector_path)?;
+        Ok(doc_ids
+            .iter()
+            .filter(|id| vector_index.contains(id))
+            .cloned()
+            .collect())
+    }
+
+    /// Embed documents of an existing index again (async)
+    ///
+    /// Used after edits that change the embedding text without changing the
+    /// document ID, such as tag edits. Only documents that already have a
+    /// vector are embedded, from their current docstore entry. Metadata
+    /// gets a new build timestamp so running servers reload the index.
+    /// Returns the number of documents embedded.
+    pub async fn refresh_embeddings(&self, output_dir: &Path, doc_ids: &[String]) -> Result<usize> {
+        let client = self
+            .embedding_client
+            .as_ref()
+            .context("Re-embedding documents requires an embedding client")?;
+        let embedded = Self::embedded_documents(output_dir, doc_ids)?;
+        if embedded.is_empty() {
+            return Ok(0);
+        }
+
+        let docstore = Docstore::load_from_file(&output_dir.join("docstore.json"))?;
+        let documents: Vec<Document> = embedded
+            .iter()
+            .filter_map(|id| docstore.get(id).cloned())
+            .collect();
+        let bm25_index = Bm25Index::load_from_file(&output_dir.join("bm25_index.json"))?;
+        let vector_path = output_dir.join("faiss_index.json");
+        let mut vector_index = VectorIndex::load_from_file(&vector_path)?;
+        vector_index.remove_batch(&embedded);
+        self.embed_documents(
+            client,
+            &documents,
+            &bm25_index,
+            &mut vector_index,
+            &|_, _, _| {},
+        )
+        .await?;
+
+        vector_index.save_to_file(&vector_path)?;
+        Self::touch_metadata(output_dir)?;
+        Ok(documents.len())
+    }
+
+    /// Store a topic model in an existing index
+    ///
+    /// Metadata gets a new build timestamp so running servers load it.
+    pub fn update_topics(output_dir: &Path, topics: &TopicModel) -> Result<()> {
+        topics.save_to_file(&output_dir.join(TOPICS_FILE))?;
+        Self::touch_metadata(output_dir)
+    }
+
+    /// Give the index a new build timestamp, if it has metadata
+    fn touch_metadata(output_dir: &Path) -> Result<()> {
+        let metadata_path = output_dir.join("metadata.json");
+        if let Ok(mut metadata) = IndexMetadata::load_from_file(&metadata_path) {
+            metadata.created_at = chrono::Utc::now().to_rfc3339();
+            metadata.save_to_file(&metadata_path)?;
+        }
+        Ok(())
+    }
+
     /// Build all indices from a changelog file (sync version, no embeddings)
     pub fn build(&self, input: &Path, output_dir: &Path) -> Result<()> {
         self.build_with_progress(input, output_dir, |_, _, _| {})
@@ -186,7 +325,6 @@ impl IndexBuilder {
     where
         F: Fn(usize, usize, &str),
     {
-        const BATCH_SIZE: usize = 10;
         let doc_count = documents.len();
 
         // Step 1: Build BM25 index
@@ -202,42 +340,9 @@ impl IndexBuilder {
 
         // Step 3: Build vector index (if embedding client available)
         let vector_index = if let Some(client) = &self.embedding_client {
-            let total_batches = doc_count.div_ceil(BATCH_SIZE);
-            progress(
-                3,
-                5,
-                &format!(
-                    "Generating embeddings ({} documents in {} batches)...",
-                    doc_count, total_batches
-                ),
-            );
-
             let mut index = VectorIndex::new(1536);
-            let texts: Vec<String> = documents.iter().map(create_embedding_text).collect();
-
-            for (batch_idx, chunk) in texts.chunks(BATCH_SIZE).enumerate() {
-                if batch_idx > 0 {
-                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                }
-
-                let batch_progress = format!(
-                    "Embedding batch {}/{} ({} documents)...",
-                    batch_idx + 1,
-                    total_batches,
-                    chunk.len()
-                );
-                progress(3, 5, &batch_progress);
-
-                let embeddings = client.embed_batch(chunk).await?;
-
-                let start_idx = batch_idx * BATCH_SIZE;
-                for (i, embedding) in embeddings.into_iter().enumerate() {
-                    let doc_idx = start_idx + i;
-                    if doc_idx < documents.len() {
-                        index.add(documents[doc_idx].id.clone(), embedding)?;
-                    }
-                }
-            }
+            self.embed_documents(client, &documents, &bm25_index, &mut index, &progress)
+                .await?;
             index
         } else {
             progress(3, 5, "Skipping embeddings (no client configured)...");
@@ -369,6 +474,199 @@ impl IndexBuilder {
 
         Ok(())
     }
+
+    /// Apply an incremental diff to an existing index in place (async)
+    ///
+    /// Only added and modified documents are tokenized and embedded; removed
+    /// and modified ones are dropped from every index first. Without an
+    /// embedding client, stale vectors of modified documents are removed so
+    /// they fall back to BM25. Metadata is written last, which is what a
+    /// running server watches for before reloading.
+    pub async fn apply_diff<F>(
+        &self,
+        diff: &IncrementalDiff,
+        output_dir: &Path,
+        progress: F,
+    ) -> Result<()>
+    where
+        F: Fn(usize, usize, &str),
+    {
+        let mut metadata = Self::load_existing_metadata(output_dir)
+            .context("No incremental index metadata found, run a full build first")?;
+        let changed = diff.needs_embedding();
+        let stale: Vec<String> = diff
+            .removed
+            .iter()
+            .cloned()
+            .chain(diff.modified.iter().map(|doc| doc.id.clone()))
+            .collect();
+
+        // Step 1: Update BM25 index
+        progress(1, 5, "Updating BM25 index...");
+        let bm25_index = Bm25Index::load_from_file(&output_dir.join("bm25_index.json"))?;
+        let added: Vec<Document> = changed.iter().map(|doc| (*doc).clone()).collect();
+        let bm25_index = bm25_index.updated(&diff.removed, &added)?;
+
+        // Step 2: Update docstore
+        progress(2, 5, "Updating document store...");
+        let mut docstore = Docstore::load_from_file(&output_dir.join("docstore.json"))?;
+        docstore.remove_batch(&stale);
+        for doc in &added {
+            docstore.add(doc.clone());
+        }
+
+        // Step 3: Update vector index
+        let vector_path = output_dir.join("faiss_index.json");
+        let mut vector_index = if vector_path.exists() {
+            VectorIndex::load_from_file(&vector_path)?
+        } else {
+            VectorIndex::new(0)
+        };
+        vector_index.remove_batch(&stale);
+
+        if let Some(client) = &self.embedding_client {
+            self.embed_documents(client, &added, &bm25_index, &mut vector_index, &progress)
+                .await?;
+            metadata.embedding_model = Some(client.model().to_string());
+        } else {
+            progress(3, 5, "Skipping embeddings (no client configured)...");
+        }
+
+        // Step 4: Save indices
+        progress(4, 5, "Saving indices...");
+        bm25_index.save_to_file(&output_dir.join("bm25_index.json"))?;
+        docstore.save_to_file(&output_dir.join("docstore.json"))?;
+        vector_index.save_to_file(&vector_path)?;
+
+        for id in &diff.removed {
+            metadata.remove_doc_hash(id);
+        }
+        for doc in &added {
+            metadata.update_doc_hash(doc.id.clone(), doc.content_hash());
+        }
+        metadata.doc_count = docstore.len();
+        metadata.created_at = chrono::Utc::now().to_rfc3339();
+        metadata.save_to_file(&output_dir.join("metadata.json"))?;
+
+        progress(5, 5, "Done!");
+
+        Ok(())
+    }
+
+    /// Embed documents into `index` in rate-limited batches
+    ///
+    /// With a duplicate detector, near-duplicates of a document that is
+    /// already in `index`, or embedded in this call, reuse its vector.
+    async fn embed_documents<F>(
+        &self,
+        client: &OpenRouterEmbedding,
+        documents: &[Document],
+        bm25_index: &Bm25Index,
+        index: &mut VectorIndex,
+        progress: &F,
+    ) -> Result<()>
+    where
+        F: Fn(usize, usize, &str),
+    {
+        // OpenRouter has a limit on total tokens per request
+        const BATCH_SIZE: usize = 10;
+
+        let sources = match &self.duplicate_detector {
+            Some(detector) => embedding_sources(detector, documents, bm25_index, index),
+            None => HashMap::new(),
+        };
+        let pending: Vec<&Document> = documents
+            .iter()
+            .filter(|doc| !sources.contains_key(&doc.id))
+            .collect();
+
+        let total_batches = pending.len().div_ceil(BATCH_SIZE);
+        progress(
+            3,
+            5,
+            &format!(
+                "Generating embeddings ({} documents in {} batches)...",
+                pending.len(),
+                total_batches
+            ),
+        );
+        for (batch_idx, chunk) in pending.chunks(BATCH_SIZE).enumerate() {
+            if batch_idx > 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            }
+
+            progress(
+                3,
+                5,
+                &format!(
+                    "Embedding batch {}/{} ({} documents)...",
+                    batch_idx + 1,
+                    total_batches,
+                    chunk.len()
+                ),
+            );
+
+            let texts: Vec<String> = chunk.iter().map(|doc| create_embedding_text(doc)).collect();
+            let embeddings = client.embed_batch(&texts).await?;
+            for (doc, embedding) in chunk.iter().zip(embeddings) {
+                index.add(doc.id.clone(), embedding)?;
+            }
+        }
+
+        if !sources.is_empty() {
+            progress(
+                3,
+                5,
+                &format!(
+                    "Reusing embeddings for {} near-duplicate documents...",
+                    sources.len()
+                ),
+            );
+        }
+        for (id, source) in &sources {
+            if let Some(vector) = index.get_vector(source).map(<[f32]>::to_vec) {
+                index.add(id.clone(), vector)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Map each document that can reuse another document's embedding to that
+/// document
+///
+/// Within a duplicate cluster the source is a member that already has a
+/// vector, or else the first of `documents` in it.
+fn embedding_sources(
+    detector: &DuplicateDetector,
+    documents: &[Document],
+    bm25_index: &Bm25Index,
+    index: &VectorIndex,
+) -> HashMap<String, String> {
+    let pending: HashSet<&str> = documents.iter().map(|doc| doc.id.as_str()).collect();
+    let mut sources = HashMap::new();
+    for cluster in detector.find(bm25_index.documents(), None) {
+        let source = cluster
+            .doc_ids
+            .iter()
+            .find(|id| index.contains(id))
+            .or_else(|| {
+                cluster
+                    .doc_ids
+                    .iter()
+                    .find(|id| pending.contains(id.as_str()))
+            });
+        let Some(source) = source else {
+            continue;
+        };
+        for id in &cluster.doc_ids {
+            if id != source && pending.contains(id.as_str()) {
+                sources.insert(id.clone(), source.clone());
+            }
+        }
+    }
+    sources
 }
 
 #[cfg(test)]
@@ -452,5 +750,142 @@ mod tests {
         assert!(result.contains("タグ: worklog"));
     }
 
+    fn memo(title: &str, text: &str) -> Document {
+        let date = Utc.with_ymd_and_hms(2025, 1, 15, 10, 0, 0).unwrap();
+        Document::with_content_id(title.to_string(), date, vec![], text.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_apply_diff_updates_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let builder = IndexBuilder::new();
+        let kept = memo("Kept", "unchanged");
+        let edited = memo("Edited", "before");
+        let dropped = memo("Dropped", "gone");
+        builder
+            .build_from_documents(
+                vec![kept.clone(), edited.clone(), dropped.clone()],
+                dir.path(),
+            )
+            .unwrap();
+
+        let mut edited_after = edited.clone();
+        edited_after.text = "after".to_string();
+        let added = memo("Added", "new");
+        let metadata = IndexBuilder::load_existing_metadata(dir.path()).unwrap();
+        let diff = IncrementalDiff::compute(
+            vec![kept.clone(), edited_after.clone(), added.clone()],
+            &metadata.doc_hashes,
+        );
+        builder
+            .apply_diff(&diff, dir.path(), |_, _, _| {})
+            .await
+            .unwrap();
+
+        let docstore = Docstore::load_from_file(&dir.path().join("docstore.json")).unwrap();
+        assert_eq!(docstore.len(), 3);
+        assert!(!docstore.contains(&dropped.id));
+        assert_eq!(docstore.get(&edited.id).unwrap().text, "after");
+        assert!(docstore.contains(&added.id));
+
+        let bm25 = Bm25Index::load_from_file(&dir.path().join("bm25_index.json")).unwrap();
+        assert_eq!(bm25.len(), 3);
+        assert!(bm25.doc_tokens(&dropped.id).is_none());
+
+        let metadata = IndexBuilder::load_existing_metadata(dir.path()).unwrap();
+        assert_eq!(metadata.doc_count, 3);
+        assert_eq!(
+            metadata.get_doc_hash(&edited.id),
+            Some(&edited_after.content_hash())
+        );
+        assert!(metadata.get_doc_hash(&dropped.id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_apply_diff_requires_existing_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let diff = IncrementalDiff::compute(vec![memo("New", "text")], &Default::default());
+        assert!(IndexBuilder::new()
+            .apply_diff(&diff, dir.path(), |_, _, _| {})
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_update_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        let doc = memo("Tagged", "text");
+        IndexBuilder::new()
+            .build_from_documents(vec![doc.clone()], dir.path())
+            .unwrap();
+        let before = IndexBuilder::load_existing_metadata(dir.path()).unwrap();
+
+        let tags = HashMap::from([
+            (doc.id.clone(), vec!["memo".to_string()]),
+            ("missing".to_string(), vec!["memo".to_string()]),
+        ]);
+        assert_eq!(IndexBuilder::update_tags(dir.path(), &tags).unwrap(), 1);
+
+        let docstore = Docstore::load_from_file(&dir.path().join("docstore.json")).unwrap();
+        assert_eq!(docstore.get(&doc.id).unwrap().tags(), &["memo"]);
+        let after = IndexBuilder::load_existing_metadata(dir.path()).unwrap();
+        assert_eq!(after.doc_hashes, before.doc_hashes);
+    }
+
+    #[test]
+    fn test_update_inferred_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        let doc = memo("Untagged", "text");
+        IndexBuilder::new()
+            .build_from_documents(vec![doc.clone()], dir.path())
+            .unwrap();
+
+        let tags = HashMap::from([(doc.id.clone(), vec!["idea".to_string()])]);
+        assert_eq!(
+            IndexBuilder::update_inferred_tags(dir.path(), &tags).unwrap(),
+            1
+        );
+
+        let docstore = Docstore::load_from_file(&dir.path().join("docstore.json")).unwrap();
+        let stored = docstore.get(&doc.id).unwrap();
+        assert!(stored.tags().is_empty());
+        assert_eq!(stored.inferred_tags(), &["idea"]);
+    }
+
+    #[test]
+    fn test_embedding_sources_reuse_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let bm25_path = dir.path().join("bm25_index.json");
+        std::fs::write(
+            &bm25_path,
+            r#"{"version": "1.0", "doc_ids": ["old", "copy", "new1", "new2", "other"],
+                "corpus": [["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"],
+                           ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k"],
+                           ["p", "q", "r", "s"], ["p", "q", "r", "s"], ["x", "y", "z"]]}"#,
+        )
+        .unwrap();
+        let bm25_index = Bm25Index::load_from_file(&bm25_path).unwrap();
+        let mut index = VectorIndex::new(2);
+        index.add("old".to_string(), vec![1.0, 0.0]).unwrap();
+
+        let pending: Vec<Document> = ["copy", "new1", "new2", "other"]
+            .iter()
+            .map(|id| {
+                Document::with_id(
+                    id.to_string(),
+                    id.to_string(),
+                    Utc::now(),
+                    vec![],
+                    String::new(),
+                )
+            })
+            .collect();
+        let sources = embedding_sources(&DuplicateDetector::new(), &pending, &bm25_index, &index);
+
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources["copy"], "old");
+        assert_eq!(sources["new2"], "new1");
+    }
+
     // TODO: Add more tests in Process 12
 }
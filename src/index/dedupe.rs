@@ -0,0 +1,270 @@
+//! Near-duplicate detection
+//!
+//! [`DuplicateDetector`] finds copy-pasted memos with MinHash over shingles
+//! of the tokens the BM25 index stores. Locality-sensitive hashing of the
+//! signatures proposes candidate pairs, which are confirmed by the exact
+//! Jaccard similarity of their shingles and, optionally, by the cosine
+//! similarity of their embeddings.
+
+use super::VectorIndex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Default Jaccard similarity above which two memos are duplicates
+pub const DEFAULT_DUPLICATE_THRESHOLD: f32 = 0.8;
+
+/// Number of MinHash functions in a signature
+const NUM_HASHES: usize = 128;
+
+/// Signature rows per LSH band (32 bands of 4 rows)
+const BAND_ROWS: usize = 4;
+
+/// Tokens per shingle
+const SHINGLE_SIZE: usize = 2;
+
+/// A group of memos that are near-duplicates of each other
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuplicateCluster {
+    /// Document IDs, in the order they were given to the detector
+    pub doc_ids: Vec<String>,
+    /// Lowest similarity among the pairs that joined the cluster
+    pub similarity: f32,
+}
+
+/// Finds near-duplicate documents from their tokens
+#[derive(Debug, Clone)]
+pub struct DuplicateDetector {
+    threshold: f32,
+    vector_threshold: Option<f32>,
+}
+
+impl Default for DuplicateDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DuplicateDetector {
+    /// Create a detector with the default threshold
+    pub fn new() -> Self {
+        Self {
+            threshold: DEFAULT_DUPLICATE_THRESHOLD,
+            vector_threshold: None,
+        }
+    }
+
+    /// Set the Jaccard similarity (0-1) above which memos are duplicates
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Also require this cosine similarity when both memos have embeddings
+    pub fn with_vector_threshold(mut self, threshold: Option<f32>) -> Self {
+        self.vector_threshold = threshold;
+        self
+    }
+
+    /// Find clusters of near-duplicates among `(doc_id, tokens)` pairs
+    ///
+    /// Documents without tokens are never duplicates. When `vectors` is
+    /// given and a vector threshold is set, pairs whose embeddings are less
+    /// similar are rejected; pairs missing an embedding are judged on tokens
+    /// alone.
+    pub fn find<'a, I>(&self, docs: I, vectors: Option<&VectorIndex>) -> Vec<DuplicateCluster>
+    where
+        I: IntoIterator<Item = (&'a str, &'a [String])>,
+    {
+        let docs: Vec<(&str, HashSet<u64>)> = docs
+            .into_iter()
+            .map(|(id, tokens)| (id, shingles(tokens)))
+            .filter(|(_, shingles)| !shingles.is_empty())
+            .collect();
+
+        // Documents sharing any band of their signature are candidates
+        let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+        for (i, (_, shingles)) in docs.iter().enumerate() {
+            for (band, rows) in signature(shingles).chunks(BAND_ROWS).enumerate() {
+                buckets.entry((band, hash_of(rows))).or_default().push(i);
+            }
+        }
+        let mut candidates: Vec<(usize, usize)> = buckets
+            .values()
+            .flat_map(|members| {
+                members
+                    .iter()
+                    .enumerate()
+                    .flat_map(move |(n, &a)| members[n + 1..].iter().map(move |&b| (a, b)))
+            })
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        candidates.sort_unstable();
+
+        let mut parents: Vec<usize> = (0..docs.len()).collect();
+        let mut edges = Vec::new();
+        for (a, b) in candidates {
+            let similarity = jaccard(&docs[a].1, &docs[b].1);
+            if similarity < self.threshold || !self.vectors_agree(vectors, docs[a].0, docs[b].0) {
+                continue;
+            }
+            let (root_a, root_b) = (find_root(&mut parents, a), find_root(&mut parents, b));
+            parents[root_a.max(root_b)] = root_a.min(root_b);
+            edges.push((a, similarity));
+        }
+
+        let mut clusters: HashMap<usize, DuplicateCluster> = HashMap::new();
+        for (a, similarity) in edges {
+            let root = find_root(&mut parents, a);
+            let cluster = clusters.entry(root).or_insert(DuplicateCluster {
+                doc_ids: Vec::new(),
+                similarity,
+            });
+            cluster.similarity = cluster.similarity.min(similarity);
+        }
+        for (i, (id, _)) in docs.iter().enumerate() {
+            let root = find_root(&mut parents, i);
+            if let Some(cluster) = clusters.get_mut(&root) {
+                cluster.doc_ids.push(id.to_string());
+            }
+        }
+
+        let mut clusters: Vec<(usize, DuplicateCluster)> = clusters.into_iter().collect();
+        clusters.sort_by_key(|(root, _)| *root);
+        clusters.into_iter().map(|(_, cluster)| cluster).collect()
+    }
+
+    /// Check the embedding similarity of a candidate pair
+    fn vectors_agree(&self, vectors: Option<&VectorIndex>, a: &str, b: &str) -> bool {
+        let (Some(threshold), Some(vectors)) = (self.vector_threshold, vectors) else {
+            return true;
+        };
+        match (vectors.get_vector(a), vectors.get_vector(b)) {
+            (Some(a), Some(b)) => VectorIndex::cosine_similarity(a, b) >= threshold,
+            _ => true,
+        }
+    }
+}
+
+/// Hashed token shingles; documents shorter than a shingle use their tokens
+fn shingles(tokens: &[String]) -> HashSet<u64> {
+    if tokens.len() < SHINGLE_SIZE {
+        return tokens.iter().map(hash_of).collect();
+    }
+    tokens.windows(SHINGLE_SIZE).map(hash_of).collect()
+}
+
+/// MinHash signature of a shingle set
+fn signature(shingles: &HashSet<u64>) -> Vec<u64> {
+    (0..NUM_HASHES as u64)
+        .map(|i| {
+            let seed = mix(i);
+            shingles
+                .iter()
+                .map(|shingle| mix(shingle ^ seed))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+/// Exact Jaccard similarity of two shingle sets
+fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f32 {
+    let shared = a.intersection(b).count();
+    shared as f32 / (a.len() + b.len() - shared) as f32
+}
+
+fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// SplitMix64 finalizer, used to derive independent hash functions
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn find_root(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(text: &str) -> Vec<String> {
+        text.split_whitespace().map(String::from).collect()
+    }
+
+    fn find(detector: &DuplicateDetector, docs: &[(&str, Vec<String>)]) -> Vec<DuplicateCluster> {
+        detector.find(docs.iter().map(|(id, t)| (*id, t.as_slice())), None)
+    }
+
+    #[test]
+    fn test_finds_near_duplicates() {
+        let docs = vec![
+            (
+                "a",
+                tokens("deploy the api server after running the database migrations"),
+            ),
+            ("b", tokens("weekly sync notes about the hiring plan")),
+            (
+                "c",
+                tokens("deploy the api server after running the database migrations today"),
+            ),
+            ("d", vec![]),
+        ];
+
+        let clusters = find(&DuplicateDetector::new(), &docs);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].doc_ids, vec!["a", "c"]);
+        assert!(clusters[0].similarity >= DEFAULT_DUPLICATE_THRESHOLD);
+        assert!(clusters[0].similarity < 1.0);
+    }
+
+    #[test]
+    fn test_clusters_are_transitive() {
+        let base = "one two three four five six seven eight nine ten";
+        let docs = vec![
+            ("a", tokens(base)),
+            ("b", tokens(&format!("{} eleven", base))),
+            ("c", tokens(&format!("{} eleven twelve", base))),
+        ];
+
+        let clusters = find(&DuplicateDetector::new(), &docs);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].doc_ids, vec!["a", "b", "c"]);
+
+        let strict = find(&DuplicateDetector::new().with_threshold(1.0), &docs);
+        assert!(strict.is_empty());
+    }
+
+    #[test]
+    fn test_vector_threshold_rejects_pairs() {
+        let docs = [
+            ("a", tokens("same words here")),
+            ("b", tokens("same words here")),
+        ];
+        let mut vectors = VectorIndex::new(2);
+        vectors.add("a".to_string(), vec![1.0, 0.0]).unwrap();
+        vectors.add("b".to_string(), vec![0.0, 1.0]).unwrap();
+        let iter = || docs.iter().map(|(id, t)| (*id, t.as_slice()));
+
+        let detector = DuplicateDetector::new();
+        assert_eq!(detector.find(iter(), Some(&vectors)).len(), 1);
+
+        let detector = detector.with_vector_threshold(Some(0.9));
+        assert!(detector.find(iter(), Some(&vectors)).is_empty());
+        // Without embeddings the token similarity decides
+        assert_eq!(detector.find(iter(), None).len(), 1);
+    }
+}
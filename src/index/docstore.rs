@@ -2,12 +2,26 @@
 //!
 //! Provides document storage and retrieval.
 
+use super::timeline::{counts_by_period, Period};
 use crate::loader::Document;
+use crate::pagination::{paginate, Page};
 use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
+/// Number of documents carrying a tag
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagCount {
+    /// Tag name
+    pub tag: String,
+    /// Documents with the tag written in the changelog
+    pub authored: usize,
+    /// Documents with the tag only inferred by `digrag suggest-tags`
+    pub inferred: usize,
+}
+
 /// Document store for retrieving full document content
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Docstore {
@@ -62,6 +76,32 @@ impl Docstore {
             .collect()
     }
 
+    /// Replace the tags of a document
+    ///
+    /// Returns false if the document does not exist.
+    pub fn set_tags(&mut self, doc_id: &str, tags: Vec<String>) -> bool {
+        match self.documents.get_mut(doc_id) {
+            Some(doc) => {
+                doc.metadata.tags = tags;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replace the inferred tags of a document
+    ///
+    /// Returns false if the document does not exist.
+    pub fn set_inferred_tags(&mut self, doc_id: &str, tags: Vec<String>) -> bool {
+        match self.documents.get_mut(doc_id) {
+            Some(doc) => {
+                doc.metadata.inferred_tags = tags;
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Get all unique tags
     pub fn get_all_tags(&self) -> Vec<String> {
         let mut tags: Vec<String> = self
@@ -74,11 +114,112 @@ impl Docstore {
         tags
     }
 
-    /// Get recent documents (sorted by date descending)
+    /// Count the documents of every authored or inferred tag
+    ///
+    /// A document that has a tag both authored and inferred counts as
+    /// authored. Tags are sorted by name.
+    pub fn tag_counts(&self) -> Vec<TagCount> {
+        let mut counts: BTreeMap<&str, TagCount> = BTreeMap::new();
+        for doc in self.documents.values() {
+            for tag in doc.tags() {
+                counts.entry(tag).or_default().authored += 1;
+            }
+            for tag in doc.inferred_tags() {
+                if !doc.tags().contains(tag) {
+                    counts.entry(tag).or_default().inferred += 1;
+                }
+            }
+        }
+        counts
+            .into_iter()
+            .map(|(tag, count)| TagCount {
+                tag: tag.to_string(),
+                ..count
+            })
+            .collect()
+    }
+
+    /// Get recent documents (sorted by date descending, then ID)
     pub fn get_recent(&self, limit: usize) -> Vec<&Document> {
+        self.sorted_by_recency().into_iter().take(limit).collect()
+    }
+
+    /// Get a page of recent documents
+    ///
+    /// A cursor from a previous page takes precedence over the offset.
+    pub fn get_recent_page(
+        &self,
+        offset: usize,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<Page<&Document>> {
+        paginate(
+            self.sorted_by_recency(),
+            |doc| doc.id.as_str(),
+            offset,
+            cursor,
+            limit,
+        )
+    }
+
+    /// Get up to `count` documents dated before and after a document
+    ///
+    /// Returns `(previous, next)`, both in chronological order, or None if the
+    /// document does not exist.
+    pub fn get_neighbors(
+        &self,
+        doc_id: &str,
+        count: usize,
+    ) -> Option<(Vec<&Document>, Vec<&Document>)> {
+        let docs = self.sorted_by_recency();
+        let pos = docs.iter().position(|doc| doc.id == doc_id)?;
+
+        // Documents are newest first, so older entries follow the position
+        let previous: Vec<&Document> = docs[pos + 1..].iter().take(count).rev().copied().collect();
+        let next: Vec<&Document> = docs[pos.saturating_sub(count)..pos]
+            .iter()
+            .rev()
+            .copied()
+            .collect();
+
+        Some((previous, next))
+    }
+
+    /// Get documents dated within `[start, end)` in chronological order
+    pub fn get_by_date_range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<&Document> {
+        let mut docs: Vec<&Document> = self
+            .documents
+            .values()
+            .filter(|doc| doc.date() >= start && doc.date() < end)
+            .collect();
+        docs.sort_by(|a, b| a.date().cmp(&b.date()).then_with(|| a.id.cmp(&b.id)));
+        docs
+    }
+
+    /// Count documents per period, keyed by the first day of the period
+    pub fn counts_by_period(&self, period: Period) -> BTreeMap<NaiveDate, usize> {
+        counts_by_period(self.documents.values(), period)
+    }
+
+    /// Count documents per tag per period
+    pub fn tag_counts_by_period(
+        &self,
+        period: Period,
+    ) -> BTreeMap<String, BTreeMap<NaiveDate, usize>> {
+        self.get_all_tags()
+            .into_iter()
+            .map(|tag| {
+                let counts = counts_by_period(self.get_by_tag(&tag), period);
+                (tag, counts)
+            })
+            .collect()
+    }
+
+    /// All documents sorted by date descending, with ID as tie-breaker
+    fn sorted_by_recency(&self) -> Vec<&Document> {
         let mut docs: Vec<_> = self.documents.values().collect();
-        docs.sort_by_key(|d| std::cmp::Reverse(d.date()));
-        docs.into_iter().take(limit).collect()
+        docs.sort_by(|a, b| b.date().cmp(&a.date()).then_with(|| a.id.cmp(&b.id)));
+        docs
     }
 
     /// Save store to file
@@ -181,6 +322,26 @@ mod tests {
         assert_eq!(worklog_docs.len(), 2);
     }
 
+    #[test]
+    fn test_tag_counts_separate_inferred_tags() {
+        let mut store = Docstore::new();
+        store.add(create_test_doc("doc1", "Test 1", vec!["memo"], 0));
+        store.add(create_test_doc("doc2", "Test 2", vec!["memo"], 0));
+        store.add(create_test_doc("doc3", "Test 3", vec![], 0));
+        store.set_inferred_tags("doc2", vec!["memo".to_string()]);
+        store.set_inferred_tags("doc3", vec!["memo".to_string(), "idea".to_string()]);
+
+        let count = |tag: &str, authored, inferred| TagCount {
+            tag: tag.to_string(),
+            authored,
+            inferred,
+        };
+        assert_eq!(
+            store.tag_counts(),
+            vec![count("idea", 0, 1), count("memo", 2, 1)]
+        );
+    }
+
     #[test]
     fn test_get_all_tags() {
         let mut store = Docstore::new();
@@ -207,6 +368,105 @@ mod tests {
         assert_eq!(recent[1].title(), "Middle");
     }
 
+    #[test]
+    fn test_get_recent_page_with_ties() {
+        let mut store = Docstore::new();
+        let date = Utc::now();
+        for id in ["c", "a", "b"] {
+            store.add(Document::with_id(
+                id.to_string(),
+                id.to_string(),
+                date,
+                vec![],
+                "Content".to_string(),
+            ));
+        }
+
+        let first = store.get_recent_page(0, None, 2).unwrap();
+        assert_eq!(first.items[0].id, "a");
+        assert_eq!(first.items[1].id, "b");
+        assert_eq!(first.total, 3);
+
+        let second = store
+            .get_recent_page(0, first.next_cursor.as_deref(), 2)
+            .unwrap();
+        assert_eq!(second.items.len(), 1);
+        assert_eq!(second.items[0].id, "c");
+        assert!(!second.has_more());
+
+        let by_offset = store.get_recent_page(1, None, 1).unwrap();
+        assert_eq!(by_offset.items[0].id, "b");
+    }
+
+    #[test]
+    fn test_get_neighbors() {
+        let mut store = Docstore::new();
+        store.add(create_test_doc("doc1", "Oldest", vec![], 10));
+        store.add(create_test_doc("doc2", "Older", vec![], 5));
+        store.add(create_test_doc("doc3", "Middle", vec![], 3));
+        store.add(create_test_doc("doc4", "Newer", vec![], 1));
+        store.add(create_test_doc("doc5", "Newest", vec![], 0));
+
+        let (previous, next) = store.get_neighbors("doc3", 2).unwrap();
+        let previous: Vec<&str> = previous.iter().map(|d| d.id.as_str()).collect();
+        let next: Vec<&str> = next.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(previous, vec!["doc1", "doc2"]);
+        assert_eq!(next, vec!["doc4", "doc5"]);
+
+        let (previous, next) = store.get_neighbors("doc5", 1).unwrap();
+        assert_eq!(previous[0].id, "doc4");
+        assert!(next.is_empty());
+
+        assert!(store.get_neighbors("missing", 1).is_none());
+    }
+
+    #[test]
+    fn test_get_by_date_range() {
+        let mut store = Docstore::new();
+        store.add(create_test_doc("doc1", "Old", vec![], 10));
+        store.add(create_test_doc("doc2", "Recent", vec![], 3));
+        store.add(create_test_doc("doc3", "Newest", vec![], 1));
+
+        let start = Utc::now() - chrono::Duration::days(7);
+        let docs = store.get_by_date_range(start, Utc::now());
+        let ids: Vec<&str> = docs.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, vec!["doc2", "doc3"]);
+
+        let end = Utc::now() - chrono::Duration::days(2);
+        assert_eq!(store.get_by_date_range(start, end).len(), 1);
+    }
+
+    #[test]
+    fn test_counts_by_period() {
+        use chrono::TimeZone;
+
+        let mut store = Docstore::new();
+        for (id, month, tags) in [
+            ("doc1", 1, vec!["memo"]),
+            ("doc2", 1, vec!["memo", "worklog"]),
+            ("doc3", 3, vec!["worklog"]),
+        ] {
+            store.add(Document::with_id(
+                id.to_string(),
+                id.to_string(),
+                Utc.with_ymd_and_hms(2025, month, 15, 10, 0, 0).unwrap(),
+                tags.into_iter().map(String::from).collect(),
+                "Content".to_string(),
+            ));
+        }
+        let month = |m| NaiveDate::from_ymd_opt(2025, m, 1).unwrap();
+
+        let counts = store.counts_by_period(Period::Month);
+        assert_eq!(
+            counts.into_iter().collect::<Vec<_>>(),
+            vec![(month(1), 2), (month(3), 1)]
+        );
+
+        let by_tag = store.tag_counts_by_period(Period::Month);
+        assert_eq!(by_tag["memo"][&month(1)], 2);
+        assert_eq!(by_tag["worklog"].len(), 2);
+    }
+
     #[test]
     fn test_docstore_serialization() {
         let mut store = Docstore::new();
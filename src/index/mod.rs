@@ -4,14 +4,23 @@
 
 mod bm25;
 mod builder;
+mod dedupe;
 mod diff;
 mod docstore;
 mod metadata;
+mod timeline;
+mod topics;
 mod vector;
+mod watch;
 
 pub use bm25::Bm25Index;
+pub(crate) use builder::create_embedding_text;
 pub use builder::IndexBuilder;
+pub use dedupe::{DuplicateCluster, DuplicateDetector, DEFAULT_DUPLICATE_THRESHOLD};
 pub use diff::IncrementalDiff;
-pub use docstore::Docstore;
+pub use docstore::{Docstore, TagCount};
 pub use metadata::IndexMetadata;
+pub use timeline::{Period, Timeline, TimelineBucket, TimelineMemo, DEFAULT_TIMELINE_TITLES};
+pub use topics::{Topic, TopicClusterer, TopicModel, DEFAULT_TOPIC_TERMS, TOPICS_FILE};
 pub use vector::VectorIndex;
+pub use watch::{IndexWatcher, DEFAULT_DEBOUNCE, DEFAULT_WATCH_INTERVAL};
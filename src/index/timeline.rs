@@ -0,0 +1,297 @@
+//! Activity timelines
+//!
+//! Groups documents into calendar periods (days, ISO weeks or months) to
+//! show how much was written when. [`Timeline`] turns a list of documents
+//! into a histogram with the most relevant titles of each period; the
+//! [`Docstore`](super::Docstore) offers plain counts per period and per tag.
+
+use crate::loader::Document;
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Default number of titles listed per period
+pub const DEFAULT_TIMELINE_TITLES: usize = 3;
+
+/// Length of a timeline bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Day,
+    #[default]
+    Week,
+    Month,
+}
+
+impl Period {
+    /// Parse "day", "week" or "month"
+    pub fn parse(period: &str) -> Result<Self> {
+        match period.trim().to_lowercase().as_str() {
+            "day" | "daily" => Ok(Period::Day),
+            "week" | "weekly" => Ok(Period::Week),
+            "month" | "monthly" => Ok(Period::Month),
+            other => Err(anyhow!(
+                "Unknown period '{}' (expected day, week or month)",
+                other
+            )),
+        }
+    }
+
+    /// First day of the period containing a date (weeks start on Monday)
+    pub fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Period::Month => date.with_day(1).expect("day 1 exists in every month"),
+        }
+    }
+
+    /// First day of the following period
+    pub fn next(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => start + Duration::days(1),
+            Period::Week => start + Duration::days(7),
+            Period::Month => start + Months::new(1),
+        }
+    }
+
+    /// Label of the period starting at `start`, e.g. "2025-01-15",
+    /// "2025-W03" or "2025-01"
+    pub fn label(self, start: NaiveDate) -> String {
+        match self {
+            Period::Day => start.format("%Y-%m-%d").to_string(),
+            Period::Week => {
+                let week = start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Period::Month => start.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Period::Day => "day",
+            Period::Week => "week",
+            Period::Month => "month",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A memo listed in a timeline bucket
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineMemo {
+    /// Document ID
+    pub doc_id: String,
+    /// Document title
+    pub title: String,
+    /// Document date (YYYY-MM-DD)
+    pub date: String,
+}
+
+/// Activity in one period
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineBucket {
+    /// Period label, e.g. "2025-W03"
+    pub label: String,
+    /// First day of the period (YYYY-MM-DD)
+    pub start: String,
+    /// Number of memos in the period
+    pub count: usize,
+    /// Memo counts per tag in the period
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, usize>,
+    /// Top memos of the period
+    pub memos: Vec<TimelineMemo>,
+}
+
+/// Builds a histogram of documents over time
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    period: Period,
+    titles: usize,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self::new(Period::default())
+    }
+}
+
+impl Timeline {
+    /// Create a timeline with the given bucket length
+    pub fn new(period: Period) -> Self {
+        Self {
+            period,
+            titles: DEFAULT_TIMELINE_TITLES,
+            from: None,
+            to: None,
+        }
+    }
+
+    /// Set how many titles are listed per period
+    pub fn with_titles(mut self, titles: usize) -> Self {
+        self.titles = titles;
+        self
+    }
+
+    /// Restrict the timeline to an inclusive date range
+    pub fn with_range(mut self, from: Option<NaiveDate>, to: Option<NaiveDate>) -> Self {
+        self.from = from;
+        self.to = to;
+        self
+    }
+
+    /// Bucket length
+    pub fn period(&self) -> Period {
+        self.period
+    }
+
+    /// Group documents into periods, oldest first
+    ///
+    /// Documents should be given in priority order (e.g. by relevance):
+    /// each period lists the first ones it receives. Periods without memos
+    /// between the first and last (or the requested range) are included so
+    /// that gaps show up in the histogram.
+    pub fn build<'a, I>(&self, docs: I) -> Vec<TimelineBucket>
+    where
+        I: IntoIterator<Item = &'a Document>,
+    {
+        let mut buckets: BTreeMap<NaiveDate, TimelineBucket> = BTreeMap::new();
+        for doc in docs {
+            let date = doc.date().date_naive();
+            if self.from.is_some_and(|from| date < from) || self.to.is_some_and(|to| date > to) {
+                continue;
+            }
+            let start = self.period.start_of(date);
+            let bucket = buckets
+                .entry(start)
+                .or_insert_with(|| self.empty_bucket(start));
+            bucket.count += 1;
+            for tag in doc.tags() {
+                *bucket.tags.entry(tag.clone()).or_insert(0) += 1;
+            }
+            if bucket.memos.len() < self.titles {
+                bucket.memos.push(TimelineMemo {
+                    doc_id: doc.id.clone(),
+                    title: doc.title().to_string(),
+                    date: date.format("%Y-%m-%d").to_string(),
+                });
+            }
+        }
+
+        let first = self
+            .from
+            .or_else(|| buckets.keys().next().copied())
+            .map(|date| self.period.start_of(date));
+        let last = self.to.or_else(|| buckets.keys().next_back().copied());
+        let (Some(first), Some(last)) = (first, last) else {
+            return Vec::new();
+        };
+
+        let mut start = first;
+        while start <= last {
+            buckets
+                .entry(start)
+                .or_insert_with(|| self.empty_bucket(start));
+            start = self.period.next(start);
+        }
+        buckets.into_values().collect()
+    }
+
+    fn empty_bucket(&self, start: NaiveDate) -> TimelineBucket {
+        TimelineBucket {
+            label: self.period.label(start),
+            start: start.format("%Y-%m-%d").to_string(),
+            count: 0,
+            tags: BTreeMap::new(),
+            memos: Vec::new(),
+        }
+    }
+}
+
+/// Count documents per period, keyed by the first day of the period
+pub(crate) fn counts_by_period<'a, I>(docs: I, period: Period) -> BTreeMap<NaiveDate, usize>
+where
+    I: IntoIterator<Item = &'a Document>,
+{
+    let mut counts = BTreeMap::new();
+    for doc in docs {
+        *counts
+            .entry(period.start_of(doc.date().date_naive()))
+            .or_insert(0) += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn doc(id: &str, day: u32, tags: &[&str]) -> Document {
+        Document::with_id(
+            id.to_string(),
+            format!("memo {}", id),
+            Utc.with_ymd_and_hms(2025, 1, day, 10, 0, 0).unwrap(),
+            tags.iter().map(|t| t.to_string()).collect(),
+            String::new(),
+        )
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+    }
+
+    #[test]
+    fn test_period_boundaries() {
+        // 2025-01-15 is a Wednesday in ISO week 3
+        assert_eq!(Period::Week.start_of(date(15)), date(13));
+        assert_eq!(Period::Week.label(date(13)), "2025-W03");
+        assert_eq!(Period::Month.start_of(date(15)), date(1));
+        assert_eq!(
+            Period::Month.next(date(1)),
+            NaiveDate::from_ymd_opt(2025, 2, 1).unwrap()
+        );
+        assert_eq!(Period::Day.label(date(15)), "2025-01-15");
+        assert_eq!(Period::parse("Monthly").unwrap(), Period::Month);
+        assert!(Period::parse("year").is_err());
+    }
+
+    #[test]
+    fn test_build_fills_gaps_and_keeps_order() {
+        let docs = [
+            doc("b", 3, &["rust"]),
+            doc("a", 1, &["rust", "cli"]),
+            doc("c", 20, &[]),
+        ];
+        let buckets = Timeline::new(Period::Week).with_titles(1).build(&docs);
+
+        let labels: Vec<&str> = buckets.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, vec!["2025-W01", "2025-W02", "2025-W03", "2025-W04"]);
+        let counts: Vec<usize> = buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![2, 0, 0, 1]);
+        // The first document given wins the only title slot
+        assert_eq!(buckets[0].memos[0].doc_id, "b");
+        assert_eq!(buckets[0].tags["rust"], 2);
+        assert_eq!(buckets[0].tags["cli"], 1);
+    }
+
+    #[test]
+    fn test_build_with_range() {
+        let docs = [doc("a", 1, &[]), doc("b", 10, &[]), doc("c", 20, &[])];
+        let buckets = Timeline::new(Period::Day)
+            .with_range(Some(date(9)), Some(date(11)))
+            .build(&docs);
+
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(buckets[1].label, "2025-01-10");
+        assert_eq!(buckets.iter().map(|b| b.count).sum::<usize>(), 1);
+        assert!(Timeline::default().build(&[]).is_empty());
+    }
+}
@@ -0,0 +1,484 @@
+//! Topic clustering
+//!
+//! [`TopicClusterer`] groups documents into topics with spherical k-means
+//! over their embeddings, or over tf-idf vectors of their BM25 tokens when
+//! the index has no embeddings. Each topic is labelled with the terms most
+//! distinctive of its documents. The resulting [`TopicModel`] is stored as
+//! `topics.json` in the index directory.
+
+use super::{Bm25Index, VectorIndex};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// File name of the topic model in an index directory
+pub const TOPICS_FILE: &str = "topics.json";
+
+/// Default number of terms in a topic label
+pub const DEFAULT_TOPIC_TERMS: usize = 3;
+
+/// Upper bound for the automatically chosen number of topics
+const MAX_DEFAULT_TOPICS: usize = 50;
+
+/// Maximum k-means iterations
+const MAX_ITERATIONS: usize = 50;
+
+/// A sparse, L2-normalized feature vector
+type Features = Vec<(usize, f32)>;
+
+/// A cluster of related documents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Topic {
+    /// Topic number, starting at 1 (largest topic first)
+    pub id: usize,
+    /// Human-readable label built from the topic terms
+    pub label: String,
+    /// Most distinctive terms of the topic's documents
+    pub terms: Vec<String>,
+    /// Documents assigned to the topic
+    pub doc_ids: Vec<String>,
+}
+
+/// Topic assignments of an index
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TopicModel {
+    /// Features the clustering used: "embeddings" or "tfidf"
+    pub source: String,
+    /// When the topics were computed (RFC 3339)
+    pub created_at: String,
+    /// Topics, largest first
+    pub topics: Vec<Topic>,
+    /// Topic of each document
+    #[serde(skip)]
+    assignments: HashMap<String, usize>,
+}
+
+impl TopicModel {
+    /// Create a model from topics
+    pub fn new(source: &str, topics: Vec<Topic>) -> Self {
+        let mut model = Self {
+            source: source.to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            topics,
+            assignments: HashMap::new(),
+        };
+        model.index_assignments();
+        model
+    }
+
+    fn index_assignments(&mut self) {
+        self.assignments = self
+            .topics
+            .iter()
+            .flat_map(|topic| topic.doc_ids.iter().map(|id| (id.clone(), topic.id)))
+            .collect();
+    }
+
+    /// Get a topic by ID
+    pub fn get(&self, id: usize) -> Option<&Topic> {
+        self.topics.iter().find(|topic| topic.id == id)
+    }
+
+    /// Topic a document is assigned to
+    pub fn topic_of(&self, doc_id: &str) -> Option<usize> {
+        self.assignments.get(doc_id).copied()
+    }
+
+    /// Number of topics
+    pub fn len(&self) -> usize {
+        self.topics.len()
+    }
+
+    /// Check if there are no topics
+    pub fn is_empty(&self) -> bool {
+        self.topics.is_empty()
+    }
+
+    /// Save to a JSON file
+    pub fn save_to_file(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write topics: {:?}", path))?;
+        Ok(())
+    }
+
+    /// Load from a JSON file
+    pub fn load_from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read topics: {:?}", path))?;
+        let mut model: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse topics: {:?}", path))?;
+        model.index_assignments();
+        Ok(model)
+    }
+}
+
+/// Clusters the documents of an index into topics
+#[derive(Debug, Clone)]
+pub struct TopicClusterer {
+    topics: Option<usize>,
+    label_terms: usize,
+}
+
+impl Default for TopicClusterer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TopicClusterer {
+    /// Create a clusterer choosing the number of topics from the corpus size
+    pub fn new() -> Self {
+        Self {
+            topics: None,
+            label_terms: DEFAULT_TOPIC_TERMS,
+        }
+    }
+
+    /// Set the number of topics (None chooses about sqrt(n / 2))
+    pub fn with_topics(mut self, topics: Option<usize>) -> Self {
+        self.topics = topics.map(|k| k.max(1));
+        self
+    }
+
+    /// Set the number of terms in each label
+    pub fn with_label_terms(mut self, terms: usize) -> Self {
+        self.label_terms = terms;
+        self
+    }
+
+    /// Cluster the documents of an index
+    ///
+    /// Uses the stored embeddings when there are any; documents without an
+    /// embedding (or, for tf-idf, without tokens) are left unassigned.
+    pub fn cluster(&self, bm25_index: &Bm25Index, vector_index: &VectorIndex) -> TopicModel {
+        let (source, points) = if vector_index.is_empty() {
+            ("tfidf", tfidf_features(bm25_index))
+        } else {
+            ("embeddings", embedding_features(bm25_index, vector_index))
+        };
+        if points.is_empty() {
+            return TopicModel::new(source, Vec::new());
+        }
+
+        let k = self
+            .topics
+            .unwrap_or_else(|| default_topic_count(points.len()))
+            .min(points.len());
+        let assignments = kmeans(&points, k);
+
+        let mut clusters: Vec<Vec<&str>> = vec![Vec::new(); k];
+        for ((doc_id, _), cluster) in points.iter().zip(assignments) {
+            clusters[cluster].push(doc_id);
+        }
+        clusters.retain(|docs| !docs.is_empty());
+        clusters.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(b[0])));
+
+        let tokens: HashMap<&str, &[String]> = bm25_index.documents().collect();
+        let labels = distinctive_terms(&clusters, &tokens, self.label_terms);
+        let topics = clusters
+            .into_iter()
+            .zip(labels)
+            .enumerate()
+            .map(|(i, (docs, terms))| Topic {
+                id: i + 1,
+                label: if terms.is_empty() {
+                    format!("topic {}", i + 1)
+                } else {
+                    terms.join(", ")
+                },
+                terms,
+                doc_ids: docs.into_iter().map(String::from).collect(),
+            })
+            .collect();
+        TopicModel::new(source, topics)
+    }
+}
+
+/// About sqrt(n / 2) topics, between 1 and [`MAX_DEFAULT_TOPICS`]
+fn default_topic_count(documents: usize) -> usize {
+    ((documents as f64 / 2.0).sqrt().round() as usize).clamp(1, MAX_DEFAULT_TOPICS)
+}
+
+/// Normalized embeddings of the documents that have one
+fn embedding_features<'a>(
+    bm25_index: &'a Bm25Index,
+    vector_index: &VectorIndex,
+) -> Vec<(&'a str, Features)> {
+    bm25_index
+        .documents()
+        .filter_map(|(id, _)| {
+            let vector = vector_index.get_vector(id)?;
+            normalize(vector.iter().copied().enumerate().collect()).map(|f| (id, f))
+        })
+        .collect()
+}
+
+/// Normalized tf-idf vectors of the documents' BM25 tokens
+fn tfidf_features(bm25_index: &Bm25Index) -> Vec<(&str, Features)> {
+    let mut vocabulary: HashMap<&str, usize> = HashMap::new();
+    let mut doc_frequencies: Vec<usize> = Vec::new();
+    let mut counts: Vec<(&str, HashMap<usize, usize>)> = Vec::new();
+    for (id, tokens) in bm25_index.documents() {
+        let mut tf: HashMap<usize, usize> = HashMap::new();
+        for token in tokens {
+            let next = vocabulary.len();
+            let term = *vocabulary.entry(token.as_str()).or_insert(next);
+            if term == doc_frequencies.len() {
+                doc_frequencies.push(0);
+            }
+            *tf.entry(term).or_default() += 1;
+        }
+        for term in tf.keys() {
+            doc_frequencies[*term] += 1;
+        }
+        counts.push((id, tf));
+    }
+
+    let n = counts.len() as f32;
+    counts
+        .into_iter()
+        .filter_map(|(id, tf)| {
+            let mut features: Features = tf
+                .into_iter()
+                .map(|(term, count)| {
+                    let idf = ((n + 1.0) / (doc_frequencies[term] as f32 + 1.0)).ln() + 1.0;
+                    (term, count as f32 * idf)
+                })
+                .collect();
+            features.sort_unstable_by_key(|(term, _)| *term);
+            normalize(features).map(|f| (id, f))
+        })
+        .collect()
+}
+
+/// Scale to unit length, or None for a zero vector
+fn normalize(mut features: Features) -> Option<Features> {
+    let norm = features.iter().map(|(_, v)| v * v).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return None;
+    }
+    for (_, value) in &mut features {
+        *value /= norm;
+    }
+    Some(features)
+}
+
+fn dot(features: &Features, centroid: &[f32]) -> f32 {
+    features.iter().map(|(i, v)| v * centroid[*i]).sum()
+}
+
+fn densify(features: &Features, dimension: usize) -> Vec<f32> {
+    let mut dense = vec![0.0; dimension];
+    for (i, v) in features {
+        dense[*i] = *v;
+    }
+    dense
+}
+
+/// Spherical k-means with deterministic farthest-point seeding
+///
+/// Returns the cluster of each point.
+fn kmeans(points: &[(&str, Features)], k: usize) -> Vec<usize> {
+    let dimension = points
+        .iter()
+        .flat_map(|(_, f)| f.iter().map(|(i, _)| i + 1))
+        .max()
+        .unwrap_or(0);
+
+    // Seed with the first point, then repeatedly the point least similar
+    // to every seed so far
+    let mut centroids = vec![densify(&points[0].1, dimension)];
+    let mut nearest: Vec<f32> = points.iter().map(|(_, f)| dot(f, &centroids[0])).collect();
+    while centroids.len() < k {
+        let (next, similarity) =
+            nearest.iter().enumerate().fold(
+                (0, f32::MAX),
+                |best, (i, &s)| if s < best.1 { (i, s) } else { best },
+            );
+        if similarity >= 1.0 - 1e-6 {
+            break;
+        }
+        let centroid = densify(&points[next].1, dimension);
+        for (best, (_, f)) in nearest.iter_mut().zip(points) {
+            *best = best.max(dot(f, &centroid));
+        }
+        centroids.push(centroid);
+    }
+
+    let mut assignments = vec![usize::MAX; points.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let mut changed = false;
+        for ((_, f), assignment) in points.iter().zip(assignments.iter_mut()) {
+            let closest = centroids
+                .iter()
+                .enumerate()
+                .map(|(c, centroid)| (c, dot(f, centroid)))
+                .fold(
+                    (0, f32::MIN),
+                    |best, (c, s)| if s > best.1 { (c, s) } else { best },
+                )
+                .0;
+            if *assignment != closest {
+                *assignment = closest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+
+        let mut sums = vec![vec![0.0; dimension]; centroids.len()];
+        for ((_, f), &cluster) in points.iter().zip(&assignments) {
+            for (i, v) in f {
+                sums[cluster][*i] += v;
+            }
+        }
+        for (centroid, sum) in centroids.iter_mut().zip(sums) {
+            let norm = sum.iter().map(|v| v * v).sum::<f32>().sqrt();
+            // An emptied cluster keeps its previous centroid
+            if norm > 0.0 {
+                *centroid = sum.into_iter().map(|v| v / norm).collect();
+            }
+        }
+    }
+    assignments
+}
+
+/// Terms over-represented in each cluster compared to the whole corpus
+fn distinctive_terms(
+    clusters: &[Vec<&str>],
+    tokens: &HashMap<&str, &[String]>,
+    limit: usize,
+) -> Vec<Vec<String>> {
+    let mut corpus: HashMap<&str, usize> = HashMap::new();
+    let mut corpus_len = 0;
+    let cluster_counts: Vec<HashMap<&str, usize>> = clusters
+        .iter()
+        .map(|docs| {
+            let mut counts: HashMap<&str, usize> = HashMap::new();
+            for token in docs
+                .iter()
+                .flat_map(|id| tokens.get(id).copied().unwrap_or(&[]))
+            {
+                *counts.entry(token.as_str()).or_default() += 1;
+                *corpus.entry(token.as_str()).or_default() += 1;
+                corpus_len += 1;
+            }
+            counts
+        })
+        .collect();
+
+    cluster_counts
+        .into_iter()
+        .map(|counts| {
+            let cluster_len: usize = counts.values().sum();
+            let mut scored: Vec<(&str, f32)> = counts
+                .into_iter()
+                .map(|(term, count)| {
+                    let share = count as f32 / cluster_len as f32;
+                    let corpus_share = corpus[term] as f32 / corpus_len as f32;
+                    (term, share * (share / corpus_share).ln_1p())
+                })
+                .collect();
+            scored.sort_by(|a, b| {
+                b.1.partial_cmp(&a.1)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then_with(|| a.0.cmp(b.0))
+            });
+            scored
+                .into_iter()
+                .take(limit)
+                .map(|(term, _)| term.to_string())
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pre-tokenized index with a Rust topic and a cooking topic
+    fn bm25_index(dir: &Path) -> Bm25Index {
+        let path = dir.join("bm25_index.json");
+        std::fs::write(
+            &path,
+            r#"{"version": "1.0", "doc_ids": ["r1", "c1", "r2", "c2", "r3", "empty"],
+                "corpus": [["rust", "cargo", "crate", "build"],
+                           ["recipe", "curry", "rice", "spice"],
+                           ["rust", "borrow", "cargo", "crate"],
+                           ["curry", "recipe", "onion", "rice"],
+                           ["rust", "crate", "trait", "cargo"],
+                           []]}"#,
+        )
+        .unwrap();
+        Bm25Index::load_from_file(&path).unwrap()
+    }
+
+    #[test]
+    fn test_cluster_tfidf() {
+        let dir = tempfile::tempdir().unwrap();
+        let bm25 = bm25_index(dir.path());
+
+        let model = TopicClusterer::new()
+            .with_topics(Some(2))
+            .cluster(&bm25, &VectorIndex::new(0));
+        assert_eq!(model.source, "tfidf");
+        assert_eq!(model.len(), 2);
+        assert_eq!(model.topics[0].doc_ids, vec!["r1", "r2", "r3"]);
+        assert_eq!(model.topics[1].doc_ids, vec!["c1", "c2"]);
+        assert!(model.topics[0].terms.contains(&"rust".to_string()));
+        assert!(!model.topics[0].terms.contains(&"curry".to_string()));
+        assert_eq!(model.topic_of("c2"), Some(2));
+        assert_eq!(model.topic_of("empty"), None);
+    }
+
+    #[test]
+    fn test_cluster_embeddings() {
+        let dir = tempfile::tempdir().unwrap();
+        let bm25 = bm25_index(dir.path());
+        let mut vectors = VectorIndex::new(2);
+        for (id, vector) in [
+            ("r1", [1.0, 0.0]),
+            ("r2", [0.9, 0.2]),
+            ("c1", [0.0, 1.0]),
+            ("c2", [0.1, 0.9]),
+        ] {
+            vectors.add(id.to_string(), vector.to_vec()).unwrap();
+        }
+
+        let model = TopicClusterer::new()
+            .with_topics(Some(2))
+            .cluster(&bm25, &vectors);
+        assert_eq!(model.source, "embeddings");
+        let mut sizes: Vec<usize> = model.topics.iter().map(|t| t.doc_ids.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![2, 2]);
+        assert_eq!(model.topic_of("r1"), model.topic_of("r2"));
+        assert_ne!(model.topic_of("r1"), model.topic_of("c1"));
+        // Documents without an embedding are not assigned
+        assert_eq!(model.topic_of("r3"), None);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let bm25 = bm25_index(dir.path());
+        let model = TopicClusterer::new().cluster(&bm25, &VectorIndex::new(0));
+        let path = dir.path().join(TOPICS_FILE);
+        model.save_to_file(&path).unwrap();
+
+        let loaded = TopicModel::load_from_file(&path).unwrap();
+        assert_eq!(loaded.topics, model.topics);
+        assert_eq!(loaded.topic_of("r1"), model.topic_of("r1"));
+    }
+
+    #[test]
+    fn test_default_topic_count() {
+        assert_eq!(default_topic_count(1), 1);
+        assert_eq!(default_topic_count(200), 10);
+        assert_eq!(default_topic_count(1_000_000), MAX_DEFAULT_TOPICS);
+    }
+}
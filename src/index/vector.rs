@@ -111,6 +111,14 @@ impl VectorIndex {
         self.doc_ids.iter().any(|id| id == doc_id)
     }
 
+    /// Get the stored embedding for a document
+    pub fn get_vector(&self, doc_id: &str) -> Option<&[f32]> {
+        self.doc_ids
+            .iter()
+            .position(|id| id == doc_id)
+            .map(|idx| self.vectors[idx].as_slice())
+    }
+
     /// Remove a document by ID
     pub fn remove(&mut self, doc_id: &str) {
         if let Some(idx) = self.doc_ids.iter().position(|id| id == doc_id) {
@@ -181,6 +189,14 @@ mod tests {
         assert_eq!(index.dimension(), 3);
     }
 
+    #[test]
+    fn test_get_vector() {
+        let mut index = VectorIndex::new(0);
+        index.add("doc1".to_string(), vec![0.1, 0.2, 0.3]).unwrap();
+        assert_eq!(index.get_vector("doc1"), Some(&[0.1, 0.2, 0.3][..]));
+        assert!(index.get_vector("doc2").is_none());
+    }
+
     #[test]
     fn test_vector_index_serialization() {
         let mut index = VectorIndex::new(3);
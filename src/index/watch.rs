@@ -0,0 +1,310 @@
+//! Continuous re-indexing
+//!
+//! [`IndexWatcher`] polls the input files for changes, re-parses only the
+//! files that were touched and applies the resulting [`IncrementalDiff`] to
+//! the index directory. Polling keeps the watcher dependency-free and works
+//! the same on every platform and on network filesystems.
+
+use super::{IncrementalDiff, IndexBuilder};
+use crate::loader::{expand_inputs, ChangelogLoader, Document};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Default interval between scans of the input files
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default quiet period before changes are indexed
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Modification time and size of a file at the last scan
+type FileStamp = (SystemTime, u64);
+
+/// Watches input files and keeps an index directory up to date
+pub struct IndexWatcher {
+    inputs: Vec<PathBuf>,
+    output_dir: PathBuf,
+    builder: IndexBuilder,
+    loader: ChangelogLoader,
+    interval: Duration,
+    debounce: Duration,
+    /// Stamps of the files seen at the last scan
+    stamps: HashMap<PathBuf, FileStamp>,
+    /// Parsed documents of each file
+    documents: HashMap<PathBuf, Vec<Document>>,
+}
+
+impl IndexWatcher {
+    /// Create a watcher for input files or directories
+    ///
+    /// Directories are expanded with the same exclusions as `digrag build`,
+    /// and files created in them later are picked up.
+    pub fn new(inputs: Vec<PathBuf>, output_dir: PathBuf, builder: IndexBuilder) -> Self {
+        Self {
+            inputs,
+            output_dir,
+            builder,
+            loader: ChangelogLoader::new(),
+            interval: DEFAULT_WATCH_INTERVAL,
+            debounce: DEFAULT_DEBOUNCE,
+            stamps: HashMap::new(),
+            documents: HashMap::new(),
+        }
+    }
+
+    /// Set the interval between scans
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set how long the inputs must stay unchanged before indexing
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Index directory kept up to date
+    pub fn output_dir(&self) -> &Path {
+        &self.output_dir
+    }
+
+    /// Scan the inputs and return files created, modified or deleted since
+    /// the last scan
+    ///
+    /// The first scan reports every input file.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let current: HashMap<PathBuf, FileStamp> = expand_inputs(&self.inputs)
+            .into_iter()
+            .filter_map(|path| {
+                let metadata = std::fs::metadata(&path).ok()?;
+                Some((path, (metadata.modified().ok()?, metadata.len())))
+            })
+            .collect();
+
+        let mut touched: Vec<PathBuf> = current
+            .iter()
+            .filter(|(path, stamp)| self.stamps.get(*path) != Some(stamp))
+            .map(|(path, _)| path.clone())
+            .chain(
+                self.stamps
+                    .keys()
+                    .filter(|path| !current.contains_key(*path))
+                    .cloned(),
+            )
+            .collect();
+        touched.sort();
+
+        self.stamps = current;
+        touched
+    }
+
+    /// Re-parse touched files and update the index
+    ///
+    /// Falls back to a full build when the index directory has no
+    /// incremental metadata yet. Returns the applied diff; the index is left
+    /// untouched when nothing changed. When a file fails to parse, the stamps
+    /// of it and the files after it are forgotten so the next
+    /// [`poll`](Self::poll) reports them again.
+    pub async fn sync(&mut self, touched: &[PathBuf]) -> Result<IncrementalDiff> {
+        for (i, path) in touched.iter().enumerate() {
+            if path.is_file() {
+                match self.loader.load_from_file(path) {
+                    Ok(documents) => {
+                        self.documents.insert(path.clone(), documents);
+                    }
+                    Err(e) => {
+                        for pending in &touched[i..] {
+                            self.stamps.remove(pending);
+                        }
+                        return Err(e);
+                    }
+                }
+            } else {
+                self.documents.remove(path);
+            }
+        }
+
+        let documents: Vec<Document> = self.documents.values().flatten().cloned().collect();
+        let progress = |step: usize, total: usize, msg: &str| {
+            tracing::debug!("[{}/{}] {}", step, total, msg);
+        };
+
+        match IndexBuilder::load_existing_metadata(&self.output_dir) {
+            Some(metadata) => {
+                let diff = IncrementalDiff::compute(documents, &metadata.doc_hashes);
+                if diff.has_changes() {
+                    self.builder
+                        .apply_diff(&diff, &self.output_dir, progress)
+                        .await?;
+                }
+                Ok(diff)
+            }
+            None => {
+                let diff = IncrementalDiff::compute(documents.clone(), &HashMap::new());
+                self.builder
+                    .build_from_documents_with_embeddings(documents, &self.output_dir, progress)
+                    .await?;
+                Ok(diff)
+            }
+        }
+    }
+
+    /// Wait until the inputs stop changing and return every touched file
+    async fn settle(&mut self, mut touched: Vec<PathBuf>) -> Vec<PathBuf> {
+        loop {
+            tokio::time::sleep(self.debounce).await;
+            let more = self.poll();
+            if more.is_empty() {
+                break;
+            }
+            touched.extend(more);
+        }
+        touched.sort();
+        touched.dedup();
+        touched
+    }
+
+    /// Re-index the inputs whenever they change
+    ///
+    /// Files not seen by an earlier [`poll`](Self::poll) are indexed on the
+    /// first scan. `report` is called after every sync with the touched files and the
+    /// outcome. Files that failed to parse are retried on the next scan; other
+    /// failed syncs are retried with the next change, since every diff is
+    /// computed against the hashes stored in the index.
+    pub async fn run<F>(mut self, mut report: F)
+    where
+        F: FnMut(&[PathBuf], Result<IncrementalDiff>),
+    {
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let touched = self.poll();
+            if touched.is_empty() {
+                continue;
+            }
+            let touched = self.settle(touched).await;
+            let outcome = self.sync(&touched).await;
+            report(&touched, outcome);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::Docstore;
+
+    const MEMO_A: &str = "* Memo A 2025-01-01 10:00:00 [memo]:\nfirst\n";
+    const MEMO_B: &str = "* Memo B 2025-01-02 10:00:00 [memo]:\nsecond\n";
+
+    /// Write a file and bump its mtime even on filesystems with coarse timestamps
+    fn write(path: &Path, content: &str, ahead_secs: u64) {
+        std::fs::write(path, content).unwrap();
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(ahead_secs))
+            .unwrap();
+    }
+
+    fn titles(output: &Path) -> Vec<String> {
+        let docstore = Docstore::load_from_file(&output.join("docstore.json")).unwrap();
+        let mut titles: Vec<String> = docstore
+            .documents()
+            .values()
+            .map(|doc| doc.title().to_string())
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    #[test]
+    fn test_poll_reports_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("notes");
+        std::fs::create_dir(&notes).unwrap();
+        write(&notes.join("a.md"), MEMO_A, 0);
+
+        let mut watcher = IndexWatcher::new(
+            vec![notes.clone()],
+            dir.path().join(".rag"),
+            IndexBuilder::new(),
+        );
+        assert_eq!(watcher.poll(), vec![notes.join("a.md")]);
+        assert!(watcher.poll().is_empty());
+
+        write(&notes.join("a.md"), MEMO_B, 5);
+        write(&notes.join("b.md"), MEMO_B, 0);
+        assert_eq!(watcher.poll(), vec![notes.join("a.md"), notes.join("b.md")]);
+
+        std::fs::remove_file(notes.join("b.md")).unwrap();
+        assert_eq!(watcher.poll(), vec![notes.join("b.md")]);
+    }
+
+    #[tokio::test]
+    async fn test_sync_applies_incremental_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("notes");
+        let output = dir.path().join(".rag");
+        std::fs::create_dir(&notes).unwrap();
+        write(&notes.join("a.md"), MEMO_A, 0);
+
+        let mut watcher =
+            IndexWatcher::new(vec![notes.clone()], output.clone(), IndexBuilder::new());
+
+        // First sync builds the index from scratch
+        let touched = watcher.poll();
+        let diff = watcher.sync(&touched).await.unwrap();
+        assert_eq!(diff.added_count(), 1);
+        assert_eq!(titles(&output), vec!["Memo A"]);
+
+        // Only the new file is parsed; the existing memo is unchanged
+        write(&notes.join("b.md"), MEMO_B, 0);
+        let touched = watcher.poll();
+        assert_eq!(touched, vec![notes.join("b.md")]);
+        let diff = watcher.sync(&touched).await.unwrap();
+        assert_eq!(diff.added_count(), 1);
+        assert_eq!(diff.unchanged_count(), 1);
+        assert_eq!(titles(&output), vec!["Memo A", "Memo B"]);
+
+        // Deleting a file removes its memos
+        std::fs::remove_file(notes.join("a.md")).unwrap();
+        let touched = watcher.poll();
+        let diff = watcher.sync(&touched).await.unwrap();
+        assert_eq!(diff.removed_count(), 1);
+        assert_eq!(titles(&output), vec!["Memo B"]);
+
+        // Nothing changed
+        let diff = watcher.sync(&[]).await.unwrap();
+        assert!(!diff.has_changes());
+    }
+
+    #[tokio::test]
+    async fn test_sync_retries_unparsable_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("notes");
+        let output = dir.path().join(".rag");
+        std::fs::create_dir(&notes).unwrap();
+        write(&notes.join("a.md"), MEMO_A, 0);
+
+        let mut watcher =
+            IndexWatcher::new(vec![notes.clone()], output.clone(), IndexBuilder::new());
+        let touched = watcher.poll();
+        watcher.sync(&touched).await.unwrap();
+
+        // A half-written file (truncated UTF-8) fails to parse
+        std::fs::write(notes.join("a.md"), b"* Memo B 2025-01-02 \xe3\x81").unwrap();
+        let touched = watcher.poll();
+        assert!(watcher.sync(&touched).await.is_err());
+        assert_eq!(titles(&output), vec!["Memo A"]);
+
+        // The file is reported again even though it has not changed since
+        assert_eq!(watcher.poll(), vec![notes.join("a.md")]);
+
+        std::fs::write(notes.join("a.md"), MEMO_B).unwrap();
+        let touched = watcher.poll();
+        watcher.sync(&touched).await.unwrap();
+        assert_eq!(titles(&output), vec!["Memo B"]);
+    }
+}
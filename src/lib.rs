@@ -21,14 +21,18 @@
 //! - `search`: Search integration and result fusion
 //! - `embedding`: OpenRouter embedding API client
 //! - `rewriter`: Query rewriting with LLM
-//! - `mcp`: MCP server implementation
+//! - `mcp`: MCP request/response types (the rmcp server itself lives in main.rs)
+//! - `pagination`: Offset and cursor based paging
+//! - `api`: REST/JSON API over the same index
 
+pub mod api;
 pub mod config;
 pub mod embedding;
 pub mod extract;
 pub mod index;
 pub mod loader;
-// pub mod mcp;  // MCP server is now implemented in main.rs using rmcp macros
+pub mod mcp;
+pub mod pagination;
 pub mod rewriter;
 pub mod search;
 pub mod tokenizer;
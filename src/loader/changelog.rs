@@ -95,6 +95,63 @@ impl ChangelogLoader {
         Ok(documents)
     }
 
+    /// Rewrite the tags of every entry header
+    ///
+    /// `edit` receives the tags of each header and returns the new tags.
+    /// Headers whose tags are unchanged, body lines and line endings are left
+    /// untouched, so the result differs from `content` only where tags changed.
+    pub fn rewrite_tags<F>(&self, content: &str, mut edit: F) -> String
+    where
+        F: FnMut(&[String]) -> Vec<String>,
+    {
+        let mut output = String::with_capacity(content.len());
+
+        for line in content.split_inclusive('\n') {
+            let body = line.trim_end_matches(['\r', '\n']);
+            let ending = &line[body.len()..];
+
+            let Some(rest) = self
+                .entry_pattern
+                .captures(body)
+                .and_then(|caps| caps.get(3))
+            else {
+                output.push_str(line);
+                continue;
+            };
+
+            let tags: Vec<String> = self
+                .tag_pattern
+                .captures_iter(rest.as_str())
+                .filter_map(|c| c.get(1).map(|m| m.as_str().to_string()))
+                .collect();
+            let new_tags = edit(&tags);
+            if new_tags == tags {
+                output.push_str(line);
+                continue;
+            }
+
+            // Keep any non-tag text that followed the tags
+            let remainder = self.tag_pattern.replace_all(rest.as_str(), "");
+            let mut new_rest: String = new_tags.iter().map(|t| format!("[{}]:", t)).collect();
+            if !remainder.trim().is_empty() {
+                if !new_rest.is_empty() {
+                    new_rest.push(' ');
+                }
+                new_rest.push_str(remainder.trim());
+            }
+
+            let header = body[..rest.start()].trim_end();
+            output.push_str(header);
+            if !new_rest.is_empty() {
+                output.push(' ');
+                output.push_str(&new_rest);
+            }
+            output.push_str(ending);
+        }
+
+        output
+    }
+
     /// Create a document from parsed components
     fn create_document(
         &self,
@@ -191,6 +248,33 @@ Second content"#;
         assert!(docs.is_empty());
     }
 
+    #[test]
+    fn test_rewrite_tags() {
+        let loader = ChangelogLoader::new();
+        let content = "* First 2025-01-15 10:00:00 [memos]:[work]:\r\nBody [memos]:\n* Second 2025-01-14 09:00:00 [memo]:\nText";
+
+        let rewritten = loader.rewrite_tags(content, |tags| {
+            tags.iter()
+                .filter(|t| *t != "work")
+                .map(|t| {
+                    if t == "memos" {
+                        "memo".to_string()
+                    } else {
+                        t.clone()
+                    }
+                })
+                .collect()
+        });
+        assert_eq!(
+            rewritten,
+            "* First 2025-01-15 10:00:00 [memo]:\r\nBody [memos]:\n* Second 2025-01-14 09:00:00 [memo]:\nText"
+        );
+
+        // Removing the last tag leaves a bare header
+        let rewritten = loader.rewrite_tags("* Only 2025-01-15 10:00:00 [memo]:\n", |_| vec![]);
+        assert_eq!(rewritten, "* Only 2025-01-15 10:00:00\n");
+    }
+
     #[test]
     fn test_date_parsing() {
         let loader = ChangelogLoader::new();
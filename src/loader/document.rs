@@ -16,6 +16,9 @@ pub struct Metadata {
     pub date: DateTime<Utc>,
     /// Document tags
     pub tags: Vec<String>,
+    /// Tags suggested from similar documents, kept apart from authored tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inferred_tags: Vec<String>,
 }
 
 /// A document in the search index
@@ -34,7 +37,12 @@ impl Document {
     pub fn new(title: String, date: DateTime<Utc>, tags: Vec<String>, text: String) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
-            metadata: Metadata { title, date, tags },
+            metadata: Metadata {
+                title,
+                date,
+                tags,
+                inferred_tags: Vec::new(),
+            },
             text,
         }
     }
@@ -49,7 +57,12 @@ impl Document {
     ) -> Self {
         Self {
             id,
-            metadata: Metadata { title, date, tags },
+            metadata: Metadata {
+                title,
+                date,
+                tags,
+                inferred_tags: Vec::new(),
+            },
             text,
         }
     }
@@ -80,7 +93,12 @@ impl Document {
         let id = Self::compute_content_hash(&title, &text);
         Self {
             id,
-            metadata: Metadata { title, date, tags },
+            metadata: Metadata {
+                title,
+                date,
+                tags,
+                inferred_tags: Vec::new(),
+            },
             text,
         }
     }
@@ -107,9 +125,18 @@ impl Document {
         &self.metadata.tags
     }
 
-    /// Check if document has a specific tag
+    /// Get the tags inferred by `digrag suggest-tags`
+    pub fn inferred_tags(&self) -> &[String] {
+        &self.metadata.inferred_tags
+    }
+
+    /// Check if document has a specific tag, authored or inferred
     pub fn has_tag(&self, tag: &str) -> bool {
-        self.metadata.tags.iter().any(|t| t == tag)
+        self.metadata
+            .tags
+            .iter()
+            .chain(&self.metadata.inferred_tags)
+            .any(|t| t == tag)
     }
 
     /// Get the category from the title
@@ -274,6 +301,24 @@ mod tests {
         assert!(!doc.has_tag("tips"));
     }
 
+    #[test]
+    fn test_inferred_tags_are_separate() {
+        let date = Utc.with_ymd_and_hms(2025, 1, 15, 10, 0, 0).unwrap();
+        let mut doc = Document::new("Test".to_string(), date, vec![], "Content".to_string());
+        doc.metadata.inferred_tags = vec!["tips".to_string()];
+
+        assert!(doc.tags().is_empty());
+        assert_eq!(doc.inferred_tags(), &["tips"]);
+        assert!(doc.has_tag("tips"));
+
+        // Documents without inferred tags serialize as before
+        let json = serde_json::to_string(&doc.metadata).unwrap();
+        assert!(json.contains("inferred_tags"));
+        doc.metadata.inferred_tags.clear();
+        let json = serde_json::to_string(&doc.metadata).unwrap();
+        assert!(!json.contains("inferred_tags"));
+    }
+
     #[test]
     fn test_document_serialization() {
         let date = Utc.with_ymd_and_hms(2025, 1, 15, 10, 0, 0).unwrap();
@@ -298,6 +343,7 @@ mod tests {
             title: "Test".to_string(),
             date,
             tags: vec!["memo".to_string()],
+            inferred_tags: vec![],
         };
 
         let json = serde_json::to_string(&metadata).unwrap();
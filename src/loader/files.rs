@@ -0,0 +1,64 @@
+//! Input file discovery
+//!
+//! Expands `--input` paths into the markdown files to index.
+
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// ディレクトリから.mdファイルを再帰的に収集（node_modules, .git等を除外）
+pub fn collect_markdown_files(dir: &Path) -> Vec<PathBuf> {
+    WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            // node_modules, .git, target などを除外
+            !matches!(name.as_ref(), "node_modules" | ".git" | "target" | ".rag")
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_file() && e.path().extension().is_some_and(|ext| ext == "md"))
+        .map(|e| e.path().to_path_buf())
+        .collect()
+}
+
+/// Expand input paths, replacing directories with the markdown files inside
+pub fn expand_inputs(inputs: &[PathBuf]) -> Vec<PathBuf> {
+    inputs
+        .iter()
+        .flat_map(|input| {
+            if input.is_dir() {
+                collect_markdown_files(input)
+            } else {
+                vec![input.clone()]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_markdown_files_skips_excluded_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("notes")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules")).unwrap();
+        std::fs::create_dir_all(root.join(".rag")).unwrap();
+        std::fs::write(root.join("a.md"), "").unwrap();
+        std::fs::write(root.join("notes/b.md"), "").unwrap();
+        std::fs::write(root.join("notes/c.txt"), "").unwrap();
+        std::fs::write(root.join("node_modules/d.md"), "").unwrap();
+        std::fs::write(root.join(".rag/e.md"), "").unwrap();
+
+        let mut files = collect_markdown_files(root);
+        files.sort();
+        assert_eq!(files, vec![root.join("a.md"), root.join("notes/b.md")]);
+
+        let single = root.join("notes/c.txt");
+        let mut expanded = expand_inputs(&[root.join("notes"), single.clone()]);
+        expanded.sort();
+        assert_eq!(expanded, vec![root.join("notes/b.md"), single]);
+    }
+}
@@ -4,8 +4,14 @@
 
 mod changelog;
 mod document;
+mod files;
 mod jsonl;
+mod tags;
+mod writer;
 
 pub use changelog::ChangelogLoader;
 pub use document::{Document, Metadata};
+pub use files::{collect_markdown_files, expand_inputs};
 pub use jsonl::JsonlLoader;
+pub use tags::{TagEdit, TagRewrite};
+pub use writer::{ChangelogWriter, NewMemo, WritePosition};
@@ -0,0 +1,256 @@
+//! Tag maintenance across changelog files
+//!
+//! Renames, merges and deletes `[tag]:` markers in entry headers. A
+//! [`TagRewrite`] holds the rewritten file until it is written, so callers
+//! can preview the change first.
+
+use super::writer::{open_locked, replace_locked};
+use super::ChangelogLoader;
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// A change applied to every entry's tags
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagEdit {
+    /// Replace each of `from` with `to` (a rename, or a merge of several tags)
+    Rename { from: Vec<String>, to: String },
+    /// Remove a tag
+    Delete(String),
+}
+
+impl TagEdit {
+    /// Rename `from` to `to`
+    pub fn rename(from: &str, to: &str) -> Result<Self> {
+        Self::merge(&[from.to_string()], to)
+    }
+
+    /// Merge several tags into `to`
+    pub fn merge(from: &[String], to: &str) -> Result<Self> {
+        if from.is_empty() {
+            bail!("At least one tag to merge is required");
+        }
+        for tag in from.iter().map(String::as_str).chain([to]) {
+            validate_tag(tag)?;
+        }
+        Ok(Self::Rename {
+            from: from.iter().filter(|t| *t != to).cloned().collect(),
+            to: to.to_string(),
+        })
+    }
+
+    /// Delete `tag`
+    pub fn delete(tag: &str) -> Result<Self> {
+        validate_tag(tag)?;
+        Ok(Self::Delete(tag.to_string()))
+    }
+
+    /// Apply the edit to a tag list, keeping order and dropping duplicates
+    pub fn apply(&self, tags: &[String]) -> Vec<String> {
+        let mut result: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let new_tag = match self {
+                Self::Rename { from, to } if from.contains(tag) => Some(to),
+                Self::Delete(deleted) if deleted == tag => None,
+                _ => Some(tag),
+            };
+            if let Some(new_tag) = new_tag {
+                if !result.contains(new_tag) {
+                    result.push(new_tag.clone());
+                }
+            }
+        }
+        result
+    }
+}
+
+fn validate_tag(tag: &str) -> Result<()> {
+    if tag.is_empty() || tag.contains(['[', ']', '\n']) {
+        bail!("Invalid tag '{}'", tag);
+    }
+    Ok(())
+}
+
+/// Pending tag changes for one changelog file
+#[derive(Debug, Clone)]
+pub struct TagRewrite {
+    /// File being rewritten
+    pub path: PathBuf,
+    original: String,
+    updated: String,
+    /// New tags of each changed entry, by document ID
+    pub retagged: HashMap<String, Vec<String>>,
+}
+
+impl TagRewrite {
+    /// Rewrite the tags in one file, or `None` if no entry changes
+    pub fn plan(path: &Path, edit: &TagEdit) -> Result<Option<Self>> {
+        let loader = ChangelogLoader::new();
+        let original = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read file: {:?}", path))?;
+        let updated = loader.rewrite_tags(&original, |tags| edit.apply(tags));
+        if updated == original {
+            return Ok(None);
+        }
+
+        // Tags are not part of the content hash, so entries keep their IDs
+        let retagged = loader
+            .load_from_string(&original)?
+            .into_iter()
+            .zip(loader.load_from_string(&updated)?)
+            .filter(|(before, after)| before.tags() != after.tags())
+            .map(|(_, after)| (after.id.clone(), after.tags().to_vec()))
+            .collect();
+
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            original,
+            updated,
+            retagged,
+        }))
+    }
+
+    /// Plan the edit for every file, skipping files without changes
+    pub fn plan_all(paths: &[PathBuf], edit: &TagEdit) -> Result<Vec<Self>> {
+        let mut rewrites = Vec::new();
+        for path in paths {
+            if let Some(rewrite) = Self::plan(path, edit)? {
+                rewrites.push(rewrite);
+            }
+        }
+        Ok(rewrites)
+    }
+
+    /// Number of entry headers that change
+    pub fn changed_lines(&self) -> usize {
+        self.original
+            .lines()
+            .zip(self.updated.lines())
+            .filter(|(before, after)| before != after)
+            .count()
+    }
+
+    /// Line-by-line preview of the change
+    pub fn diff(&self) -> String {
+        let mut diff = format!("--- {}\n+++ {}\n", self.path.display(), self.path.display());
+        for (i, (before, after)) in self.original.lines().zip(self.updated.lines()).enumerate() {
+            if before != after {
+                diff.push_str(&format!("@@ line {} @@\n-{}\n+{}\n", i + 1, before, after));
+            }
+        }
+        diff
+    }
+
+    /// Write the rewritten file
+    ///
+    /// Takes the same exclusive lock as [`ChangelogWriter`](super::ChangelogWriter)
+    /// and fails if the file changed since it was planned (e.g. a memo was
+    /// added meanwhile), so no entry is lost. The new content is written to a
+    /// temporary file that is renamed over the changelog, so a crash cannot
+    /// leave it truncated.
+    pub fn write(&self) -> Result<()> {
+        let mut file = open_locked(&self.path)?;
+        let mut current = String::new();
+        file.read_to_string(&mut current)
+            .with_context(|| format!("Failed to read file: {:?}", self.path))?;
+        if current != self.original {
+            bail!(
+                "{} changed since the tag edit was planned; run it again",
+                self.path.display()
+            );
+        }
+
+        replace_locked(&self.path, &file, &self.updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(values: &[&str]) -> Vec<String> {
+        values.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_tag_edit_apply() {
+        let rename = TagEdit::rename("memos", "memo").unwrap();
+        assert_eq!(
+            rename.apply(&tags(&["memos", "work"])),
+            tags(&["memo", "work"])
+        );
+        // Merging into an existing tag does not duplicate it
+        assert_eq!(rename.apply(&tags(&["memo", "memos"])), tags(&["memo"]));
+
+        let merge = TagEdit::merge(&tags(&["work-log", "wl"]), "worklog").unwrap();
+        assert_eq!(merge.apply(&tags(&["wl", "work-log"])), tags(&["worklog"]));
+
+        let delete = TagEdit::delete("tmp").unwrap();
+        assert_eq!(delete.apply(&tags(&["tmp", "memo"])), tags(&["memo"]));
+
+        assert!(TagEdit::rename("memo", "a]b").is_err());
+        assert!(TagEdit::merge(&[], "memo").is_err());
+    }
+
+    #[test]
+    fn test_plan_and_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("changelog.md");
+        std::fs::write(
+            &path,
+            "* A 2025-01-02 10:00:00 [memos]:\na\n\n* B 2025-01-01 10:00:00 [work]:\nb\n",
+        )
+        .unwrap();
+
+        let edit = TagEdit::rename("memos", "memo").unwrap();
+        let rewrite = TagRewrite::plan(&path, &edit).unwrap().unwrap();
+        assert_eq!(rewrite.changed_lines(), 1);
+        assert!(rewrite.diff().contains(
+            "@@ line 1 @@\n-* A 2025-01-02 10:00:00 [memos]:\n+* A 2025-01-02 10:00:00 [memo]:\n"
+        ));
+        assert_eq!(rewrite.retagged.len(), 1);
+        assert_eq!(rewrite.retagged.values().next().unwrap(), &tags(&["memo"]));
+
+        // Planning does not touch the file
+        let docs = ChangelogLoader::new().load_from_file(&path).unwrap();
+        assert_eq!(docs[0].tags(), &["memos"]);
+
+        rewrite.write().unwrap();
+        let docs = ChangelogLoader::new().load_from_file(&path).unwrap();
+        assert_eq!(docs[0].tags(), &["memo"]);
+        assert!(rewrite.retagged.contains_key(&docs[0].id));
+
+        assert!(
+            TagRewrite::plan(&path, &TagEdit::delete("missing").unwrap())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_write_fails_when_file_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("changelog.md");
+        std::fs::write(&path, "* A 2025-01-02 10:00:00 [memos]:\na\n").unwrap();
+
+        let edit = TagEdit::rename("memos", "memo").unwrap();
+        let rewrite = TagRewrite::plan(&path, &edit).unwrap().unwrap();
+
+        // A memo added after planning is not overwritten
+        let added = "* B 2025-01-03 10:00:00 [work]:\nb\n\n* A 2025-01-02 10:00:00 [memos]:\na\n";
+        std::fs::write(&path, added).unwrap();
+        assert!(rewrite.write().is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), added);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        TagRewrite::plan(&path, &edit)
+            .unwrap()
+            .unwrap()
+            .write()
+            .unwrap();
+        let docs = ChangelogLoader::new().load_from_file(&path).unwrap();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[1].tags(), &["memo"]);
+    }
+}
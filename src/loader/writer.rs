@@ -0,0 +1,335 @@
+//! Changelog writer
+//!
+//! Formats new memos in the changelog format read by [`ChangelogLoader`] and
+//! writes them to a changelog file under an exclusive file lock, so an editor
+//! plugin or a second server writing at the same time cannot interleave.
+
+use super::{ChangelogLoader, Document};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::NaiveDateTime;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Where new entries go in the changelog file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WritePosition {
+    /// Insert at the top of the file (newest first)
+    #[default]
+    Prepend,
+    /// Add at the end of the file
+    Append,
+}
+
+impl std::str::FromStr for WritePosition {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "prepend" => Ok(Self::Prepend),
+            "append" => Ok(Self::Append),
+            _ => Err(anyhow!(
+                "Invalid write position '{}': expected prepend or append",
+                s
+            )),
+        }
+    }
+}
+
+/// A memo to be written to the changelog
+#[derive(Debug, Clone)]
+pub struct NewMemo {
+    /// Memo title
+    pub title: String,
+    /// Tags, written as `[tag]:`
+    pub tags: Vec<String>,
+    /// Memo body
+    pub text: String,
+    /// Entry timestamp (local wall-clock time, like hand-written entries)
+    pub date: NaiveDateTime,
+}
+
+impl NewMemo {
+    /// Create a memo timestamped now
+    pub fn new(title: String, tags: Vec<String>, text: String) -> Self {
+        Self {
+            title,
+            tags,
+            text,
+            date: chrono::Local::now().naive_local(),
+        }
+    }
+
+    /// Format the memo as a changelog entry
+    ///
+    /// `* Title YYYY-MM-DD HH:MM:SS [tag1]:[tag2]:` followed by the body.
+    pub fn to_entry(&self) -> String {
+        let tags: String = self.tags.iter().map(|tag| format!("[{}]:", tag)).collect();
+        let mut header = format!(
+            "* {} {}",
+            self.title.trim(),
+            self.date.format("%Y-%m-%d %H:%M:%S")
+        );
+        if !tags.is_empty() {
+            header.push(' ');
+            header.push_str(&tags);
+        }
+        format!("{}\n{}\n", header, self.text.trim())
+    }
+
+    /// Check that the memo round-trips through the changelog parser
+    ///
+    /// Returns the document the loader will produce for this entry.
+    pub fn to_document(&self) -> Result<Document> {
+        if self.title.trim().is_empty() {
+            bail!("Memo title must not be empty");
+        }
+        if self.title.contains('\n') {
+            bail!("Memo title must be a single line");
+        }
+        if let Some(tag) = self
+            .tags
+            .iter()
+            .find(|tag| tag.is_empty() || tag.contains(['[', ']', '\n']))
+        {
+            bail!("Invalid tag '{}'", tag);
+        }
+
+        let mut documents = ChangelogLoader::new().load_from_string(&self.to_entry())?;
+        match documents.len() {
+            1 => Ok(documents.remove(0)),
+            _ => bail!("Memo text must not contain lines that look like changelog entry headers"),
+        }
+    }
+}
+
+/// Writes memos to a changelog file
+#[derive(Debug, Clone)]
+pub struct ChangelogWriter {
+    path: PathBuf,
+    position: WritePosition,
+}
+
+impl ChangelogWriter {
+    /// Create a writer for a changelog file (created on first write)
+    pub fn new<P: Into<PathBuf>>(path: P, position: WritePosition) -> Self {
+        Self {
+            path: path.into(),
+            position,
+        }
+    }
+
+    /// Path of the changelog file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write a memo and return the document it parses to
+    pub fn write(&self, memo: &NewMemo) -> Result<Document> {
+        let document = memo.to_document()?;
+        let entry = memo.to_entry();
+
+        let mut file = open_locked(&self.path)?;
+
+        let mut existing = String::new();
+        file.read_to_string(&mut existing)?;
+
+        match self.position {
+            WritePosition::Prepend => {
+                let content = if existing.trim().is_empty() {
+                    entry
+                } else {
+                    format!("{}\n{}", entry, existing)
+                };
+                // Rewriting in place could leave the changelog truncated
+                replace_locked(&self.path, &file, &content)?;
+            }
+            WritePosition::Append => {
+                let separator = match existing.as_str() {
+                    "" => "",
+                    s if s.ends_with("\n\n") => "",
+                    s if s.ends_with('\n') => "\n",
+                    _ => "\n\n",
+                };
+                file.seek(SeekFrom::End(0))?;
+                file.write_all(format!("{}{}", separator, entry).as_bytes())?;
+                file.sync_all()?;
+            }
+        }
+
+        Ok(document)
+    }
+}
+
+/// Open a changelog file (creating it) and take its exclusive lock
+///
+/// Tag rewrites replace the file by renaming a new one over it, so a lock
+/// taken on the old file after waiting for a rewrite guards a file that is
+/// no longer at `path`; the file is then opened and locked again.
+pub(crate) fn open_locked(path: &Path) -> Result<File> {
+    loop {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .with_context(|| format!("Failed to open changelog {}", path.display()))?;
+        file.lock()
+            .with_context(|| format!("Failed to lock changelog {}", path.display()))?;
+        if is_current(&file, path)? {
+            return Ok(file);
+        }
+    }
+}
+
+/// Replace a locked changelog with new content
+///
+/// The content is written to a temporary file next to the changelog, synced
+/// and renamed over it, so a crash or a full disk leaves either the old or
+/// the new file, never a truncated one. Writers waiting on the lock of the
+/// old file reopen the new one (see [`open_locked`]).
+pub(crate) fn replace_locked(path: &Path, locked: &File, content: &str) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("Invalid changelog path: {:?}", path))?;
+    let temp_path = path.with_file_name(format!(".{}.digrag-tmp", file_name.to_string_lossy()));
+    let result = (|| -> Result<()> {
+        let mut temp = File::create(&temp_path)?;
+        temp.write_all(content.as_bytes())?;
+        temp.set_permissions(locked.metadata()?.permissions())?;
+        temp.sync_all()?;
+        std::fs::rename(&temp_path, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result.with_context(|| format!("Failed to write file: {:?}", path))
+}
+
+#[cfg(unix)]
+fn is_current(file: &File, path: &Path) -> Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let opened = file.metadata()?;
+    Ok(match std::fs::metadata(path) {
+        Ok(current) => opened.dev() == current.dev() && opened.ino() == current.ino(),
+        Err(_) => false,
+    })
+}
+
+#[cfg(not(unix))]
+fn is_current(_file: &File, _path: &Path) -> Result<bool> {
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memo(title: &str, tags: &[&str], text: &str) -> NewMemo {
+        NewMemo {
+            title: title.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            text: text.to_string(),
+            date: NaiveDateTime::parse_from_str("2025-01-15 10:00:00", "%Y-%m-%d %H:%M:%S")
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_entry_format() {
+        assert_eq!(
+            memo("Title", &["memo", "worklog"], "Body\n").to_entry(),
+            "* Title 2025-01-15 10:00:00 [memo]:[worklog]:\nBody\n"
+        );
+        assert_eq!(
+            memo("Title", &[], "Body").to_entry(),
+            "* Title 2025-01-15 10:00:00\nBody\n"
+        );
+    }
+
+    #[test]
+    fn test_entry_round_trips() {
+        let doc = memo("Title", &["memo"], "line 1\nline 2")
+            .to_document()
+            .unwrap();
+        assert_eq!(doc.title(), "Title");
+        assert_eq!(doc.tags(), &["memo"]);
+        assert_eq!(doc.text, "line 1\nline 2");
+    }
+
+    #[test]
+    fn test_invalid_memos_rejected() {
+        assert!(memo("", &[], "Body").to_document().is_err());
+        assert!(memo("Title", &["a]b"], "Body").to_document().is_err());
+        assert!(memo("Title", &[], "* Other 2025-01-01 00:00:00\nx")
+            .to_document()
+            .is_err());
+    }
+
+    #[test]
+    fn test_write_prepend_and_append() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("changelog.md");
+        std::fs::write(&path, "* Old 2025-01-01 10:00:00 [memo]:\nold\n").unwrap();
+
+        ChangelogWriter::new(&path, WritePosition::Prepend)
+            .write(&memo("First", &[], "first"))
+            .unwrap();
+        ChangelogWriter::new(&path, WritePosition::Append)
+            .write(&memo("Last", &[], "last"))
+            .unwrap();
+
+        let docs = ChangelogLoader::new().load_from_file(&path).unwrap();
+        let titles: Vec<&str> = docs.iter().map(|d| d.title()).collect();
+        assert_eq!(titles, vec!["First", "Old", "Last"]);
+        assert_eq!(docs[1].text, "old");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_prepend_replaces_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("changelog.md");
+        let old = "* Old 2025-01-01 10:00:00 [memo]:\nold\n";
+        std::fs::write(&path, old).unwrap();
+        let mut reader = File::open(&path).unwrap();
+
+        ChangelogWriter::new(&path, WritePosition::Prepend)
+            .write(&memo("First", &[], "first"))
+            .unwrap();
+
+        // The old file was replaced, not truncated and rewritten
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        assert_eq!(content, old);
+        let names: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["changelog.md"]);
+    }
+
+    #[test]
+    fn test_write_creates_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("new.md");
+        let doc = ChangelogWriter::new(&path, WritePosition::default())
+            .write(&memo("Title", &["memo"], "Body"))
+            .unwrap();
+
+        let docs = ChangelogLoader::new().load_from_file(&path).unwrap();
+        assert_eq!(docs, vec![doc]);
+    }
+
+    #[test]
+    fn test_write_position_parse() {
+        assert_eq!(
+            "append".parse::<WritePosition>().unwrap(),
+            WritePosition::Append
+        );
+        assert!("middle".parse::<WritePosition>().is_err());
+    }
+}
@@ -1,23 +1,59 @@
 //! digrag: Command-line interface for the changelog search MCP server
 
 use anyhow::Result;
-use clap::{ArgAction, Parser, Subcommand};
-use digrag::config::{app_config::AppConfig, path_resolver, SearchConfig, SearchMode};
+use chrono::NaiveDate;
+use clap::{ArgAction, Args, Parser, Subcommand};
+use digrag::api::{serve_api, ApiState};
+use digrag::config::{
+    app_config::{AppConfig, NamedIndex, DEFAULT_INDEX_NAME},
+    path_resolver, SearchConfig, SearchMode,
+};
+use digrag::extract::cache::SummaryCache;
+use digrag::extract::persistent_cache::{PersistentCacheStats, PersistentSummaryCache};
+use digrag::extract::prompt::{PromptTemplates, SummaryContext};
+use digrag::extract::summarizer::{ContentSummarizer, DigestSource, Summary, SummaryOptions};
 use digrag::extract::{ContentExtractor, ExtractionStrategy, TruncationConfig};
-use digrag::index::{IncrementalDiff, IndexBuilder};
-use digrag::search::Searcher;
+use digrag::index::{
+    Docstore, DuplicateCluster, DuplicateDetector, IncrementalDiff, IndexBuilder, IndexWatcher,
+    Period, Timeline, TimelineBucket, TopicClusterer, TopicModel, DEFAULT_DUPLICATE_THRESHOLD,
+    DEFAULT_TIMELINE_TITLES, DEFAULT_TOPIC_TERMS,
+};
+use digrag::loader::{
+    collect_markdown_files, expand_inputs, ChangelogWriter, Document, NewMemo, TagEdit, TagRewrite,
+};
+use digrag::mcp::{
+    list_prompts, list_resource_templates, list_resources, serve_sse, AddMemoResponse,
+    DigestResult, GetMemoResponse, GetRecentMemosResponse, ListTagsResponse, ListTopicsResponse,
+    MemoDetail, MemoResource, MemoResult, PromptBuilder, QueryMemosResponse, RelatedMemosResponse,
+    ResponseFormat, SummaryScope, TagInfo, TimelineResponse, TopicInfo, RESOURCE_MIME_TYPE,
+    SSE_PATH,
+};
+use digrag::search::{
+    paginate, Answer, ExtractedResult, FacetCount, Facets, IndexReloader, IndexSelection,
+    IndexSelector, IndexSet, MemoWriter, Page, Passage, QuestionAnswerer, SearchResult, Searcher,
+    SharedIndexes, TagSuggester, TagSuggestion, DEFAULT_ANSWER_PASSAGES, DEFAULT_CONTEXT_TOKENS,
+    DEFAULT_MAX_SUGGESTED_TAGS, DEFAULT_MIN_CONFIDENCE, DEFAULT_SUGGEST_NEIGHBORS,
+};
 use rmcp::{
-    model::{CallToolResult, Content, ServerCapabilities, ServerInfo},
-    schemars, tool, ServerHandler, ServiceExt,
+    model::{
+        AnnotateAble, CallToolResult, Content, GetPromptRequestParam, GetPromptResult,
+        ListPromptsResult, ListResourceTemplatesResult, ListResourcesResult, PaginatedRequestParam,
+        Prompt, PromptArgument, PromptMessage, PromptMessageRole, RawResource, RawResourceTemplate,
+        ReadResourceRequestParam, ReadResourceResult, ResourceContents, ServerCapabilities,
+        ServerInfo,
+    },
+    schemars,
+    service::RequestContext,
+    tool, RoleServer, ServerHandler, ServiceExt,
 };
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::io;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::io::{stdin, stdout};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use walkdir::WalkDir;
 
 // ============================================================================
 // Path Resolution Helper
@@ -30,31 +66,387 @@ fn resolve_path(path: &str) -> String {
         .unwrap_or_else(|_| path.to_string())
 }
 
-/// ディレクトリから.mdファイルを再帰的に収集（node_modules, .git等を除外）
-fn collect_markdown_files(dir: &Path) -> Vec<PathBuf> {
-    WalkDir::new(dir)
-        .follow_links(true)
+/// Load a searcher, enabling semantic search when an API key is available
+///
+/// The key from config.toml takes priority over OPENROUTER_API_KEY.
+fn load_searcher<P: AsRef<Path>>(index_dir: P, config: &AppConfig) -> Result<Searcher> {
+    if let Some(api_key) = config.openrouter_api_key() {
+        tracing::info!("OpenRouter API key found in config, enabling semantic search");
+        let embedding_client = digrag::embedding::OpenRouterEmbedding::new(api_key);
+        Searcher::with_embedding_client(index_dir, embedding_client)
+    } else if let Ok(api_key) = std::env::var("OPENROUTER_API_KEY") {
+        tracing::info!("OPENROUTER_API_KEY found in env, enabling semantic search");
+        let embedding_client = digrag::embedding::OpenRouterEmbedding::new(api_key);
+        Searcher::with_embedding_client(index_dir, embedding_client)
+    } else {
+        tracing::info!("No API key configured, semantic search disabled");
+        Searcher::new(index_dir)
+    }
+}
+
+/// Resolve the named index directories to serve
+///
+/// An explicit index directory is served alone; otherwise the `[[indexes]]`
+/// declared in config.toml are used, falling back to its `index_dir`.
+fn index_dirs(index_dir: Option<&str>, config: &AppConfig) -> Vec<(String, PathBuf)> {
+    let declared = match index_dir {
+        Some(dir) => vec![NamedIndex {
+            name: DEFAULT_INDEX_NAME.to_string(),
+            path: dir.to_string(),
+        }],
+        None => config.resolved_indexes(),
+    };
+
+    declared
         .into_iter()
-        .filter_entry(|e| {
-            let name = e.file_name().to_string_lossy();
-            // node_modules, .git, target などを除外
-            !matches!(name.as_ref(), "node_modules" | ".git" | "target" | ".rag")
-        })
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().is_file() && e.path().extension().is_some_and(|ext| ext == "md"))
-        .map(|e| e.path().to_path_buf())
+        .map(|NamedIndex { name, path }| (name, PathBuf::from(resolve_path(&path))))
         .collect()
 }
 
+/// The only index directory served by `digrag http`
+///
+/// The REST API does not merge indexes, so several `[[indexes]]` are
+/// rejected rather than silently serving the first one.
+fn single_index_dir(mut dirs: Vec<(String, PathBuf)>) -> Result<(String, PathBuf)> {
+    match dirs.len() {
+        1 => Ok(dirs.remove(0)),
+        n => Err(anyhow::anyhow!(
+            "digrag http serves a single index, but {} are configured in [[indexes]]; \
+             pass --index-dir or use `digrag serve --transport http`",
+            n
+        )),
+    }
+}
+
+/// Load a searcher for each named index directory
+fn load_indexes(dirs: &[(String, PathBuf)], config: &AppConfig) -> Result<IndexSet> {
+    let mut indexes: Option<IndexSet> = None;
+    for (name, path) in dirs {
+        tracing::info!("Loading index '{}' from {}", name, path.display());
+        let searcher = load_searcher(path, config)?;
+        let name = name.clone();
+        match indexes.as_mut() {
+            Some(set) => set.add(name, searcher)?,
+            None => indexes = Some(IndexSet::new(name, searcher)),
+        }
+    }
+    indexes.ok_or_else(|| anyhow::anyhow!("No indexes configured"))
+}
+
+/// Build the detail of a memo and its text form
+///
+/// Includes up to `neighbors` entries dated before and after the memo.
+fn describe_memo(
+    docstore: &Docstore,
+    doc: &Document,
+    extractor: &ContentExtractor,
+    neighbors: usize,
+) -> (MemoDetail, String) {
+    let detail = MemoDetail::from_document(docstore, doc, extractor, neighbors);
+    let mut output = format!(
+        "# {}\nID: {}\nDate: {}\nTags: {:?}\n",
+        doc.title(),
+        doc.id,
+        doc.date().format("%Y-%m-%d %H:%M"),
+        doc.tags()
+    );
+    if !doc.inferred_tags().is_empty() {
+        output.push_str(&format!("Inferred tags: {:?}\n", doc.inferred_tags()));
+    }
+    if let Some(extracted) = &detail.memo.extracted {
+        if extracted.truncated {
+            output.push_str(&format!(
+                "[truncated: {}/{} chars]\n",
+                extracted.extracted_chars, extracted.total_chars
+            ));
+        }
+        output.push_str(&format!(
+            "\n{}\n",
+            extracted.raw_content.as_deref().unwrap_or_default()
+        ));
+    }
+
+    for (label, memos) in [("Previous", &detail.previous), ("Next", &detail.next)] {
+        if memos.is_empty() {
+            continue;
+        }
+        output.push_str(&format!("\n{}:\n", label));
+        for memo in memos {
+            // RFC 3339 dates start with YYYY-MM-DD
+            output.push_str(&format!(
+                "- {} {} ({})\n",
+                memo.date.get(..10).unwrap_or(&memo.date),
+                memo.title,
+                memo.id
+            ));
+        }
+    }
+
+    (detail, output)
+}
+
+/// Describe the position of a page, e.g. " (showing 11-20)", or "" for a single page
+fn page_range<T>(page: &Page<T>) -> String {
+    if page.offset == 0 && !page.has_more() {
+        return String::new();
+    }
+    format!(
+        " (showing {}-{})",
+        page.offset + 1,
+        page.offset + page.items.len()
+    )
+}
+
+/// Select the indexes named by a tool's `index` parameter
+fn select_indexes<'a>(
+    indexes: &'a IndexSet,
+    index: Option<&IndexSelector>,
+) -> Result<IndexSelection<'a>, rmcp::Error> {
+    indexes
+        .select(index)
+        .map_err(|e| rmcp::Error::invalid_params(e.to_string(), None))
+}
+
+/// Index name to report for a memo, only when several indexes are served
+fn index_label<'a>(indexes: &IndexSet, index: &'a str) -> Option<&'a str> {
+    (indexes.len() > 1).then_some(index)
+}
+
+/// Describe when the searched indexes were built, or "" when unknown
+fn index_built_note(timestamps: &BTreeMap<String, String>) -> String {
+    let built: Vec<String> = match timestamps.len() {
+        0 => return String::new(),
+        1 => timestamps.values().cloned().collect(),
+        _ => timestamps
+            .iter()
+            .map(|(name, created_at)| format!("{} {}", name, created_at))
+            .collect(),
+    };
+    format!("Index built: {}\n", built.join(", "))
+}
+
+/// Hint for fetching the next page, or "" on the last page
+fn next_page_hint<T>(page: &Page<T>) -> String {
+    match &page.next_cursor {
+        Some(cursor) => format!(
+            "More results available. Next page: cursor=\"{}\" (or offset={})\n",
+            cursor,
+            page.offset + page.items.len()
+        ),
+        None => String::new(),
+    }
+}
+
+/// Build a tool result containing the text and/or JSON form of a response
+fn tool_response<T: Serialize>(
+    format: ResponseFormat,
+    text: String,
+    response: &T,
+) -> Result<CallToolResult, rmcp::Error> {
+    let mut contents = Vec::new();
+    if format.includes_text() {
+        contents.push(Content::text(text));
+    }
+    if format.includes_json() {
+        contents.push(Content::json(response)?);
+    }
+    Ok(CallToolResult::success(contents))
+}
+
+/// Print a page of search results with document details to stdout
+fn print_search_results(
+    searcher: &Searcher,
+    page: &Page<SearchResult>,
+    extractor: &ContentExtractor,
+) {
+    for (i, result) in page.items.iter().enumerate() {
+        println!(
+            "{}. [score: {:.4}] {}",
+            page.offset + i + 1,
+            result.score,
+            result.doc_id
+        );
+        if let Some(doc) = searcher.docstore().get(&result.doc_id) {
+            println!("   Title: {}", doc.title());
+            println!("   Date: {}", doc.date().format("%Y-%m-%d"));
+            println!("   Tags: {:?}", doc.tags());
+            if !doc.inferred_tags().is_empty() {
+                println!("   Inferred tags: {:?}", doc.inferred_tags());
+            }
+            let extracted = extractor.extract(&doc.text);
+            println!("   {}", extracted.text);
+            if extracted.truncated {
+                println!(
+                    "   [truncated: {} of {} chars shown]",
+                    extracted.stats.extracted_chars, extracted.stats.total_chars
+                );
+            }
+        }
+        println!();
+    }
+    if let Some(cursor) = &page.next_cursor {
+        println!("Next page: --cursor {}", cursor);
+    }
+}
+
+/// Extractor for digest input
+///
+/// Snippets are too short to summarize, so the snippet mode reads the full
+/// text up to `max_chars` instead.
+fn digest_extractor(strategy: &ExtractionStrategy, max_chars: usize) -> ContentExtractor {
+    let strategy = match strategy {
+        ExtractionStrategy::Head(_) => ExtractionStrategy::Full,
+        other => other.clone(),
+    };
+    ContentExtractor::new(
+        strategy,
+        TruncationConfig {
+            max_chars: Some(max_chars),
+            max_lines: None,
+            max_sections: None,
+        },
+    )
+}
+
+fn digest_source(doc: &Document, extractor: &ContentExtractor) -> DigestSource {
+    DigestSource {
+        doc_id: doc.id.clone(),
+        title: doc.title().to_string(),
+        date: doc.date().format("%Y-%m-%d").to_string(),
+        content: extractor.extract(&doc.text),
+    }
+}
+
+/// Memo details for a summary prompt template
+fn summary_context(doc: &Document, query: &str) -> SummaryContext {
+    SummaryContext {
+        title: doc.title().to_string(),
+        date: doc.date().format("%Y-%m-%d").to_string(),
+        tags: doc.tags().to_vec(),
+        query: Some(query.to_string()),
+    }
+}
+
+/// Format a digest of search results
+fn format_digest(digest: &Summary) -> String {
+    format!(
+        "## Digest ({})\n{}\n\n",
+        summary_label(digest),
+        digest.text.trim_end()
+    )
+}
+
+/// Open the on-disk summary cache when LLM summaries are possible: enabled
+/// in config.toml, or requested per call with an API key configured
+///
+/// Failing to open it only disables caching.
+fn open_summary_store(config: &AppConfig) -> Option<Arc<PersistentSummaryCache>> {
+    if !config.summarization_enabled() && config.openrouter_api_key().is_none() {
+        return None;
+    }
+    match PersistentSummaryCache::from_config(config) {
+        Ok(store) => store.map(Arc::new),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to open summary cache, summaries will not be cached");
+            None
+        }
+    }
+}
+
+/// Summarizer from config.toml and per-request options, caching LLM
+/// summaries on disk
+fn summarizer_from_config(
+    config: &AppConfig,
+    options: &SummaryOptions,
+    store: Option<&Arc<PersistentSummaryCache>>,
+) -> Result<ContentSummarizer> {
+    let summarizer = ContentSummarizer::from_config_with(config, options)?;
+    Ok(match store {
+        Some(store) => summarizer.with_persistent_cache(store.clone()),
+        None => summarizer,
+    })
+}
+
+/// Label of a summary: method, model and tokens spent
+///
+/// Cached LLM summaries cost nothing and are marked as such.
+fn summary_label(summary: &Summary) -> String {
+    let mut label = summary.method.clone();
+    if let Some(model) = &summary.model {
+        label.push_str(&format!(", {}", model));
+        match &summary.usage {
+            Some(usage) => label.push_str(&format!(
+                ", {} tokens ({} prompt + {} completion)",
+                usage.total_tokens, usage.prompt_tokens, usage.completion_tokens
+            )),
+            None => label.push_str(", cached"),
+        }
+    }
+    label
+}
+
+/// Render summary cache statistics
+fn format_cache_stats(path: &Path, stats: &PersistentCacheStats) -> String {
+    let mut output = format!("Summary cache: {}\n", path.display());
+    output.push_str(&format!(
+        "  Entries: {} ({} expired)\n",
+        stats.entries, stats.expired
+    ));
+    output.push_str(&format!(
+        "  Cached text: {} chars, {} tokens spent\n",
+        stats.text_chars, stats.tokens
+    ));
+    if let (Some(oldest), Some(newest)) = (stats.oldest, stats.newest) {
+        output.push_str(&format!(
+            "  Created: {} .. {}\n",
+            oldest.format("%Y-%m-%d %H:%M"),
+            newest.format("%Y-%m-%d %H:%M")
+        ));
+    }
+    if !stats.models.is_empty() {
+        let models: Vec<String> = stats
+            .models
+            .iter()
+            .map(|(model, count)| format!("{} ({})", model, count))
+            .collect();
+        output.push_str(&format!("  Models: {}\n", models.join(", ")));
+    }
+    output
+}
+
+/// Run a future to completion from synchronous tool code
+///
+/// Fails as an MCP internal error when no runtime is running and a new one
+/// cannot be created.
+fn block_on<F: std::future::Future>(future: F) -> Result<F::Output, rmcp::Error> {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => Ok(tokio::task::block_in_place(|| handle.block_on(future))),
+        Err(_) => {
+            let runtime = tokio::runtime::Runtime::new()
+                .map_err(|e| rmcp::Error::internal_error(e.to_string(), None))?;
+            Ok(runtime.block_on(future))
+        }
+    }
+}
+
 // ============================================================================
 // MCP Server Implementation
 // ============================================================================
 
+/// Number of resources returned per resources/list page
+const RESOURCE_PAGE_SIZE: usize = 100;
+
 /// MCP Server for changelog search
 #[derive(Clone)]
 struct DigragMcpServer {
-    searcher: Arc<Searcher>,
+    indexes: SharedIndexes,
     config: Arc<AppConfig>,
+    /// Set when `allow_write` enables the add_memo tool
+    writer: Option<Arc<MemoWriter>>,
+    /// LLM digests of result sets, shared across requests
+    digest_cache: Arc<SummaryCache>,
+    /// On-disk cache of LLM summaries (None when disabled)
+    summary_store: Option<Arc<PersistentSummaryCache>>,
 }
 
 /// Request parameters for query_memos tool
@@ -68,9 +460,30 @@ struct QueryMemosParams {
     top_k: usize,
     /// Optional tag filter
     tag_filter: Option<String>,
+    /// Optional topic filter: a topic ID from list_topics
+    #[serde(default)]
+    topic: Option<usize>,
     /// Search mode: "bm25", "semantic", or "hybrid" (default from config.toml)
     #[serde(default)]
     mode: Option<String>,
+    /// MMR lambda for diversifying results (0.0-1.0, omit to disable)
+    #[serde(default)]
+    mmr_lambda: Option<f32>,
+    /// Return only the best-ranked memo of each group of near-duplicates (default: false)
+    #[serde(default)]
+    collapse_duplicates: bool,
+    /// Include tag, category and month counts over every match (default: false)
+    #[serde(default)]
+    facets: bool,
+    /// Number of results to skip (default: 0)
+    #[serde(default)]
+    offset: usize,
+    /// Cursor from a previous response to fetch the next page
+    #[serde(default)]
+    cursor: Option<String>,
+    /// Indexes to use: a name, a list of names, or "all" (default: all)
+    #[serde(default)]
+    index: Option<IndexSelector>,
 
     // Content extraction parameters (Process 7)
     /// Extraction mode: "snippet", "entry", or "full" (default from config.toml)
@@ -85,10 +498,27 @@ struct QueryMemosParams {
     /// Include raw content in response (default: true)
     #[serde(default = "default_true")]
     include_raw: bool,
-    /// Use LLM for summarization (default: false, uses rule-based)
+    /// Use LLM for summarization (default: summarization_enabled in config.toml; needs an API key)
+    #[serde(default)]
+    use_llm_summary: Option<bool>,
+    /// Model for LLM summaries: summarization_model or one of summarization_allowed_models (default: summarization_model)
+    #[serde(default)]
+    summary_model: Option<String>,
+    /// Maximum tokens of each LLM summary, up to summarization_max_tokens_limit (default: summarization_max_tokens)
+    #[serde(default)]
+    summary_max_tokens: Option<usize>,
+    /// Temperature of LLM summaries, between 0 and summarization_max_temperature (default: summarization_temperature)
     #[serde(default)]
-    #[allow(dead_code)]
-    use_llm_summary: bool,
+    summary_temperature: Option<f32>,
+    /// Summary scope: "per_result" (one summary per memo) or "combined" (one digest of all results) (default: per_result)
+    #[serde(default)]
+    summary_scope: Option<String>,
+    /// Prompt template for LLM summaries, from `[[summary_prompts]]` in config.toml (default: summarization_prompt)
+    #[serde(default)]
+    summary_prompt: Option<String>,
+    /// Response format: "text", "json", or "both" (default: "text")
+    #[serde(default)]
+    format: Option<String>,
 }
 
 fn default_top_k() -> usize {
@@ -109,32 +539,197 @@ struct GetRecentMemosParams {
     /// Number of memos to return (default: 10)
     #[serde(default = "default_limit")]
     limit: usize,
+    /// Number of memos to skip (default: 0)
+    #[serde(default)]
+    offset: usize,
+    /// Cursor from a previous response to fetch the next page
+    #[serde(default)]
+    cursor: Option<String>,
+    /// Indexes to use: a name, a list of names, or "all" (default: all)
+    #[serde(default)]
+    index: Option<IndexSelector>,
+    /// Response format: "text", "json", or "both" (default: "text")
+    #[serde(default)]
+    format: Option<String>,
+}
+
+/// Request parameters for timeline tool
+#[derive(Debug, Deserialize, JsonSchema)]
+struct TimelineParams {
+    /// Optional search query; only matching memos are counted
+    #[serde(default)]
+    query: Option<String>,
+    /// Bucket length: "day", "week", or "month" (default: "week")
+    #[serde(default)]
+    period: Option<String>,
+    /// First day to include (YYYY-MM-DD)
+    #[serde(default)]
+    from: Option<String>,
+    /// Last day to include (YYYY-MM-DD)
+    #[serde(default)]
+    to: Option<String>,
+    /// Optional tag filter
+    #[serde(default)]
+    tag_filter: Option<String>,
+    /// Optional topic filter: a topic ID from list_topics
+    #[serde(default)]
+    topic: Option<usize>,
+    /// Search mode that orders the titles of each period: "bm25", "semantic", or "hybrid" (default from config.toml)
+    #[serde(default)]
+    mode: Option<String>,
+    /// Number of titles listed per period (default: 3)
+    #[serde(default = "default_timeline_titles")]
+    titles: usize,
+    /// Indexes to use: a name, a list of names, or "all" (default: all)
+    #[serde(default)]
+    index: Option<IndexSelector>,
+    /// Response format: "text", "json", or "both" (default: "text")
+    #[serde(default)]
+    format: Option<String>,
+}
+
+fn default_timeline_titles() -> usize {
+    DEFAULT_TIMELINE_TITLES
+}
+
+/// Request parameters for answer_question tool
+#[derive(Debug, Deserialize, JsonSchema)]
+struct AnswerQuestionParams {
+    /// Question to answer from the memos
+    question: String,
+    /// Number of memos to retrieve (default: 8)
+    #[serde(default = "default_answer_passages")]
+    top_k: usize,
+    /// Optional tag filter
+    #[serde(default)]
+    tag_filter: Option<String>,
+    /// Optional topic filter: a topic ID from list_topics
+    #[serde(default)]
+    topic: Option<usize>,
+    /// Search mode: "bm25", "semantic", or "hybrid" (default from config.toml)
+    #[serde(default)]
+    mode: Option<String>,
+    /// Token budget for the memo passages sent to the model (default: 3000)
+    #[serde(default = "default_context_tokens")]
+    max_context_tokens: usize,
+    /// Indexes to use: a name, a list of names, or "all" (default: all)
+    #[serde(default)]
+    index: Option<IndexSelector>,
+    /// Response format: "text", "json", or "both" (default: "text")
+    #[serde(default)]
+    format: Option<String>,
+}
+
+fn default_answer_passages() -> usize {
+    DEFAULT_ANSWER_PASSAGES
+}
+
+fn default_context_tokens() -> usize {
+    DEFAULT_CONTEXT_TOKENS
+}
+
+/// Request parameters for list_topics tool
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ListTopicsParams {
+    /// Indexes to use: a name, a list of names, or "all" (default: all)
+    #[serde(default)]
+    index: Option<IndexSelector>,
+    /// Response format: "text", "json", or "both" (default: "text")
+    #[serde(default)]
+    format: Option<String>,
+}
+
+/// Request parameters for list_tags tool
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ListTagsParams {
+    /// Indexes to use: a name, a list of names, or "all" (default: all)
+    #[serde(default)]
+    index: Option<IndexSelector>,
+    /// Response format: "text", "json", or "both" (default: "text")
+    #[serde(default)]
+    format: Option<String>,
 }
 
 fn default_limit() -> usize {
     10
 }
 
+/// Request parameters for get_memo tool
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetMemoParams {
+    /// IDs of the memos to fetch
+    ids: Vec<String>,
+    /// Extraction mode: "snippet", "entry", or "full" (default: "full")
+    #[serde(default)]
+    extraction_mode: Option<String>,
+    /// Maximum characters to extract (default from config.toml)
+    #[serde(default)]
+    max_chars: Option<usize>,
+    /// Number of entries before and after each memo to include (default: 0)
+    #[serde(default)]
+    neighbors: usize,
+    /// Indexes to use: a name, a list of names, or "all" (default: all)
+    #[serde(default)]
+    index: Option<IndexSelector>,
+    /// Response format: "text", "json", or "both" (default: "text")
+    #[serde(default)]
+    format: Option<String>,
+}
+
+/// Request parameters for add_memo tool
+#[derive(Debug, Deserialize, JsonSchema)]
+struct AddMemoParams {
+    /// Memo title (single line)
+    title: String,
+    /// Memo body
+    text: String,
+    /// Tags for the memo
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Response format: "text", "json", or "both" (default: "text")
+    #[serde(default)]
+    format: Option<String>,
+}
+
+/// Request parameters for find_related_memos tool
+#[derive(Debug, Deserialize, JsonSchema)]
+struct FindRelatedMemosParams {
+    /// ID of the memo to find related memos for
+    doc_id: String,
+    /// Number of results to return (default: 10)
+    #[serde(default = "default_top_k")]
+    top_k: usize,
+    /// Optional tag filter
+    tag_filter: Option<String>,
+    /// Search mode: "bm25", "semantic", or "hybrid" (default from config.toml)
+    #[serde(default)]
+    mode: Option<String>,
+    /// Number of results to skip (default: 0)
+    #[serde(default)]
+    offset: usize,
+    /// Cursor from a previous response to fetch the next page
+    #[serde(default)]
+    cursor: Option<String>,
+    /// Response format: "text", "json", or "both" (default: "text")
+    #[serde(default)]
+    format: Option<String>,
+}
+
 #[tool(tool_box)]
 impl DigragMcpServer {
-    fn new(index_dir: String, config: AppConfig) -> Result<Self> {
-        // Check if API key is available for semantic search (config takes priority)
-        let searcher = if let Some(api_key) = config.openrouter_api_key() {
-            tracing::info!("OpenRouter API key found in config, enabling semantic search");
-            let embedding_client = digrag::embedding::OpenRouterEmbedding::new(api_key);
-            Searcher::with_embedding_client(&index_dir, embedding_client)?
-        } else if let Ok(api_key) = std::env::var("OPENROUTER_API_KEY") {
-            tracing::info!("OPENROUTER_API_KEY found in env, enabling semantic search");
-            let embedding_client = digrag::embedding::OpenRouterEmbedding::new(api_key);
-            Searcher::with_embedding_client(&index_dir, embedding_client)?
-        } else {
-            tracing::info!("No API key configured, semantic search disabled");
-            Searcher::new(&index_dir)?
-        };
-        Ok(Self {
-            searcher: Arc::new(searcher),
+    fn new(indexes: SharedIndexes, config: AppConfig) -> Self {
+        Self {
+            indexes,
+            summary_store: open_summary_store(&config),
             config: Arc::new(config),
-        })
+            writer: None,
+            digest_cache: Arc::new(SummaryCache::for_summaries()),
+        }
+    }
+
+    fn with_writer(mut self, writer: Option<MemoWriter>) -> Self {
+        self.writer = writer.map(Arc::new);
+        self
     }
 
     /// Search memos by query with optional filters
@@ -145,40 +740,49 @@ impl DigragMcpServer {
         &self,
         #[tool(aggr)] params: QueryMemosParams,
     ) -> Result<CallToolResult, rmcp::Error> {
-        use digrag::extract::summarizer::ContentSummarizer;
-        use digrag::extract::{ContentExtractor, ExtractionStrategy, TruncationConfig};
-
         // Get effective mode from params or config (config.toml takes priority over hardcoded defaults)
         let effective_mode = params
             .mode
             .as_deref()
             .unwrap_or_else(|| self.config.default_search_mode());
 
-        let search_mode = match effective_mode {
-            "semantic" => SearchMode::Semantic,
-            "hybrid" => SearchMode::Hybrid,
-            _ => SearchMode::Bm25,
-        };
+        let search_mode = SearchMode::parse(effective_mode);
 
         let config = SearchConfig::new()
             .with_mode(search_mode)
             .with_top_k(params.top_k)
-            .with_tag_filter(params.tag_filter);
+            .with_tag_filter(params.tag_filter)
+            .with_topic_filter(params.topic)
+            .with_mmr_lambda(params.mmr_lambda)
+            .with_collapse_duplicates(params.collapse_duplicates)
+            .with_offset(params.offset)
+            .with_cursor(params.cursor);
 
-        let results = self
-            .searcher
-            .search(&params.query, &config)
-            .map_err(|e| rmcp::Error::internal_error(e.to_string(), None))?;
+        let indexes = self.indexes.load();
+        let selection = select_indexes(&indexes, params.index.as_ref())?;
+        let (page, facets) = if params.facets {
+            selection
+                .search_page_with_facets(&params.query, &config)
+                .map(|(page, facets)| (page, Some(facets)))
+        } else {
+            selection
+                .search_page(&params.query, &config)
+                .map(|page| (page, None))
+        }
+        .map_err(|e| rmcp::Error::internal_error(e.to_string(), None))?;
+        let format = ResponseFormat::parse(params.format.as_deref());
 
         let mut output = format!(
-            "Found {} results for '{}':\n\n",
-            results.len(),
-            params.query
+            "Found {} results for '{}'{}:\n\n",
+            page.total_label(),
+            params.query,
+            page_range(&page)
         );
+        let mut warnings = Vec::new();
 
         // Add warning if semantic/hybrid search was requested but no vector index
         if (search_mode == SearchMode::Semantic || search_mode == SearchMode::Hybrid)
-            && !self.searcher.has_vector_index()
+            && !selection.has_vector_index()
         {
             output.push_str(
                 "Note: Vector index is not available. Semantic search requires embeddings.\n",
@@ -186,6 +790,9 @@ impl DigragMcpServer {
             output
                 .push_str("To enable semantic search, rebuild the index with embeddings using:\n");
             output.push_str("  digrag build --input <file> --output <dir> --with-embeddings\n\n");
+            warnings.push(
+                "Vector index is not available; semantic results are empty. Rebuild with --with-embeddings.".to_string(),
+            );
         }
 
         // Get effective extraction mode from params or config
@@ -194,12 +801,7 @@ impl DigragMcpServer {
             .as_deref()
             .unwrap_or_else(|| self.config.extraction_mode());
 
-        // Determine extraction strategy based on mode
-        let extraction_strategy = match effective_extraction_mode {
-            "entry" => ExtractionStrategy::ChangelogEntry,
-            "full" => ExtractionStrategy::Full,
-            _ => ExtractionStrategy::Head(150), // snippet mode (default)
-        };
+        let extraction_strategy = ExtractionStrategy::from_mode(effective_extraction_mode);
 
         let truncation = TruncationConfig {
             max_chars: Some(params.max_chars),
@@ -207,19 +809,59 @@ impl DigragMcpServer {
             max_sections: None,
         };
 
+        let summary_scope = SummaryScope::parse(params.summary_scope.as_deref());
+        let digest_extractor = digest_extractor(&extraction_strategy, params.max_chars);
         let extractor = ContentExtractor::new(extraction_strategy, truncation);
-        let summarizer = ContentSummarizer::from_config(&self.config);
+        let prompt = PromptTemplates::from_config(&self.config)
+            .get(params.summary_prompt.as_deref())
+            .cloned()
+            .map_err(|e| rmcp::Error::invalid_params(e.to_string(), None))?;
+        let options = SummaryOptions {
+            use_llm: params.use_llm_summary,
+            model: params.summary_model.clone(),
+            max_tokens: params.summary_max_tokens,
+            temperature: params.summary_temperature,
+        };
+        options
+            .validate(&self.config)
+            .map_err(|e| rmcp::Error::invalid_params(e.to_string(), None))?;
+        // Only build the summarizer (and load its tokenizer) when a summary
+        // or digest is requested
+        let wants_summary = summary_scope == SummaryScope::Combined
+            || (params.include_summary && effective_extraction_mode != "snippet");
+        let summarizer = if wants_summary {
+            Some(
+                summarizer_from_config(&self.config, &options, self.summary_store.as_ref())
+                    .map_err(|e| rmcp::Error::invalid_params(e.to_string(), None))?
+                    .with_cache(self.digest_cache.clone())
+                    .with_prompt(prompt),
+            )
+        } else {
+            None
+        };
+        let mut memo_results = Vec::with_capacity(page.items.len());
+        let mut digest_sources = Vec::new();
+
+        for (i, hit) in page.items.iter().enumerate() {
+            let result = &hit.result;
+            if let Some(doc) = hit.searcher.docstore().get(&result.doc_id) {
+                let index = index_label(&indexes, hit.index);
+                let mut memo = MemoResult::from_document(doc, Some(result.score)).with_index(index);
 
-        for (i, result) in results.iter().enumerate() {
-            if let Some(doc) = self.searcher.docstore().get(&result.doc_id) {
                 output.push_str(&format!(
                     "{}. [score: {:.4}] {}\n   Date: {}\n   Tags: {:?}\n",
-                    i + 1,
+                    page.offset + i + 1,
                     result.score,
                     doc.title(),
                     doc.date().format("%Y-%m-%d"),
                     doc.tags(),
                 ));
+                if let Some(index) = index {
+                    output.push_str(&format!("   Index: {}\n", index));
+                }
+                if summary_scope == SummaryScope::Combined {
+                    digest_sources.push(digest_source(doc, &digest_extractor));
+                }
 
                 // Extract content based on mode
                 if effective_extraction_mode == "snippet" {
@@ -231,23 +873,28 @@ impl DigragMcpServer {
                 } else {
                     // entry or full mode - use extraction engine
                     let extracted = extractor.extract(&doc.text);
+                    let mut extracted_result = ExtractedResult {
+                        summary: None,
+                        summary_method: None,
+                        summary_model: None,
+                        summary_usage: None,
+                        raw_content: None,
+                        truncated: extracted.truncated,
+                        total_chars: extracted.stats.total_chars,
+                        extracted_chars: extracted.stats.extracted_chars,
+                    };
 
-                    // Add summary if requested
-                    if params.include_summary {
-                        let rt = tokio::runtime::Handle::try_current();
-                        let summary = match rt {
-                            Ok(handle) => tokio::task::block_in_place(|| {
-                                handle.block_on(summarizer.summarize(&extracted))
-                            }),
-                            Err(_) => {
-                                let rt = tokio::runtime::Runtime::new().unwrap();
-                                rt.block_on(summarizer.summarize(&extracted))
-                            }
-                        };
+                    // Add summary if requested (combined digests follow the results)
+                    let per_result = summarizer
+                        .as_ref()
+                        .filter(|_| summary_scope == SummaryScope::PerResult);
+                    if let Some(summarizer) = per_result {
+                        let context = summary_context(doc, &params.query);
+                        let summary = block_on(summarizer.summarize_with(&extracted, &context))?;
 
                         output.push_str(&format!(
                             "\n   ## Summary ({})\n   {}\n",
-                            summary.method,
+                            summary_label(&summary),
                             summary
                                 .text
                                 .lines()
@@ -255,6 +902,10 @@ impl DigragMcpServer {
                                 .collect::<Vec<_>>()
                                 .join("\n")
                         ));
+                        extracted_result.summary = Some(summary.text);
+                        extracted_result.summary_method = Some(summary.method);
+                        extracted_result.summary_model = summary.model;
+                        extracted_result.summary_usage = summary.usage;
                     }
 
                     // Add raw content if requested
@@ -278,28 +929,131 @@ impl DigragMcpServer {
                                 .collect::<Vec<_>>()
                                 .join("\n")
                         ));
+                        extracted_result.raw_content = Some(extracted.text);
                     }
 
                     output.push('\n');
+                    memo = memo.with_extracted(extracted_result);
                 }
+
+                memo_results.push(memo);
             }
         }
 
-        Ok(CallToolResult::success(vec![Content::text(output)]))
+        let digest = match &summarizer {
+            Some(summarizer) if !digest_sources.is_empty() => {
+                let summary = block_on(summarizer.digest(&digest_sources))?;
+                output.push_str(&format_digest(&summary));
+                Some(DigestResult {
+                    text: summary.text,
+                    method: summary.method,
+                    model: summary.model,
+                    usage: summary.usage,
+                    doc_ids: digest_sources.into_iter().map(|s| s.doc_id).collect(),
+                })
+            }
+            _ => None,
+        };
+        if let Some(facets) = &facets {
+            output.push_str(&format_facets(facets));
+        }
+        output.push_str(&next_page_hint(&page));
+        let indexed_at = selection.timestamps();
+        output.push_str(&index_built_note(&indexed_at));
+
+        let response = QueryMemosResponse {
+            query: params.query,
+            mode: effective_mode.to_string(),
+            results: memo_results,
+            total: page.total,
+            total_exact: page.total_exact,
+            offset: page.offset,
+            next_cursor: page.next_cursor,
+            facets,
+            digest,
+            warnings,
+            indexed_at,
+        };
+
+        tool_response(format, output, &response)
     }
 
     /// List all available tags in the changelog
-    #[tool(description = "List all tags in the changelog with their document counts")]
-    fn list_tags(&self) -> Result<CallToolResult, rmcp::Error> {
-        let tags = self.searcher.list_tags();
+    #[tool(
+        description = "List all tags with the number of memos tagged in the changelog (`count`) and of other memos with the tag inferred by suggest-tags (`inferred_count`)"
+    )]
+    fn list_tags(
+        &self,
+        #[tool(aggr)] params: ListTagsParams,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let indexes = self.indexes.load();
+        let tags = select_indexes(&indexes, params.index.as_ref())?.tag_counts();
         let mut output = format!("Found {} tags:\n\n", tags.len());
+        let mut tag_infos = Vec::with_capacity(tags.len());
+
+        for count in tags {
+            if count.inferred > 0 {
+                output.push_str(&format!(
+                    "- {} ({}, +{} inferred)\n",
+                    count.tag, count.authored, count.inferred
+                ));
+            } else {
+                output.push_str(&format!("- {} ({})\n", count.tag, count.authored));
+            }
+            tag_infos.push(TagInfo::from_count(count));
+        }
+
+        let response = ListTagsResponse { tags: tag_infos };
+        tool_response(
+            ResponseFormat::parse(params.format.as_deref()),
+            output,
+            &response,
+        )
+    }
+
+    /// List topics found by clustering the memos
+    #[tool(
+        description = "List topics found by clustering the memos, with labels and document counts. Pass a topic ID as `topic` to query_memos to search within it"
+    )]
+    fn list_topics(
+        &self,
+        #[tool(aggr)] params: ListTopicsParams,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let indexes = self.indexes.load();
+        let selection = select_indexes(&indexes, params.index.as_ref())?;
+        let models = selection.topics();
 
-        for tag in &tags {
-            let count = self.searcher.docstore().get_by_tag(tag).len();
-            output.push_str(&format!("- {} ({})\n", tag, count));
+        let mut topics = Vec::new();
+        for (index, model) in &models {
+            for topic in &model.topics {
+                topics.push(TopicInfo::from_topic(topic).with_index(index_label(&indexes, index)));
+            }
         }
 
-        Ok(CallToolResult::success(vec![Content::text(output)]))
+        let output = if models.is_empty() {
+            "No topics found. Run `digrag topics build` to cluster the memos.\n".to_string()
+        } else {
+            let mut output = format!("Found {} topics:\n\n", topics.len());
+            for topic in &topics {
+                let index = topic
+                    .index
+                    .as_ref()
+                    .map(|index| format!("[{}] ", index))
+                    .unwrap_or_default();
+                output.push_str(&format!(
+                    "- {}{}: {} ({} memos)\n",
+                    index, topic.id, topic.label, topic.count
+                ));
+            }
+            output
+        };
+
+        let response = ListTopicsResponse { topics };
+        tool_response(
+            ResponseFormat::parse(params.format.as_deref()),
+            output,
+            &response,
+        )
     }
 
     /// Get recent memos
@@ -308,36 +1062,498 @@ impl DigragMcpServer {
         &self,
         #[tool(aggr)] params: GetRecentMemosParams,
     ) -> Result<CallToolResult, rmcp::Error> {
-        let memos = self.searcher.get_recent_memos(params.limit);
-        let mut output = format!("Recent {} memos:\n\n", memos.len());
+        let indexes = self.indexes.load();
+        let selection = select_indexes(&indexes, params.index.as_ref())?;
+        let page = selection
+            .recent_page(params.offset, params.cursor.as_deref(), params.limit)
+            .map_err(|e| rmcp::Error::invalid_params(e.to_string(), None))?;
+        let mut output = format!(
+            "Recent {} memos{}:\n\n",
+            page.items.len(),
+            page_range(&page)
+        );
 
-        for (i, doc) in memos.iter().enumerate() {
+        for (i, item) in page.items.iter().enumerate() {
+            let doc = item.doc;
             output.push_str(&format!(
-                "{}. {}\n   Date: {}\n   Tags: {:?}\n   {}\n\n",
-                i + 1,
+                "{}. {}\n   Date: {}\n   Tags: {:?}\n",
+                page.offset + i + 1,
                 doc.title(),
                 doc.date().format("%Y-%m-%d %H:%M"),
                 doc.tags(),
+            ));
+            if let Some(index) = index_label(&indexes, item.index) {
+                output.push_str(&format!("   Index: {}\n", index));
+            }
+            output.push_str(&format!(
+                "   {}\n\n",
                 doc.text.chars().take(150).collect::<String>()
             ));
         }
 
-        Ok(CallToolResult::success(vec![Content::text(output)]))
-    }
-}
+        output.push_str(&next_page_hint(&page));
+        let indexed_at = selection.timestamps();
+        output.push_str(&index_built_note(&indexed_at));
 
-#[tool(tool_box)]
-impl ServerHandler for DigragMcpServer {
-    fn get_info(&self) -> ServerInfo {
-        ServerInfo {
-            instructions: Some(
-                "Changelog memo search server with BM25 and semantic search capabilities".into(),
-            ),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
-            ..Default::default()
-        }
+        let response = GetRecentMemosResponse {
+            memos: page
+                .items
+                .iter()
+                .map(|item| {
+                    MemoResult::from_document(item.doc, None)
+                        .with_index(index_label(&indexes, item.index))
+                })
+                .collect(),
+            total: page.total,
+            total_exact: page.total_exact,
+            offset: page.offset,
+            next_cursor: page.next_cursor.clone(),
+            indexed_at,
+        };
+
+        tool_response(
+            ResponseFormat::parse(params.format.as_deref()),
+            output,
+            &response,
+        )
     }
-}
+
+    /// Histogram of memo activity over time
+    #[tool(
+        description = "Count memos per day, week or month, with the top titles of each period. Optionally restricted to memos matching a search query, a tag or a date range (e.g. 'what did I work on each week in Q3?')"
+    )]
+    fn timeline(
+        &self,
+        #[tool(aggr)] params: TimelineParams,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let period = Period::parse(params.period.as_deref().unwrap_or("week"))
+            .map_err(|e| rmcp::Error::invalid_params(e.to_string(), None))?;
+        let from = parse_date_param("from", params.from.as_deref())?;
+        let to = parse_date_param("to", params.to.as_deref())?;
+        let timeline = Timeline::new(period)
+            .with_titles(params.titles)
+            .with_range(from, to);
+
+        let mode = params
+            .mode
+            .as_deref()
+            .unwrap_or_else(|| self.config.default_search_mode());
+        let config = SearchConfig::new()
+            .with_mode(SearchMode::parse(mode))
+            .with_tag_filter(params.tag_filter)
+            .with_topic_filter(params.topic);
+        let query = params.query.filter(|q| !q.trim().is_empty());
+
+        let indexes = self.indexes.load();
+        let buckets = select_indexes(&indexes, params.index.as_ref())?
+            .timeline(query.as_deref().unwrap_or(""), &config, &timeline)
+            .map_err(|e| rmcp::Error::internal_error(e.to_string(), None))?;
+        let total = buckets.iter().map(|b| b.count).sum();
+
+        let subject = query
+            .as_ref()
+            .map(|q| format!(" matching '{}'", q))
+            .unwrap_or_default();
+        let output = format!(
+            "{} memos{} per {}:\n\n{}",
+            total,
+            subject,
+            period,
+            format_timeline(&buckets)
+        );
+
+        let response = TimelineResponse {
+            query,
+            period,
+            total,
+            buckets,
+        };
+        tool_response(
+            ResponseFormat::parse(params.format.as_deref()),
+            output,
+            &response,
+        )
+    }
+
+    /// Answer a question from retrieved memos
+    #[tool(
+        description = "Answer a question from the memos. Retrieves the most relevant memos, asks the configured chat model (requires an OpenRouter API key) and returns the answer with [n] citations mapped to memo IDs and dates."
+    )]
+    async fn answer_question(
+        &self,
+        #[tool(aggr)] params: AnswerQuestionParams,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let answerer = QuestionAnswerer::from_config(&self.config)
+            .map_err(|e| rmcp::Error::invalid_request(e.to_string(), None))?
+            .with_context_tokens(params.max_context_tokens);
+        let mode = params
+            .mode
+            .as_deref()
+            .unwrap_or_else(|| self.config.default_search_mode());
+        let config = SearchConfig::new()
+            .with_mode(SearchMode::parse(mode))
+            .with_top_k(params.top_k)
+            .with_tag_filter(params.tag_filter)
+            .with_topic_filter(params.topic);
+
+        let passages = {
+            let indexes = self.indexes.load();
+            let selection = select_indexes(&indexes, params.index.as_ref())?;
+            retrieve_passages(&indexes, &selection, &answerer, &params.question, &config)
+                .map_err(|e| rmcp::Error::internal_error(e.to_string(), None))?
+        };
+        let answer = answerer
+            .answer(&params.question, passages)
+            .await
+            .map_err(|e| rmcp::Error::internal_error(e.to_string(), None))?;
+
+        tool_response(
+            ResponseFormat::parse(params.format.as_deref()),
+            format_answer(&answer),
+            &answer,
+        )
+    }
+
+    /// Fetch memos by ID
+    #[tool(
+        description = "Fetch one or more changelog memos by ID. Supports the same extraction modes as query_memos (default 'full') and can include neighbouring entries by date for context."
+    )]
+    fn get_memo(&self, #[tool(aggr)] params: GetMemoParams) -> Result<CallToolResult, rmcp::Error> {
+        if params.ids.is_empty() {
+            return Err(rmcp::Error::invalid_params(
+                "At least one memo ID is required",
+                None,
+            ));
+        }
+
+        let truncation = TruncationConfig {
+            max_chars: Some(
+                params
+                    .max_chars
+                    .unwrap_or_else(|| self.config.extraction_max_chars()),
+            ),
+            max_lines: None,
+            max_sections: None,
+        };
+        let extractor = ContentExtractor::new(
+            ExtractionStrategy::from_mode(params.extraction_mode.as_deref().unwrap_or("full")),
+            truncation,
+        );
+
+        let indexes = self.indexes.load();
+        let selection = select_indexes(&indexes, params.index.as_ref())?;
+        let mut sections = Vec::with_capacity(params.ids.len());
+        let mut memos = Vec::with_capacity(params.ids.len());
+        let mut not_found = Vec::new();
+
+        for id in params.ids {
+            match selection.find(&id) {
+                Some(found) => {
+                    let (mut detail, text) = describe_memo(
+                        found.searcher.docstore(),
+                        found.doc,
+                        &extractor,
+                        params.neighbors,
+                    );
+                    detail.memo.index = index_label(&indexes, found.index).map(str::to_string);
+                    sections.push(text);
+                    memos.push(detail);
+                }
+                None => {
+                    sections.push(format!("Memo not found: {}\n", id));
+                    not_found.push(id);
+                }
+            }
+        }
+
+        let response = GetMemoResponse { memos, not_found };
+        tool_response(
+            ResponseFormat::parse(params.format.as_deref()),
+            sections.join("\n---\n\n"),
+            &response,
+        )
+    }
+
+    /// Find memos related to an existing memo
+    #[tool(
+        description = "Find memos related to a given memo (\"more like this\"). Uses the memo's stored embedding when available, otherwise its most distinctive keywords."
+    )]
+    fn find_related_memos(
+        &self,
+        #[tool(aggr)] params: FindRelatedMemosParams,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        // Related memos come from the index that holds the source memo
+        let indexes = self.indexes.load();
+        let found = select_indexes(&indexes, None)?
+            .find(&params.doc_id)
+            .ok_or_else(|| {
+                rmcp::Error::invalid_params(format!("Memo not found: {}", params.doc_id), None)
+            })?;
+        let (searcher, source) = (found.searcher, found.doc);
+
+        let effective_mode = params
+            .mode
+            .as_deref()
+            .unwrap_or_else(|| self.config.default_search_mode());
+
+        let config = SearchConfig::new()
+            .with_mode(SearchMode::parse(effective_mode))
+            .with_top_k(params.top_k)
+            .with_tag_filter(params.tag_filter)
+            .with_offset(params.offset)
+            .with_cursor(params.cursor);
+
+        let page = searcher
+            .similar_page(&params.doc_id, &config)
+            .map_err(|e| rmcp::Error::invalid_params(e.to_string(), None))?;
+
+        let mut output = format!(
+            "Found {} memos related to '{}'{}:\n\n",
+            page.total_label(),
+            source.title(),
+            page_range(&page)
+        );
+
+        let mut memo_results = Vec::with_capacity(page.items.len());
+
+        for (i, result) in page.items.iter().enumerate() {
+            if let Some(doc) = searcher.docstore().get(&result.doc_id) {
+                memo_results.push(
+                    MemoResult::from_document(doc, Some(result.score))
+                        .with_index(index_label(&indexes, found.index)),
+                );
+                output.push_str(&format!(
+                    "{}. [score: {:.4}] {}\n   ID: {}\n   Date: {}\n   Tags: {:?}\n   {}\n\n",
+                    page.offset + i + 1,
+                    result.score,
+                    doc.title(),
+                    doc.id,
+                    doc.date().format("%Y-%m-%d"),
+                    doc.tags(),
+                    doc.text.chars().take(150).collect::<String>()
+                ));
+            }
+        }
+
+        output.push_str(&next_page_hint(&page));
+
+        let response = RelatedMemosResponse {
+            source_id: source.id.clone(),
+            source_title: source.title().to_string(),
+            results: memo_results,
+            total: page.total,
+            total_exact: page.total_exact,
+            offset: page.offset,
+            next_cursor: page.next_cursor,
+        };
+
+        tool_response(
+            ResponseFormat::parse(params.format.as_deref()),
+            output,
+            &response,
+        )
+    }
+
+    /// Record a new memo in the changelog
+    #[tool(
+        description = "Record a new memo in the changelog. The memo is searchable immediately. Requires allow_write = true in config.toml."
+    )]
+    async fn add_memo(
+        &self,
+        #[tool(aggr)] params: AddMemoParams,
+    ) -> Result<CallToolResult, rmcp::Error> {
+        let writer = self.writer.as_ref().ok_or_else(|| {
+            rmcp::Error::invalid_request(
+                "Writing memos is disabled. Set allow_write = true and changelog_path in config.toml",
+                None,
+            )
+        })?;
+
+        let memo = NewMemo::new(params.title, params.tags, params.text);
+        memo.to_document()
+            .map_err(|e| rmcp::Error::invalid_params(e.to_string(), None))?;
+        let doc = writer
+            .add(&self.indexes, &memo)
+            .await
+            .map_err(|e| rmcp::Error::internal_error(format!("{:#}", e), None))?;
+
+        let changelog = writer.changelog().path().display().to_string();
+        let output = format!(
+            "Added memo '{}' (ID: {}) to {}\n",
+            doc.title(),
+            doc.id,
+            changelog
+        );
+        let response = AddMemoResponse {
+            memo: MemoResult::from_document(&doc, None),
+            changelog,
+        };
+
+        tool_response(
+            ResponseFormat::parse(params.format.as_deref()),
+            output,
+            &response,
+        )
+    }
+}
+
+#[tool(tool_box)]
+impl ServerHandler for DigragMcpServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            instructions: Some(
+                "Changelog memo search server with BM25 and semantic search capabilities".into(),
+            ),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_prompts()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: PaginatedRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, rmcp::Error> {
+        let prompts = list_prompts()
+            .into_iter()
+            .map(|info| {
+                let arguments = info
+                    .arguments
+                    .into_iter()
+                    .map(|arg| PromptArgument {
+                        name: arg.name.to_string(),
+                        description: Some(arg.description.to_string()),
+                        required: Some(arg.required),
+                    })
+                    .collect();
+                Prompt::new(info.name, Some(info.description), Some(arguments))
+            })
+            .collect();
+
+        Ok(ListPromptsResult {
+            prompts,
+            next_cursor: None,
+        })
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, rmcp::Error> {
+        // Arguments are strings per the MCP spec; accept other scalars leniently
+        let args: HashMap<String, String> = request
+            .arguments
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, value)| match value {
+                serde_json::Value::String(s) => (name, s),
+                other => (name, other.to_string()),
+            })
+            .collect();
+
+        let indexes = self.indexes.load();
+        let searcher = indexes
+            .resolve(args.get("index").map(String::as_str))
+            .map_err(|e| rmcp::Error::invalid_params(e.to_string(), None))?;
+        let prompt = PromptBuilder::new(searcher)
+            .with_search_mode(SearchMode::parse(self.config.default_search_mode()))
+            .build(&request.name, &args)
+            .map_err(|e| rmcp::Error::invalid_params(e.to_string(), None))?;
+
+        Ok(GetPromptResult {
+            description: Some(prompt.description),
+            messages: vec![PromptMessage::new_text(
+                PromptMessageRole::User,
+                prompt.text,
+            )],
+        })
+    }
+
+    async fn list_resources(
+        &self,
+        request: PaginatedRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, rmcp::Error> {
+        let cursor = request.and_then(|r| r.cursor);
+        let indexes = self.indexes.load();
+        let page = paginate(
+            list_resources(&indexes.docstores()),
+            |r| r.uri.as_str(),
+            0,
+            cursor.as_deref(),
+            RESOURCE_PAGE_SIZE,
+        )
+        .map_err(|e| rmcp::Error::invalid_params(e.to_string(), None))?;
+
+        let resources = page
+            .items
+            .into_iter()
+            .map(|info| {
+                let mut resource = RawResource::new(info.uri, info.name);
+                resource.description = Some(info.description);
+                resource.mime_type = Some(RESOURCE_MIME_TYPE.to_string());
+                resource.no_annotation()
+            })
+            .collect();
+
+        Ok(ListResourcesResult {
+            resources,
+            next_cursor: page.next_cursor,
+        })
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: PaginatedRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, rmcp::Error> {
+        let resource_templates = list_resource_templates()
+            .into_iter()
+            .map(|info| {
+                RawResourceTemplate {
+                    uri_template: info.uri_template,
+                    name: info.name,
+                    description: Some(info.description),
+                    mime_type: Some(RESOURCE_MIME_TYPE.to_string()),
+                }
+                .no_annotation()
+            })
+            .collect();
+
+        Ok(ListResourceTemplatesResult {
+            resource_templates,
+            next_cursor: None,
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, rmcp::Error> {
+        let text = MemoResource::parse(&request.uri)
+            .and_then(|resource| resource.read(&self.indexes.load().docstores()))
+            .ok_or_else(|| {
+                rmcp::Error::resource_not_found(
+                    format!("Resource not found: {}", request.uri),
+                    None,
+                )
+            })?;
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: request.uri,
+                mime_type: Some(RESOURCE_MIME_TYPE.to_string()),
+                text,
+            }],
+        })
+    }
+}
 
 // ============================================================================
 // CLI Implementation
@@ -382,6 +1598,89 @@ fn load_app_config() -> AppConfig {
     }
 }
 
+/// Files and index a tag edit applies to
+#[derive(Args)]
+struct TagTarget {
+    /// Changelog file(s) or directory(s) to rewrite (default: changelog_path from config.toml)
+    #[arg(short, long, action = ArgAction::Append)]
+    input: Vec<String>,
+
+    /// Path to the index directory to update
+    #[arg(long)]
+    index_dir: Option<String>,
+
+    /// Show the changes without writing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Subcommand)]
+enum TopicsAction {
+    /// Cluster the memos of an index into topics and store them
+    Build {
+        /// Path to the index directory
+        #[arg(short, long)]
+        index_dir: Option<String>,
+
+        /// Number of topics (default: about sqrt(memos / 2))
+        #[arg(short = 'k', long)]
+        topics: Option<usize>,
+
+        /// Number of terms in each topic label
+        #[arg(long, default_value_t = DEFAULT_TOPIC_TERMS)]
+        terms: usize,
+    },
+    /// Show the stored topics
+    List {
+        /// Path to the index directory
+        #[arg(short, long)]
+        index_dir: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Show the number, size and age of cached summaries
+    Stats,
+    /// Delete cached summaries
+    Clear {
+        /// Only delete expired summaries
+        #[arg(long)]
+        expired: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum TagsAction {
+    /// Rename a tag
+    Rename {
+        /// Current tag name
+        from: String,
+        /// New tag name
+        to: String,
+        #[command(flatten)]
+        target: TagTarget,
+    },
+    /// Merge several tags into one
+    Merge {
+        /// Tags to merge
+        #[arg(required = true)]
+        from: Vec<String>,
+        /// Tag to merge into
+        #[arg(long)]
+        into: String,
+        #[command(flatten)]
+        target: TagTarget,
+    },
+    /// Remove a tag from every entry
+    Delete {
+        /// Tag to remove
+        tag: String,
+        #[command(flatten)]
+        target: TagTarget,
+    },
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize digrag configuration
@@ -392,9 +1691,27 @@ enum Commands {
     },
     /// Start the MCP server
     Serve {
-        /// Path to the index directory (default: .rag)
-        #[arg(short, long, default_value = ".rag")]
-        index_dir: String,
+        /// Path to a single index directory (default: indexes from config.toml)
+        #[arg(short, long)]
+        index_dir: Option<String>,
+
+        /// Transport: stdio or http (SSE)
+        #[arg(long, default_value = "stdio", value_parser = ["stdio", "http"])]
+        transport: String,
+
+        /// Address to listen on with --transport http
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        bind: String,
+    },
+    /// Start the REST/JSON API server
+    Http {
+        /// Path to the index directory
+        #[arg(short, long)]
+        index_dir: Option<String>,
+
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8081")]
+        bind: String,
     },
     /// Build search indices from changelog file
     Build {
@@ -421,61 +1738,628 @@ enum Commands {
         /// Force full rebuild even with --incremental
         #[arg(long)]
         force: bool,
+
+        /// Infer tags for untagged memos after building (see suggest-tags)
+        #[arg(long)]
+        suggest_tags: bool,
+
+        /// Embed one memo per group of near-duplicates and share its vector
+        #[arg(long)]
+        skip_duplicate_embeddings: bool,
     },
-    /// Search the changelog (for testing)
-    Search {
-        /// Search query
-        query: String,
+    /// Record a new memo in the changelog and add it to the index
+    Add {
+        /// Memo title
+        title: String,
+
+        /// Memo body (read from stdin when omitted)
+        #[arg(long)]
+        text: Option<String>,
+
+        /// Tag for the memo - can be specified multiple times
+        #[arg(short, long = "tag", action = ArgAction::Append)]
+        tags: Vec<String>,
+
+        /// Changelog file to write to (default: changelog_path from config.toml)
+        #[arg(short, long)]
+        changelog: Option<String>,
 
         /// Path to the index directory
         #[arg(short, long)]
         index_dir: Option<String>,
+    },
+    /// Rename, merge or delete tags in the changelog files
+    Tags {
+        #[command(subcommand)]
+        action: TagsAction,
+    },
+    /// Cluster memos into topics, or list the stored topics
+    Topics {
+        #[command(subcommand)]
+        action: TopicsAction,
+    },
+    /// Inspect or clear the on-disk summary cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Show memo activity per day, week or month
+    Timeline {
+        /// Only count memos matching this search query
+        query: Option<String>,
 
-        /// Number of results to return
+        /// Path to the index directory
         #[arg(short, long)]
-        top_k: Option<usize>,
+        index_dir: Option<String>,
 
-        /// Search mode: bm25, semantic, or hybrid
-        #[arg(short, long)]
-        mode: Option<String>,
+        /// Bucket length: day, week, or month
+        #[arg(short, long, default_value = "week")]
+        period: String,
+
+        /// First day to include (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// Last day to include (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<NaiveDate>,
 
         /// Filter by tag
         #[arg(long)]
         tag: Option<String>,
 
-        /// Extraction mode: snippet, entry, or full
-        #[arg(short = 'e', long)]
-        extraction: Option<String>,
+        /// Filter by topic ID (see `digrag topics list`)
+        #[arg(long)]
+        topic: Option<usize>,
+
+        /// Search mode that orders the titles of each period: bm25, semantic, or hybrid
+        #[arg(short, long)]
+        mode: Option<String>,
+
+        /// Number of titles listed per period
+        #[arg(long, default_value_t = DEFAULT_TIMELINE_TITLES)]
+        titles: usize,
     },
-}
+    /// Report groups of near-duplicate memos
+    Dedupe {
+        /// Path to the index directory
+        #[arg(short, long)]
+        index_dir: Option<String>,
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+        /// Token similarity (Jaccard, 0-1) above which memos are duplicates
+        #[arg(long, default_value_t = DEFAULT_DUPLICATE_THRESHOLD)]
+        threshold: f32,
 
-    // Initialize logging (to stderr to not interfere with MCP stdio)
-    let log_level = if cli.verbose { "debug" } else { "warn" };
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| log_level.to_string()),
-        ))
-        .with(
-            tracing_subscriber::fmt::layer()
-                .with_target(false)
-                .with_writer(std::io::stderr),
-        )
-        .init();
+        /// Also require this embedding cosine similarity when memos have vectors
+        #[arg(long)]
+        vector_threshold: Option<f32>,
+    },
+    /// Suggest tags for untagged memos from similar tagged memos
+    SuggestTags {
+        /// Path to the index directory
+        #[arg(short, long)]
+        index_dir: Option<String>,
 
-    match cli.command {
-        Commands::Init { force } => {
-            let config_dir = path_resolver::get_config_dir();
-            let config_path = config_dir.join("config.toml");
+        /// Number of tagged neighbours that vote
+        #[arg(short = 'k', long, default_value_t = DEFAULT_SUGGEST_NEIGHBORS)]
+        neighbors: usize,
 
-            eprintln!("Initializing digrag configuration...");
-            eprintln!("Config directory: {}", config_dir.display());
+        /// Minimum confidence (0-1) of a suggested tag
+        #[arg(long, default_value_t = DEFAULT_MIN_CONFIDENCE)]
+        min_confidence: f32,
 
-            // Create config directory
-            if !config_dir.exists() {
+        /// Maximum number of tags suggested per memo
+        #[arg(long, default_value_t = DEFAULT_MAX_SUGGESTED_TAGS)]
+        max_tags: usize,
+
+        /// Store the suggestions in the index as inferred tags
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Watch input files and re-index them incrementally as they change
+    Watch {
+        /// Path to the changelog file(s) or directory(s) - can be specified multiple times
+        #[arg(short, long, action = ArgAction::Append, required = true)]
+        input: Vec<String>,
+
+        /// Path to the output index directory
+        #[arg(short, long, default_value = ".rag")]
+        output: String,
+
+        /// Generate embeddings for changed memos (requires OPENROUTER_API_KEY)
+        #[arg(long)]
+        with_embeddings: bool,
+
+        /// Milliseconds between scans of the input files
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+
+        /// Milliseconds the inputs must stay unchanged before re-indexing
+        #[arg(long, default_value_t = 500)]
+        debounce_ms: u64,
+
+        /// Also serve MCP from the watched index in this process
+        #[arg(long)]
+        serve: bool,
+
+        /// Transport for --serve: stdio or http (SSE)
+        #[arg(long, default_value = "stdio", value_parser = ["stdio", "http"])]
+        transport: String,
+
+        /// Address to listen on with --serve --transport http
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        bind: String,
+    },
+    /// Search the changelog (for testing)
+    Search {
+        /// Search query
+        query: String,
+
+        /// Path to the index directory
+        #[arg(short, long)]
+        index_dir: Option<String>,
+
+        /// Number of results to return
+        #[arg(short, long)]
+        top_k: Option<usize>,
+
+        /// Search mode: bm25, semantic, or hybrid
+        #[arg(short, long)]
+        mode: Option<String>,
+
+        /// Filter by tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Filter by topic ID (see `digrag topics list`)
+        #[arg(long)]
+        topic: Option<usize>,
+
+        /// Extraction mode: snippet, entry, or full
+        #[arg(short = 'e', long)]
+        extraction: Option<String>,
+
+        /// Diversify results with MMR (0.0 = max diversity, 1.0 = pure relevance)
+        #[arg(long)]
+        mmr_lambda: Option<f32>,
+
+        /// Show only the best-ranked memo of each group of near-duplicates
+        #[arg(long)]
+        collapse_duplicates: bool,
+
+        /// Show how all matches are distributed across tags, categories and months
+        #[arg(long)]
+        facets: bool,
+
+        /// Summarize all results of the page in one digest
+        #[arg(long)]
+        digest: bool,
+
+        /// Number of results to skip
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+
+        /// Cursor printed with the previous page
+        #[arg(long)]
+        cursor: Option<String>,
+    },
+    /// Answer a question from the memos with the configured chat model
+    Ask {
+        /// Question to answer
+        question: String,
+
+        /// Path to the index directory
+        #[arg(short, long)]
+        index_dir: Option<String>,
+
+        /// Number of memos to retrieve
+        #[arg(short = 'k', long, default_value_t = DEFAULT_ANSWER_PASSAGES)]
+        top_k: usize,
+
+        /// Search mode: bm25, semantic, or hybrid
+        #[arg(short, long)]
+        mode: Option<String>,
+
+        /// Filter by tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Filter by topic ID (see `digrag topics list`)
+        #[arg(long)]
+        topic: Option<usize>,
+
+        /// Token budget for the memo passages sent to the model
+        #[arg(long, default_value_t = DEFAULT_CONTEXT_TOKENS)]
+        max_context_tokens: usize,
+    },
+    /// Show memos by ID
+    Show {
+        /// IDs of the memos to show
+        #[arg(required = true)]
+        ids: Vec<String>,
+
+        /// Path to the index directory
+        #[arg(short, long)]
+        index_dir: Option<String>,
+
+        /// Extraction mode: snippet, entry, or full
+        #[arg(short = 'e', long, default_value = "full")]
+        extraction: String,
+
+        /// Maximum characters to show per memo
+        #[arg(long)]
+        max_chars: Option<usize>,
+
+        /// Number of entries before and after each memo to list
+        #[arg(short, long, default_value_t = 0)]
+        neighbors: usize,
+    },
+    /// Find memos related to an existing memo
+    Similar {
+        /// ID of the source document
+        doc_id: String,
+
+        /// Path to the index directory
+        #[arg(short, long)]
+        index_dir: Option<String>,
+
+        /// Number of results to return
+        #[arg(short, long)]
+        top_k: Option<usize>,
+
+        /// Search mode: bm25, semantic, or hybrid
+        #[arg(short, long)]
+        mode: Option<String>,
+
+        /// Filter by tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
+}
+
+/// Create the memo writer when `allow_write` is enabled
+///
+/// Memos are added to the first (default) index. `changelog` overrides
+/// `changelog_path` from config.toml.
+fn memo_writer(
+    dirs: &[(String, PathBuf)],
+    config: &AppConfig,
+    changelog: Option<&str>,
+) -> Result<Option<MemoWriter>> {
+    if !config.allow_write() {
+        return Ok(None);
+    }
+    let changelog = changelog
+        .or_else(|| config.changelog_path())
+        .ok_or_else(|| anyhow::anyhow!("allow_write requires changelog_path in config.toml"))?;
+    let (name, dir) = dirs
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("No index to add memos to"))?;
+
+    Ok(Some(MemoWriter::new(
+        ChangelogWriter::new(resolve_path(changelog), config.write_position()),
+        name,
+        dir.clone(),
+    )))
+}
+
+/// Serve MCP over stdio or HTTP/SSE, reloading indexes when they are rebuilt
+async fn serve_mcp(
+    indexes: IndexSet,
+    dirs: Vec<(String, PathBuf)>,
+    app_config: AppConfig,
+    transport: &str,
+    bind: &str,
+) -> Result<()> {
+    let bearer_token = app_config.http_bearer_token().map(str::to_string);
+
+    // Swap in rebuilt indexes without restarting the server
+    let shared = SharedIndexes::new(indexes);
+    let reload_config = app_config.clone();
+    let dirs_for_writer = dirs.clone();
+    let reloader = IndexReloader::new(shared.clone(), dirs, move |dir: &Path| {
+        load_searcher(dir, &reload_config)
+    });
+    tokio::spawn(reloader.run());
+
+    // Create MCP server with the loaded indexes and config
+    let writer = memo_writer(&dirs_for_writer, &app_config, None)?;
+    let server = DigragMcpServer::new(shared, app_config).with_writer(writer);
+
+    if transport == "http" {
+        let listener = tokio::net::TcpListener::bind(bind).await?;
+        eprintln!(
+            "Index loaded. Serving MCP over HTTP/SSE at http://{}{}{}",
+            listener.local_addr()?,
+            SSE_PATH,
+            if bearer_token.is_some() {
+                " (bearer token required)"
+            } else {
+                ""
+            }
+        );
+        // Sessions share the loaded indexes through the cloned Arc
+        return serve_sse(listener, bearer_token, move || server.clone()).await;
+    }
+
+    eprintln!("Index loaded. Starting MCP stdio transport...");
+
+    // Serve via stdio transport
+    let transport = (stdin(), stdout());
+    let service = server.serve(transport).await?;
+
+    // Wait for service to complete
+    let _quit_reason = service.waiting().await?;
+    Ok(())
+}
+
+/// Render duplicate clusters with each memo's ID, date and title
+fn format_duplicate_clusters(docstore: &Docstore, clusters: &[DuplicateCluster]) -> String {
+    let mut output = String::new();
+    for (i, cluster) in clusters.iter().enumerate() {
+        output.push_str(&format!(
+            "Group {} (similarity >= {:.2}):\n",
+            i + 1,
+            cluster.similarity
+        ));
+        for id in &cluster.doc_ids {
+            match docstore.get(id) {
+                Some(doc) => output.push_str(&format!(
+                    "  {}  {}  {}\n",
+                    id,
+                    doc.date().format("%Y-%m-%d"),
+                    doc.title()
+                )),
+                None => output.push_str(&format!("  {}\n", id)),
+            }
+        }
+    }
+    output
+}
+
+/// Retrieve memos for a question and pack them into passages
+fn retrieve_passages(
+    indexes: &IndexSet,
+    selection: &IndexSelection,
+    answerer: &QuestionAnswerer,
+    question: &str,
+    config: &SearchConfig,
+) -> Result<Vec<Passage>> {
+    let page = selection.search_page(question, config)?;
+    Ok(answerer.pack(page.items.iter().filter_map(|hit| {
+        let doc = hit.searcher.docstore().get(&hit.result.doc_id)?;
+        Some((doc, index_label(indexes, hit.index)))
+    })))
+}
+
+/// Render an answer followed by the memos it cites
+fn format_answer(answer: &Answer) -> String {
+    let mut output = format!("{}\n", answer.answer.trim_end());
+    let (heading, passages) = if answer.citations.is_empty() {
+        ("Retrieved memos", &answer.sources)
+    } else {
+        ("Sources", &answer.citations)
+    };
+    if !passages.is_empty() {
+        output.push_str(&format!("\n{}:\n", heading));
+        for passage in passages {
+            let index = passage
+                .index
+                .as_ref()
+                .map(|index| format!("{}/", index))
+                .unwrap_or_default();
+            output.push_str(&format!(
+                "[{}] {} ({}, {}{})\n",
+                passage.number, passage.title, passage.date, index, passage.doc_id
+            ));
+        }
+    }
+    output
+}
+
+/// Parse an optional YYYY-MM-DD tool parameter
+fn parse_date_param(name: &str, value: Option<&str>) -> Result<Option<NaiveDate>, rmcp::Error> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+                rmcp::Error::invalid_params(
+                    format!("Invalid date for {}: {} (expected YYYY-MM-DD)", name, value),
+                    None,
+                )
+            })
+        })
+        .transpose()
+}
+
+/// Render facet counts, listing the most frequent tags and categories and
+/// the latest months
+fn format_facets(facets: &Facets) -> String {
+    const FACET_VALUES: usize = 10;
+
+    let line = |name: &str, counts: &[FacetCount]| {
+        let values: Vec<String> = counts
+            .iter()
+            .map(|f| format!("{} ({})", f.value, f.count))
+            .collect();
+        let more = if values.len() > FACET_VALUES {
+            ", ..."
+        } else {
+            ""
+        };
+        format!(
+            "  {}: {}{}\n",
+            name,
+            values[..values.len().min(FACET_VALUES)].join(", "),
+            more
+        )
+    };
+
+    let mut output = String::from("Facets:\n");
+    if !facets.tags.is_empty() {
+        output.push_str(&line("Tags", &facets.tags));
+    }
+    if !facets.categories.is_empty() {
+        output.push_str(&line("Categories", &facets.categories));
+    }
+    let latest = facets.months.len().saturating_sub(FACET_VALUES);
+    let months: Vec<FacetCount> = facets.months[latest..].iter().rev().cloned().collect();
+    if !months.is_empty() {
+        output.push_str(&line("Months", &months));
+    }
+    output
+}
+
+/// Render timeline buckets as a histogram with the listed memos
+fn format_timeline(buckets: &[TimelineBucket]) -> String {
+    const BAR_WIDTH: usize = 30;
+    const BUCKET_TAGS: usize = 5;
+
+    if buckets.is_empty() {
+        return "No memos found\n".to_string();
+    }
+    let max = buckets.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+    let label_width = buckets.iter().map(|b| b.label.len()).max().unwrap_or(0);
+
+    let mut output = String::new();
+    for bucket in buckets {
+        let bar = "#".repeat((bucket.count * BAR_WIDTH).div_ceil(max));
+        let line = format!(
+            "{:<width$}  {:>4} {}",
+            bucket.label,
+            bucket.count,
+            bar,
+            width = label_width
+        );
+        output.push_str(line.trim_end());
+        output.push('\n');
+
+        let mut tags: Vec<(&String, &usize)> = bucket.tags.iter().collect();
+        tags.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        if !tags.is_empty() {
+            let tags: Vec<String> = tags
+                .into_iter()
+                .take(BUCKET_TAGS)
+                .map(|(tag, count)| format!("{} {}", tag, count))
+                .collect();
+            output.push_str(&format!("    tags: {}\n", tags.join(", ")));
+        }
+        for memo in &bucket.memos {
+            output.push_str(&format!(
+                "    - {} ({}, {})\n",
+                memo.title, memo.date, memo.doc_id
+            ));
+        }
+    }
+    output
+}
+
+/// Render topics with their size and newest memo titles
+fn format_topics(docstore: &Docstore, model: &TopicModel) -> String {
+    const SAMPLE_TITLES: usize = 3;
+
+    let mut output = String::new();
+    for topic in &model.topics {
+        output.push_str(&format!(
+            "{}. {} ({} memos)\n",
+            topic.id,
+            topic.label,
+            topic.doc_ids.len()
+        ));
+        let mut docs: Vec<&Document> = topic
+            .doc_ids
+            .iter()
+            .filter_map(|id| docstore.get(id))
+            .collect();
+        docs.sort_by(|a, b| b.date().cmp(&a.date()).then_with(|| a.id.cmp(&b.id)));
+        for doc in docs.into_iter().take(SAMPLE_TITLES) {
+            output.push_str(&format!("   - {}\n", doc.title()));
+        }
+    }
+    output
+}
+
+/// Share one embedding across near-duplicates when requested
+fn duplicate_aware(builder: IndexBuilder, skip_duplicates: bool) -> IndexBuilder {
+    if skip_duplicates {
+        builder.with_duplicate_detector(DuplicateDetector::new())
+    } else {
+        builder
+    }
+}
+
+/// Render tag suggestions as one block per memo
+fn format_tag_suggestions(suggestions: &[TagSuggestion]) -> String {
+    let mut output = String::new();
+    for suggestion in suggestions {
+        output.push_str(&format!("{} ({})\n", suggestion.title, suggestion.doc_id));
+        for tag in &suggestion.tags {
+            output.push_str(&format!("  {} {:.2}\n", tag.tag, tag.confidence));
+        }
+    }
+    output
+}
+
+/// Map document IDs to their suggested tag names
+fn inferred_tag_map(suggestions: &[TagSuggestion]) -> HashMap<String, Vec<String>> {
+    suggestions
+        .iter()
+        .map(|s| (s.doc_id.clone(), s.tag_names()))
+        .collect()
+}
+
+/// Store default tag suggestions for untagged memos in a freshly built index
+fn infer_tags(index_dir: &Path) -> Result<()> {
+    let searcher = Searcher::new(index_dir)?;
+    let suggestions = TagSuggester::new().suggest_untagged(&searcher)?;
+    let updated = IndexBuilder::update_inferred_tags(index_dir, &inferred_tag_map(&suggestions))?;
+    eprintln!("Inferred tags for {} untagged memos", updated);
+    Ok(())
+}
+
+/// Print what a watch sync changed
+fn report_sync(touched: &[PathBuf], outcome: Result<IncrementalDiff>) {
+    match outcome {
+        Ok(diff) if diff.has_changes() => eprintln!(
+            "Re-indexed {} file(s): {} added, {} modified, {} removed",
+            touched.len(),
+            diff.added_count(),
+            diff.modified_count(),
+            diff.removed_count()
+        ),
+        Ok(_) => eprintln!("{} file(s) changed, no memo changes", touched.len()),
+        Err(e) => eprintln!("Re-indexing failed: {:#}", e),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    // Initialize logging (to stderr to not interfere with MCP stdio)
+    let log_level = if cli.verbose { "debug" } else { "warn" };
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(
+            std::env::var("RUST_LOG").unwrap_or_else(|_| log_level.to_string()),
+        ))
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_target(false)
+                .with_writer(std::io::stderr),
+        )
+        .init();
+
+    match cli.command {
+        Commands::Init { force } => {
+            let config_dir = path_resolver::get_config_dir();
+            let config_path = config_dir.join("config.toml");
+
+            eprintln!("Initializing digrag configuration...");
+            eprintln!("Config directory: {}", config_dir.display());
+
+            // Create config directory
+            if !config_dir.exists() {
                 std::fs::create_dir_all(&config_dir)?;
                 eprintln!("Created config directory");
             }
@@ -501,18 +2385,20 @@ async fn main() -> Result<()> {
 
             Ok(())
         }
-        Commands::Serve { index_dir } => {
+        Commands::Serve {
+            index_dir,
+            transport,
+            bind,
+        } => {
             // Load application configuration
             let app_config = load_app_config();
 
-            let resolved_index_dir = resolve_path(&index_dir);
-            tracing::info!(
-                "Starting MCP server with index directory: {}",
-                resolved_index_dir
-            );
+            let dirs = index_dirs(index_dir.as_deref(), &app_config);
+            let indexes = load_indexes(&dirs, &app_config)?;
+            tracing::info!("Starting MCP server with indexes: {:?}", indexes.names());
             eprintln!(
-                "digrag MCP server starting... (index_dir: {})",
-                resolved_index_dir
+                "digrag MCP server starting... (indexes: {})",
+                indexes.names().join(", ")
             );
 
             if app_config.summarization_enabled() {
@@ -522,17 +2408,30 @@ async fn main() -> Result<()> {
                 );
             }
 
-            // Create MCP server with searcher and config
-            let server = DigragMcpServer::new(resolved_index_dir.clone(), app_config)?;
-            eprintln!("Index loaded. Starting MCP stdio transport...");
+            serve_mcp(indexes, dirs, app_config, &transport, &bind).await
+        }
+        Commands::Http { index_dir, bind } => {
+            let app_config = load_app_config();
+
+            let (name, dir) = single_index_dir(index_dirs(index_dir.as_deref(), &app_config))?;
+            let searcher = load_searcher(&dir, &app_config)?;
+            let listener = tokio::net::TcpListener::bind(&bind).await?;
+            eprintln!(
+                "digrag REST API listening on http://{} (index_dir: {})",
+                listener.local_addr()?,
+                dir.display()
+            );
 
-            // Serve via stdio transport
-            let transport = (stdin(), stdout());
-            let service = server.serve(transport).await?;
+            let shared = SharedIndexes::new(IndexSet::new(name.clone(), searcher));
+            let reload_config = app_config.clone();
+            let reloader =
+                IndexReloader::new(shared.clone(), vec![(name, dir)], move |dir: &Path| {
+                    load_searcher(dir, &reload_config)
+                });
+            tokio::spawn(reloader.run());
 
-            // Wait for service to complete
-            let _quit_reason = service.waiting().await?;
-            Ok(())
+            let state = ApiState::new(shared, Arc::new(app_config));
+            serve_api(listener, state).await
         }
         Commands::Build {
             input,
@@ -541,6 +2440,8 @@ async fn main() -> Result<()> {
             with_embeddings,
             incremental,
             force,
+            suggest_tags,
+            skip_duplicate_embeddings,
         } => {
             if input.is_empty() {
                 return Err(anyhow::anyhow!("At least one --input is required"));
@@ -598,7 +2499,10 @@ async fn main() -> Result<()> {
                     let api_key = std::env::var("OPENROUTER_API_KEY").map_err(|_| {
                         anyhow::anyhow!("OPENROUTER_API_KEY environment variable not set")
                     })?;
-                    let builder = IndexBuilder::with_embeddings(api_key);
+                    let builder = duplicate_aware(
+                        IndexBuilder::with_embeddings(api_key),
+                        skip_duplicate_embeddings,
+                    );
                     builder
                         .build_from_documents_with_embeddings(
                             documents,
@@ -620,6 +2524,9 @@ async fn main() -> Result<()> {
                     )?;
                 }
 
+                if suggest_tags {
+                    infer_tags(output_path)?;
+                }
                 eprintln!("\nIndex build complete!");
                 return Ok(());
             }
@@ -694,7 +2601,10 @@ async fn main() -> Result<()> {
 
                 eprintln!("Embedding generation enabled (using OpenRouter API)");
 
-                let builder = IndexBuilder::with_embeddings(api_key);
+                let builder = duplicate_aware(
+                    IndexBuilder::with_embeddings(api_key),
+                    skip_duplicate_embeddings,
+                );
                 builder
                     .build_from_documents_with_embeddings(
                         all_documents,
@@ -716,42 +2626,394 @@ async fn main() -> Result<()> {
                 )?;
             }
 
+            if suggest_tags {
+                infer_tags(output_path)?;
+            }
             eprintln!("\nIndex build complete!");
             Ok(())
         }
-        Commands::Search {
-            query,
+        Commands::Add {
+            title,
+            text,
+            tags,
+            changelog,
             index_dir,
-            top_k,
-            mode,
-            tag,
-            extraction,
         } => {
-            // Load config and apply CLI overrides
             let app_config = load_app_config();
+            if !app_config.allow_write() {
+                return Err(anyhow::anyhow!(
+                    "Writing memos is disabled. Set allow_write = true in config.toml"
+                ));
+            }
+
+            let text = match text {
+                Some(text) => text,
+                None => io::read_to_string(io::stdin())?,
+            };
+            let memo = NewMemo::new(title, tags, text);
 
             let resolved_index_dir =
                 resolve_path(&index_dir.unwrap_or_else(|| app_config.index_dir().to_string()));
-            let effective_top_k = top_k.unwrap_or_else(|| app_config.default_top_k());
-            let effective_mode =
-                mode.unwrap_or_else(|| app_config.default_search_mode().to_string());
-            let effective_extraction =
-                extraction.unwrap_or_else(|| app_config.extraction_mode().to_string());
+            let dirs = vec![(
+                DEFAULT_INDEX_NAME.to_string(),
+                PathBuf::from(&resolved_index_dir),
+            )];
+            let writer = memo_writer(&dirs, &app_config, changelog.as_deref())?
+                .ok_or_else(|| anyhow::anyhow!("Writing memos is disabled"))?;
+            let indexes = SharedIndexes::new(load_indexes(&dirs, &app_config)?);
 
-            let search_mode = match effective_mode.as_str() {
-                "bm25" => SearchMode::Bm25,
-                "semantic" => SearchMode::Semantic,
-                "hybrid" => SearchMode::Hybrid,
-                _ => {
-                    eprintln!("Unknown mode '{}', using bm25", effective_mode);
-                    SearchMode::Bm25
-                }
+            let doc = writer.add(&indexes, &memo).await?;
+            eprintln!(
+                "Added memo to {} and index {}",
+                writer.changelog().path().display(),
+                resolved_index_dir
+            );
+            println!("{}", doc.id);
+            Ok(())
+        }
+        Commands::Tags { action } => {
+            let (edit, target) = match action {
+                TagsAction::Rename { from, to, target } => (TagEdit::rename(&from, &to)?, target),
+                TagsAction::Merge { from, into, target } => (TagEdit::merge(&from, &into)?, target),
+                TagsAction::Delete { tag, target } => (TagEdit::delete(&tag)?, target),
             };
+            let app_config = load_app_config();
 
-            // Set up extraction strategy based on effective_extraction
-            let extraction_strategy = match effective_extraction.as_str() {
-                "entry" => ExtractionStrategy::ChangelogEntry,
-                "full" => ExtractionStrategy::Full,
+            let inputs: Vec<PathBuf> = if target.input.is_empty() {
+                let changelog = app_config.changelog_path().ok_or_else(|| {
+                    anyhow::anyhow!("--input is required when changelog_path is not configured")
+                })?;
+                vec![resolve_path(changelog).into()]
+            } else {
+                target
+                    .input
+                    .iter()
+                    .map(|i| resolve_path(i).into())
+                    .collect()
+            };
+
+            let rewrites = TagRewrite::plan_all(&expand_inputs(&inputs), &edit)?;
+            if rewrites.is_empty() {
+                eprintln!("No entries use the given tag(s); nothing to do.");
+                return Ok(());
+            }
+            let entries: usize = rewrites.iter().map(TagRewrite::changed_lines).sum();
+
+            if target.dry_run {
+                for rewrite in &rewrites {
+                    print!("{}", rewrite.diff());
+                }
+                eprintln!(
+                    "\nDry run: {} entries in {} file(s) would change",
+                    entries,
+                    rewrites.len()
+                );
+                return Ok(());
+            }
+
+            let mut retagged = HashMap::new();
+            for rewrite in &rewrites {
+                rewrite.write()?;
+                retagged.extend(rewrite.retagged.clone());
+            }
+            eprintln!("Rewrote {} entries in {} file(s)", entries, rewrites.len());
+
+            let index_dir = resolve_path(
+                &target
+                    .index_dir
+                    .unwrap_or_else(|| app_config.index_dir().to_string()),
+            );
+            let index_path = Path::new(&index_dir);
+            if index_path.join("docstore.json").exists() {
+                let updated = IndexBuilder::update_tags(index_path, &retagged)?;
+                eprintln!("Updated tags of {} documents in {}", updated, index_dir);
+
+                // Tags are part of the embedding text
+                let retagged_ids: Vec<String> = retagged.into_keys().collect();
+                let stale = IndexBuilder::embedded_documents(index_path, &retagged_ids)?;
+                if !stale.is_empty() {
+                    match app_config.openrouter_api_key() {
+                        Some(api_key) => {
+                            let embedded = IndexBuilder::with_embeddings(api_key)
+                                .refresh_embeddings(index_path, &stale)
+                                .await?;
+                            eprintln!("Re-embedded {} documents", embedded);
+                        }
+                        None => eprintln!(
+                            "Warning: the embeddings of {} documents still use their old tags; \
+                             set OPENROUTER_API_KEY to re-embed them on tag edits, or run a full \
+                             `digrag build --with-embeddings`",
+                            stale.len()
+                        ),
+                    }
+                }
+            } else {
+                eprintln!(
+                    "No index at {}; run `digrag build` to index the changes",
+                    index_dir
+                );
+            }
+            Ok(())
+        }
+        Commands::Topics { action } => {
+            let app_config = load_app_config();
+            let index_dir = match &action {
+                TopicsAction::Build { index_dir, .. } | TopicsAction::List { index_dir } => {
+                    index_dir.clone()
+                }
+            };
+            let resolved_index_dir =
+                resolve_path(&index_dir.unwrap_or_else(|| app_config.index_dir().to_string()));
+            let searcher = Searcher::new(&resolved_index_dir)?;
+
+            match action {
+                TopicsAction::Build { topics, terms, .. } => {
+                    let model = searcher.cluster_topics(
+                        &TopicClusterer::new()
+                            .with_topics(topics)
+                            .with_label_terms(terms),
+                    );
+                    if model.is_empty() {
+                        println!("No memos to cluster");
+                        return Ok(());
+                    }
+                    IndexBuilder::update_topics(Path::new(&resolved_index_dir), &model)?;
+                    print!("{}", format_topics(searcher.docstore(), &model));
+                    eprintln!(
+                        "\nStored {} topics ({}) in {}",
+                        model.len(),
+                        model.source,
+                        resolved_index_dir
+                    );
+                }
+                TopicsAction::List { .. } => match searcher.topics() {
+                    Some(model) => print!("{}", format_topics(searcher.docstore(), model)),
+                    None => println!("No topics stored; run `digrag topics build` first"),
+                },
+            }
+            Ok(())
+        }
+        Commands::Cache { action } => {
+            let app_config = load_app_config();
+            let path = PersistentSummaryCache::config_path(&app_config)?;
+            let Some(cache) = PersistentSummaryCache::from_config(&app_config)? else {
+                println!("Summary cache is disabled (summary_cache_enabled = false)");
+                return Ok(());
+            };
+            match action {
+                CacheAction::Stats => print!("{}", format_cache_stats(&path, &cache.stats()?)),
+                CacheAction::Clear { expired: true } => {
+                    let deleted = cache.cleanup()?;
+                    println!(
+                        "Deleted {} expired summaries from {}",
+                        deleted,
+                        path.display()
+                    );
+                }
+                CacheAction::Clear { expired: false } => {
+                    let deleted = cache.clear()?;
+                    println!("Deleted {} summaries from {}", deleted, path.display());
+                }
+            }
+            Ok(())
+        }
+        Commands::Timeline {
+            query,
+            index_dir,
+            period,
+            from,
+            to,
+            tag,
+            topic,
+            mode,
+            titles,
+        } => {
+            let app_config = load_app_config();
+            let resolved_index_dir =
+                resolve_path(&index_dir.unwrap_or_else(|| app_config.index_dir().to_string()));
+            let timeline = Timeline::new(Period::parse(&period)?)
+                .with_titles(titles)
+                .with_range(from, to);
+            let mode = mode.unwrap_or_else(|| app_config.default_search_mode().to_string());
+            let config = SearchConfig::new()
+                .with_mode(SearchMode::parse(&mode))
+                .with_tag_filter(tag)
+                .with_topic_filter(topic);
+
+            let searcher = load_searcher(&resolved_index_dir, &app_config)?;
+            let indexes = IndexSet::new(DEFAULT_INDEX_NAME, searcher);
+            let buckets = indexes.select(None)?.timeline(
+                query.as_deref().unwrap_or(""),
+                &config,
+                &timeline,
+            )?;
+
+            print!("{}", format_timeline(&buckets));
+            let total: usize = buckets.iter().map(|b| b.count).sum();
+            eprintln!("\n{} memos per {}", total, timeline.period());
+            Ok(())
+        }
+        Commands::Dedupe {
+            index_dir,
+            threshold,
+            vector_threshold,
+        } => {
+            let app_config = load_app_config();
+            let resolved_index_dir =
+                resolve_path(&index_dir.unwrap_or_else(|| app_config.index_dir().to_string()));
+            let searcher = Searcher::new(&resolved_index_dir)?;
+            let detector = DuplicateDetector::new()
+                .with_threshold(threshold)
+                .with_vector_threshold(vector_threshold);
+
+            let clusters = searcher.find_duplicates(&detector);
+            if clusters.is_empty() {
+                println!("No near-duplicate memos found");
+                return Ok(());
+            }
+            print!(
+                "{}",
+                format_duplicate_clusters(searcher.docstore(), &clusters)
+            );
+            let copies: usize = clusters.iter().map(|c| c.doc_ids.len() - 1).sum();
+            eprintln!(
+                "\n{} groups of near-duplicates ({} redundant memos)",
+                clusters.len(),
+                copies
+            );
+            Ok(())
+        }
+        Commands::SuggestTags {
+            index_dir,
+            neighbors,
+            min_confidence,
+            max_tags,
+            apply,
+        } => {
+            let app_config = load_app_config();
+            let resolved_index_dir =
+                resolve_path(&index_dir.unwrap_or_else(|| app_config.index_dir().to_string()));
+            let searcher = Searcher::new(&resolved_index_dir)?;
+            let suggester = TagSuggester::new()
+                .with_neighbors(neighbors)
+                .with_min_confidence(min_confidence)
+                .with_max_tags(max_tags);
+
+            let suggestions = suggester.suggest_untagged(&searcher)?;
+            if suggestions.is_empty() {
+                println!("No tag suggestions for untagged memos");
+                return Ok(());
+            }
+            print!("{}", format_tag_suggestions(&suggestions));
+
+            if apply {
+                let updated = IndexBuilder::update_inferred_tags(
+                    Path::new(&resolved_index_dir),
+                    &inferred_tag_map(&suggestions),
+                )?;
+                eprintln!(
+                    "\nStored inferred tags for {} memos in {}",
+                    updated, resolved_index_dir
+                );
+            } else {
+                eprintln!("\nRun with --apply to store these as inferred tags");
+            }
+            Ok(())
+        }
+        Commands::Watch {
+            input,
+            output,
+            with_embeddings,
+            interval_ms,
+            debounce_ms,
+            serve,
+            transport,
+            bind,
+        } => {
+            let inputs: Vec<PathBuf> = input.iter().map(|i| resolve_path(i).into()).collect();
+            let output_path = PathBuf::from(resolve_path(&output));
+
+            let builder = if with_embeddings {
+                let api_key = std::env::var("OPENROUTER_API_KEY").map_err(|_| {
+                    anyhow::anyhow!("OPENROUTER_API_KEY environment variable not set. Required for --with-embeddings")
+                })?;
+                IndexBuilder::with_embeddings(api_key)
+            } else {
+                IndexBuilder::new()
+            };
+
+            let mut watcher = IndexWatcher::new(inputs, output_path.clone(), builder)
+                .with_interval(std::time::Duration::from_millis(interval_ms))
+                .with_debounce(std::time::Duration::from_millis(debounce_ms));
+
+            // Bring the index up to date before serving or waiting for changes
+            let touched = watcher.poll();
+            eprintln!(
+                "Watching {} file(s), indexing to {}",
+                touched.len(),
+                output_path.display()
+            );
+            let outcome = watcher.sync(&touched).await;
+            let initial_ok = outcome.is_ok();
+            report_sync(&touched, outcome);
+
+            if !serve {
+                watcher.run(report_sync).await;
+                return Ok(());
+            }
+            if !initial_ok {
+                return Err(anyhow::anyhow!(
+                    "Cannot serve: initial indexing of {} failed",
+                    output_path.display()
+                ));
+            }
+
+            let app_config = load_app_config();
+            let dirs = vec![(DEFAULT_INDEX_NAME.to_string(), output_path)];
+            let indexes = load_indexes(&dirs, &app_config)?;
+            tokio::spawn(watcher.run(report_sync));
+            serve_mcp(indexes, dirs, app_config, &transport, &bind).await
+        }
+        Commands::Search {
+            query,
+            index_dir,
+            top_k,
+            mode,
+            tag,
+            topic,
+            extraction,
+            mmr_lambda,
+            collapse_duplicates,
+            facets,
+            digest,
+            offset,
+            cursor,
+        } => {
+            // Load config and apply CLI overrides
+            let app_config = load_app_config();
+
+            let resolved_index_dir =
+                resolve_path(&index_dir.unwrap_or_else(|| app_config.index_dir().to_string()));
+            let effective_top_k = top_k.unwrap_or_else(|| app_config.default_top_k());
+            let effective_mode =
+                mode.unwrap_or_else(|| app_config.default_search_mode().to_string());
+            let effective_extraction =
+                extraction.unwrap_or_else(|| app_config.extraction_mode().to_string());
+
+            let search_mode = match effective_mode.as_str() {
+                "bm25" => SearchMode::Bm25,
+                "semantic" => SearchMode::Semantic,
+                "hybrid" => SearchMode::Hybrid,
+                _ => {
+                    eprintln!("Unknown mode '{}', using bm25", effective_mode);
+                    SearchMode::Bm25
+                }
+            };
+
+            // Set up extraction strategy based on effective_extraction
+            let extraction_strategy = match effective_extraction.as_str() {
+                "entry" => ExtractionStrategy::ChangelogEntry,
+                "full" => ExtractionStrategy::Full,
                 "snippet" => ExtractionStrategy::Head(150),
                 _ => {
                     eprintln!(
@@ -767,49 +3029,173 @@ async fn main() -> Result<()> {
                 max_lines: None,
                 max_sections: None,
             };
+            let digest_extractor =
+                digest_extractor(&extraction_strategy, app_config.extraction_max_chars());
             let extractor = ContentExtractor::new(extraction_strategy, truncation);
 
-            // Create searcher with embedding client if API key is available
-            let searcher = if let Some(api_key) = app_config.openrouter_api_key() {
-                tracing::info!("Using OpenRouter API key from config for semantic search");
-                let embedding_client = digrag::embedding::OpenRouterEmbedding::new(api_key);
-                Searcher::with_embedding_client(&resolved_index_dir, embedding_client)?
-            } else if let Ok(api_key) = std::env::var("OPENROUTER_API_KEY") {
-                tracing::info!("Using OPENROUTER_API_KEY env var for semantic search");
-                let embedding_client = digrag::embedding::OpenRouterEmbedding::new(api_key);
-                Searcher::with_embedding_client(&resolved_index_dir, embedding_client)?
-            } else {
-                Searcher::new(&resolved_index_dir)?
-            };
+            let searcher = load_searcher(&resolved_index_dir, &app_config)?;
             let config = SearchConfig::new()
                 .with_mode(search_mode)
                 .with_top_k(effective_top_k)
-                .with_tag_filter(tag);
+                .with_tag_filter(tag)
+                .with_topic_filter(topic)
+                .with_mmr_lambda(mmr_lambda)
+                .with_collapse_duplicates(collapse_duplicates)
+                .with_offset(offset)
+                .with_cursor(cursor);
 
-            let results = searcher.search(&query, &config)?;
+            let (page, facets) = if facets {
+                let (page, facets) = searcher.search_page_with_facets(&query, &config)?;
+                (page, Some(facets))
+            } else {
+                (searcher.search_page(&query, &config)?, None)
+            };
 
-            if results.is_empty() {
+            if page.items.is_empty() {
                 println!("No results found for '{}'", query);
             } else {
-                println!("Found {} results for '{}':\n", results.len(), query);
-                for (i, result) in results.iter().enumerate() {
-                    println!("{}. [score: {:.4}] {}", i + 1, result.score, result.doc_id);
-                    if let Some(doc) = searcher.docstore().get(&result.doc_id) {
-                        println!("   Title: {}", doc.title());
-                        println!("   Date: {}", doc.date().format("%Y-%m-%d"));
-                        println!("   Tags: {:?}", doc.tags());
-                        let extracted = extractor.extract(&doc.text);
-                        println!("   {}", extracted.text);
-                        if extracted.truncated {
-                            println!(
-                                "   [truncated: {} of {} chars shown]",
-                                extracted.stats.extracted_chars, extracted.stats.total_chars
-                            );
+                println!(
+                    "Found {} results for '{}'{}:\n",
+                    page.total_label(),
+                    query,
+                    page_range(&page)
+                );
+                if let Some(facets) = &facets {
+                    println!("{}", format_facets(facets));
+                }
+                print_search_results(&searcher, &page, &extractor);
+                if digest {
+                    let sources: Vec<DigestSource> = page
+                        .items
+                        .iter()
+                        .filter_map(|result| searcher.docstore().get(&result.doc_id))
+                        .map(|doc| digest_source(doc, &digest_extractor))
+                        .collect();
+                    let store = open_summary_store(&app_config);
+                    let summary = summarizer_from_config(
+                        &app_config,
+                        &SummaryOptions::default(),
+                        store.as_ref(),
+                    )?
+                    .digest(&sources)
+                    .await;
+                    print!("{}", format_digest(&summary));
+                }
+            }
+            Ok(())
+        }
+        Commands::Ask {
+            question,
+            index_dir,
+            top_k,
+            mode,
+            tag,
+            topic,
+            max_context_tokens,
+        } => {
+            let app_config = load_app_config();
+            let resolved_index_dir =
+                resolve_path(&index_dir.unwrap_or_else(|| app_config.index_dir().to_string()));
+            let answerer =
+                QuestionAnswerer::from_config(&app_config)?.with_context_tokens(max_context_tokens);
+            let mode = mode.unwrap_or_else(|| app_config.default_search_mode().to_string());
+            let config = SearchConfig::new()
+                .with_mode(SearchMode::parse(&mode))
+                .with_top_k(top_k)
+                .with_tag_filter(tag)
+                .with_topic_filter(topic);
+
+            let searcher = load_searcher(&resolved_index_dir, &app_config)?;
+            let indexes = IndexSet::new(DEFAULT_INDEX_NAME, searcher);
+            let passages = retrieve_passages(
+                &indexes,
+                &indexes.select(None)?,
+                &answerer,
+                &question,
+                &config,
+            )?;
+            let answer = answerer.answer(&question, passages).await?;
+            print!("{}", format_answer(&answer));
+            Ok(())
+        }
+        Commands::Show {
+            ids,
+            index_dir,
+            extraction,
+            max_chars,
+            neighbors,
+        } => {
+            let app_config = load_app_config();
+
+            let resolved_index_dir =
+                resolve_path(&index_dir.unwrap_or_else(|| app_config.index_dir().to_string()));
+            let searcher = Searcher::new(&resolved_index_dir)?;
+
+            let truncation = TruncationConfig {
+                max_chars: Some(max_chars.unwrap_or_else(|| app_config.extraction_max_chars())),
+                max_lines: None,
+                max_sections: None,
+            };
+            let extractor =
+                ContentExtractor::new(ExtractionStrategy::from_mode(&extraction), truncation);
+
+            let mut found = 0;
+            for id in &ids {
+                match searcher.docstore().get(id) {
+                    Some(doc) => {
+                        if found > 0 {
+                            println!("---\n");
                         }
+                        let (_, text) =
+                            describe_memo(searcher.docstore(), doc, &extractor, neighbors);
+                        println!("{}", text);
+                        found += 1;
                     }
-                    println!();
+                    None => eprintln!("Memo not found: {}", id),
                 }
             }
+
+            if found == 0 {
+                anyhow::bail!("None of the requested memos were found");
+            }
+            Ok(())
+        }
+        Commands::Similar {
+            doc_id,
+            index_dir,
+            top_k,
+            mode,
+            tag,
+        } => {
+            let app_config = load_app_config();
+
+            let resolved_index_dir =
+                resolve_path(&index_dir.unwrap_or_else(|| app_config.index_dir().to_string()));
+            let effective_top_k = top_k.unwrap_or_else(|| app_config.default_top_k());
+            let effective_mode =
+                mode.unwrap_or_else(|| app_config.default_search_mode().to_string());
+
+            // Stored vectors are used directly, so no embedding client is needed
+            let searcher = Searcher::new(&resolved_index_dir)?;
+            let config = SearchConfig::new()
+                .with_mode(SearchMode::parse(&effective_mode))
+                .with_top_k(effective_top_k)
+                .with_tag_filter(tag);
+
+            let page = searcher.similar_page(&doc_id, &config)?;
+            let extractor =
+                ContentExtractor::new(ExtractionStrategy::Head(150), TruncationConfig::default());
+
+            if page.items.is_empty() {
+                println!("No related memos found for '{}'", doc_id);
+            } else {
+                println!(
+                    "Found {} memos related to '{}':\n",
+                    page.total_label(),
+                    doc_id
+                );
+                print_search_results(&searcher, &page, &extractor);
+            }
             Ok(())
         }
     }
@@ -826,6 +3212,42 @@ mod tests {
         assert!(cli.is_ok());
     }
 
+    #[test]
+    fn test_cli_serve_http() {
+        let cli = Cli::try_parse_from([
+            "digrag",
+            "serve",
+            "--transport",
+            "http",
+            "--bind",
+            "0.0.0.0:9000",
+        ]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Serve {
+                transport, bind, ..
+            } = parsed.command
+            {
+                assert_eq!(transport, "http");
+                assert_eq!(bind, "0.0.0.0:9000");
+            }
+        }
+
+        assert!(Cli::try_parse_from(["digrag", "serve", "--transport", "tcp"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_http_command() {
+        let cli = Cli::try_parse_from(["digrag", "http", "--bind", "127.0.0.1:9999"]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Http { index_dir, bind } = parsed.command {
+                assert!(index_dir.is_none());
+                assert_eq!(bind, "127.0.0.1:9999");
+            }
+        }
+    }
+
     #[test]
     fn test_cli_build_command() {
         let cli = Cli::try_parse_from([
@@ -861,12 +3283,668 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_add_command() {
+        let cli = Cli::try_parse_from([
+            "digrag", "add", "New idea", "--text", "body", "-t", "idea", "-t", "memo",
+        ]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Add {
+                title, text, tags, ..
+            } = parsed.command
+            {
+                assert_eq!(title, "New idea");
+                assert_eq!(text.as_deref(), Some("body"));
+                assert_eq!(tags, vec!["idea", "memo"]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_memo_params() {
+        let params: AddMemoParams =
+            serde_json::from_str(r#"{"title": "Title", "text": "Body"}"#).unwrap();
+        assert!(params.tags.is_empty());
+        assert!(serde_json::from_str::<AddMemoParams>(r#"{"title": "Title"}"#).is_err());
+    }
+
+    #[test]
+    fn test_memo_writer_requires_allow_write() {
+        let dirs = vec![("default".to_string(), PathBuf::from(".rag"))];
+        assert!(
+            memo_writer(&dirs, &AppConfig::default(), Some("changelog.md"))
+                .unwrap()
+                .is_none()
+        );
+
+        let config: AppConfig = toml::from_str("allow_write = true").unwrap();
+        assert!(memo_writer(&dirs, &config, None).is_err());
+        let writer = memo_writer(&dirs, &config, Some("changelog.md"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(writer.index_name(), "default");
+    }
+
+    #[test]
+    fn test_cli_tags_command() {
+        let cli = Cli::try_parse_from([
+            "digrag",
+            "tags",
+            "merge",
+            "memos",
+            "memo-s",
+            "--into",
+            "memo",
+            "-i",
+            "log.md",
+            "--dry-run",
+        ]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Tags {
+                action: TagsAction::Merge { from, into, target },
+            } = parsed.command
+            {
+                assert_eq!(from, vec!["memos", "memo-s"]);
+                assert_eq!(into, "memo");
+                assert_eq!(target.input, vec!["log.md"]);
+                assert!(target.dry_run);
+            }
+        }
+
+        assert!(Cli::try_parse_from(["digrag", "tags", "rename", "a", "b"]).is_ok());
+        assert!(Cli::try_parse_from(["digrag", "tags", "delete", "tmp"]).is_ok());
+        assert!(Cli::try_parse_from(["digrag", "tags", "merge", "--into", "memo"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_suggest_tags_command() {
+        let cli = Cli::try_parse_from([
+            "digrag",
+            "suggest-tags",
+            "-k",
+            "3",
+            "--min-confidence",
+            "0.5",
+            "--apply",
+        ]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::SuggestTags {
+                neighbors,
+                min_confidence,
+                max_tags,
+                apply,
+                ..
+            } = parsed.command
+            {
+                assert_eq!(neighbors, 3);
+                assert!((min_confidence - 0.5).abs() < 1e-6);
+                assert_eq!(max_tags, DEFAULT_MAX_SUGGESTED_TAGS);
+                assert!(apply);
+            }
+        }
+
+        let cli = Cli::try_parse_from(["digrag", "build", "-i", "log.md", "--suggest-tags"]);
+        assert!(matches!(
+            cli.map(|c| c.command),
+            Ok(Commands::Build {
+                suggest_tags: true,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_cli_dedupe_command() {
+        let cli = Cli::try_parse_from([
+            "digrag",
+            "dedupe",
+            "--threshold",
+            "0.9",
+            "--vector-threshold",
+            "0.95",
+        ]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Dedupe {
+                threshold,
+                vector_threshold,
+                ..
+            } = parsed.command
+            {
+                assert!((threshold - 0.9).abs() < 1e-6);
+                assert_eq!(vector_threshold, Some(0.95));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cli_topics_command() {
+        let cli = Cli::try_parse_from(["digrag", "topics", "build", "-k", "4"]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Topics {
+                action: TopicsAction::Build { topics, terms, .. },
+            } = parsed.command
+            {
+                assert_eq!(topics, Some(4));
+                assert_eq!(terms, DEFAULT_TOPIC_TERMS);
+            }
+        }
+
+        let cli = Cli::try_parse_from(["digrag", "search", "deploy", "--topic", "2"]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Search { topic, .. } = parsed.command {
+                assert_eq!(topic, Some(2));
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_topics() {
+        use chrono::{TimeZone, Utc};
+        use digrag::index::Topic;
+
+        let mut docstore = Docstore::new();
+        for (id, day) in [("old", 1), ("new", 2)] {
+            docstore.add(Document::with_id(
+                id.to_string(),
+                format!("{} memo", id),
+                Utc.with_ymd_and_hms(2025, 1, day, 10, 0, 0).unwrap(),
+                vec![],
+                String::new(),
+            ));
+        }
+        let model = TopicModel::new(
+            "tfidf",
+            vec![Topic {
+                id: 1,
+                label: "deploy, server".to_string(),
+                terms: vec!["deploy".to_string(), "server".to_string()],
+                doc_ids: vec!["old".to_string(), "new".to_string()],
+            }],
+        );
+        assert_eq!(
+            format_topics(&docstore, &model),
+            "1. deploy, server (2 memos)\n   - new memo\n   - old memo\n"
+        );
+    }
+
+    #[test]
+    fn test_cli_timeline_command() {
+        let cli = Cli::try_parse_from([
+            "digrag",
+            "timeline",
+            "deploy",
+            "--period",
+            "month",
+            "--from",
+            "2025-07-01",
+            "--to",
+            "2025-09-30",
+        ]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Timeline {
+                query,
+                period,
+                from,
+                titles,
+                ..
+            } = parsed.command
+            {
+                assert_eq!(query.as_deref(), Some("deploy"));
+                assert_eq!(period, "month");
+                assert_eq!(from, NaiveDate::from_ymd_opt(2025, 7, 1));
+                assert_eq!(titles, DEFAULT_TIMELINE_TITLES);
+            }
+        }
+        assert!(Cli::try_parse_from(["digrag", "timeline", "--from", "July"]).is_err());
+    }
+
+    #[test]
+    fn test_format_timeline() {
+        use digrag::index::TimelineMemo;
+
+        let bucket = |label: &str, count: usize, memos: Vec<TimelineMemo>| TimelineBucket {
+            label: label.to_string(),
+            start: String::new(),
+            count,
+            tags: [("rust".to_string(), count)]
+                .into_iter()
+                .filter(|(_, count)| *count > 0)
+                .collect(),
+            memos,
+        };
+        let buckets = vec![
+            bucket(
+                "2025-W01",
+                2,
+                vec![TimelineMemo {
+                    doc_id: "doc1".to_string(),
+                    title: "Deploy".to_string(),
+                    date: "2025-01-02".to_string(),
+                }],
+            ),
+            bucket("2025-W02", 0, vec![]),
+        ];
+        assert_eq!(
+            format_timeline(&buckets),
+            format!(
+                "2025-W01     2 {}\n    tags: rust 2\n    - Deploy (2025-01-02, doc1)\n2025-W02     0\n",
+                "#".repeat(30)
+            )
+        );
+        assert_eq!(format_timeline(&[]), "No memos found\n");
+    }
+
+    #[test]
+    fn test_format_facets() {
+        let count = |value: &str, count: usize| FacetCount {
+            value: value.to_string(),
+            count,
+        };
+        let facets = Facets {
+            tags: vec![count("rust", 3), count("cli", 1)],
+            categories: vec![],
+            months: (1..=12)
+                .map(|m| count(&format!("2025-{:02}", m), 1))
+                .collect(),
+        };
+        assert_eq!(
+            format_facets(&facets),
+            "Facets:\n  Tags: rust (3), cli (1)\n  Months: 2025-12 (1), 2025-11 (1), \
+             2025-10 (1), 2025-09 (1), 2025-08 (1), 2025-07 (1), 2025-06 (1), \
+             2025-05 (1), 2025-04 (1), 2025-03 (1)\n"
+        );
+
+        let cli = Cli::try_parse_from(["digrag", "search", "deploy", "--facets"]).unwrap();
+        assert!(matches!(cli.command, Commands::Search { facets: true, .. }));
+    }
+
+    #[test]
+    fn test_cli_ask_command() {
+        let cli = Cli::try_parse_from(["digrag", "ask", "How do we roll back?", "-k", "5"]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Ask {
+                question,
+                top_k,
+                max_context_tokens,
+                ..
+            } = parsed.command
+            {
+                assert_eq!(question, "How do we roll back?");
+                assert_eq!(top_k, 5);
+                assert_eq!(max_context_tokens, DEFAULT_CONTEXT_TOKENS);
+            }
+        }
+        assert!(Cli::try_parse_from(["digrag", "ask"]).is_err());
+    }
+
+    #[test]
+    fn test_format_answer() {
+        let passage = |number: usize, doc_id: &str| Passage {
+            number,
+            doc_id: doc_id.to_string(),
+            title: format!("Memo {}", doc_id),
+            date: "2025-07-01".to_string(),
+            index: None,
+            text: String::new(),
+            truncated: false,
+        };
+        let mut answer = Answer {
+            question: "How?".to_string(),
+            answer: "Like this [2].\n".to_string(),
+            citations: vec![passage(2, "b")],
+            sources: vec![passage(1, "a"), passage(2, "b")],
+            model: Some("model".to_string()),
+            usage: None,
+        };
+        assert_eq!(
+            format_answer(&answer),
+            "Like this [2].\n\nSources:\n[2] Memo b (2025-07-01, b)\n"
+        );
+
+        answer.citations.clear();
+        answer.sources[0].index = Some("wiki".to_string());
+        assert!(
+            format_answer(&answer).contains("Retrieved memos:\n[1] Memo a (2025-07-01, wiki/a)\n")
+        );
+    }
+
+    #[test]
+    fn test_format_duplicate_clusters() {
+        use chrono::{TimeZone, Utc};
+
+        let mut docstore = Docstore::new();
+        docstore.add(Document::with_id(
+            "doc1".to_string(),
+            "Deploy".to_string(),
+            Utc.with_ymd_and_hms(2025, 1, 15, 10, 0, 0).unwrap(),
+            vec![],
+            String::new(),
+        ));
+        let clusters = vec![DuplicateCluster {
+            doc_ids: vec!["doc1".to_string(), "gone".to_string()],
+            similarity: 0.875,
+        }];
+        assert_eq!(
+            format_duplicate_clusters(&docstore, &clusters),
+            "Group 1 (similarity >= 0.88):\n  doc1  2025-01-15  Deploy\n  gone\n"
+        );
+    }
+
+    #[test]
+    fn test_format_tag_suggestions() {
+        use digrag::search::SuggestedTag;
+
+        let suggestions = vec![TagSuggestion {
+            doc_id: "doc1".to_string(),
+            title: "Untagged".to_string(),
+            tags: vec![SuggestedTag {
+                tag: "rust".to_string(),
+                confidence: 0.75,
+            }],
+        }];
+        assert_eq!(
+            format_tag_suggestions(&suggestions),
+            "Untagged (doc1)\n  rust 0.75\n"
+        );
+        assert_eq!(
+            inferred_tag_map(&suggestions)["doc1"],
+            vec!["rust".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_cli_watch_command() {
+        let cli = Cli::try_parse_from(["digrag", "watch", "-i", "notes", "-i", "log.md"]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Watch {
+                input,
+                output,
+                serve,
+                debounce_ms,
+                ..
+            } = parsed.command
+            {
+                assert_eq!(input, vec!["notes", "log.md"]);
+                assert_eq!(output, ".rag");
+                assert!(!serve);
+                assert_eq!(debounce_ms, 500);
+            }
+        }
+
+        let cli = Cli::try_parse_from([
+            "digrag",
+            "watch",
+            "-i",
+            "notes",
+            "--serve",
+            "--transport",
+            "http",
+        ]);
+        assert!(cli.is_ok());
+
+        // At least one input is required
+        assert!(Cli::try_parse_from(["digrag", "watch"]).is_err());
+    }
+
     #[test]
     fn test_cli_search_command() {
         let cli = Cli::try_parse_from(["digrag", "search", "test query", "--top-k", "5"]);
         assert!(cli.is_ok());
     }
 
+    #[test]
+    fn test_cli_search_pagination() {
+        let cli = Cli::try_parse_from(["digrag", "search", "q", "--offset", "10"]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Search { offset, cursor, .. } = parsed.command {
+                assert_eq!(offset, 10);
+                assert!(cursor.is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn test_cli_search_digest() {
+        let cli = Cli::try_parse_from(["digrag", "search", "q", "--digest"]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Search { digest, .. } = parsed.command {
+                assert!(digest);
+            }
+        }
+
+        let params: QueryMemosParams =
+            serde_json::from_str(r#"{"query":"q","summary_scope":"combined"}"#).unwrap();
+        assert_eq!(
+            SummaryScope::parse(params.summary_scope.as_deref()),
+            SummaryScope::Combined
+        );
+    }
+
+    #[test]
+    fn test_query_memos_params_with_summary_options() {
+        let params: QueryMemosParams = serde_json::from_str(r#"{"query":"q"}"#).unwrap();
+        assert!(params.use_llm_summary.is_none());
+
+        let params: QueryMemosParams = serde_json::from_str(
+            r#"{"query":"q","use_llm_summary":true,"summary_model":"m","summary_max_tokens":300,"summary_temperature":0.5}"#,
+        )
+        .unwrap();
+        assert_eq!(params.use_llm_summary, Some(true));
+        assert_eq!(params.summary_model.as_deref(), Some("m"));
+        assert_eq!(params.summary_max_tokens, Some(300));
+        assert_eq!(params.summary_temperature, Some(0.5));
+    }
+
+    #[test]
+    fn test_summary_label() {
+        use digrag::extract::summarizer::SummaryUsage;
+        use digrag::extract::ContentStats;
+
+        let mut summary = Summary {
+            text: "- point".to_string(),
+            method: "rule-based".to_string(),
+            model: None,
+            stats: ContentStats {
+                total_chars: 7,
+                total_lines: 1,
+                extracted_chars: 7,
+            },
+            usage: None,
+        };
+        assert_eq!(summary_label(&summary), "rule-based");
+
+        summary.method = "llm".to_string();
+        summary.model = Some("test-model".to_string());
+        assert_eq!(summary_label(&summary), "llm, test-model, cached");
+
+        summary.usage = Some(SummaryUsage {
+            prompt_tokens: 100,
+            completion_tokens: 50,
+            total_tokens: 150,
+            model: "test-model".to_string(),
+        });
+        assert_eq!(
+            summary_label(&summary),
+            "llm, test-model, 150 tokens (100 prompt + 50 completion)"
+        );
+        assert!(format_digest(&summary).starts_with("## Digest (llm, test-model, 150 tokens"));
+    }
+
+    #[test]
+    fn test_digest_extractor_reads_past_snippets() {
+        let extractor = digest_extractor(&ExtractionStrategy::Head(5), 8);
+        let extracted = extractor.extract("Deployed the new build");
+        assert_eq!(extracted.text, "Deployed");
+        assert!(extracted.truncated);
+    }
+
+    #[test]
+    fn test_cli_cache_command() {
+        let cli = Cli::try_parse_from(["digrag", "cache", "clear", "--expired"]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            assert!(matches!(
+                parsed.command,
+                Commands::Cache {
+                    action: CacheAction::Clear { expired: true }
+                }
+            ));
+        }
+        assert!(Cli::try_parse_from(["digrag", "cache", "stats"]).is_ok());
+    }
+
+    #[test]
+    fn test_format_cache_stats() {
+        use chrono::{TimeZone, Utc};
+
+        let stats = PersistentCacheStats {
+            entries: 3,
+            expired: 1,
+            text_chars: 420,
+            tokens: 900,
+            oldest: Some(Utc.with_ymd_and_hms(2025, 7, 1, 9, 0, 0).unwrap()),
+            newest: Some(Utc.with_ymd_and_hms(2025, 7, 3, 18, 30, 0).unwrap()),
+            models: vec![("model-a".to_string(), 2), ("model-b".to_string(), 1)],
+        };
+        assert_eq!(
+            format_cache_stats(Path::new("/tmp/summaries.db"), &stats),
+            "Summary cache: /tmp/summaries.db\n  Entries: 3 (1 expired)\n  \
+             Cached text: 420 chars, 900 tokens spent\n  \
+             Created: 2025-07-01 09:00 .. 2025-07-03 18:30\n  \
+             Models: model-a (2), model-b (1)\n"
+        );
+    }
+
+    #[test]
+    fn test_get_recent_memos_params_pagination() {
+        let params: GetRecentMemosParams =
+            serde_json::from_str(r#"{"limit":5,"cursor":"abc"}"#).unwrap();
+        assert_eq!(params.limit, 5);
+        assert_eq!(params.offset, 0);
+        assert_eq!(params.cursor, Some("abc".to_string()));
+    }
+
+    #[test]
+    fn test_params_format() {
+        let params: ListTagsParams = serde_json::from_str("{}").unwrap();
+        assert!(params.format.is_none());
+
+        let params: QueryMemosParams =
+            serde_json::from_str(r#"{"query":"test","format":"json"}"#).unwrap();
+        assert_eq!(params.format, Some("json".to_string()));
+    }
+
+    #[test]
+    fn test_tool_response_formats() {
+        let response = ListTagsResponse {
+            tags: vec![TagInfo {
+                name: "memo".to_string(),
+                count: 2,
+                inferred_count: 1,
+            }],
+        };
+
+        let text = tool_response(ResponseFormat::Text, "text".to_string(), &response).unwrap();
+        assert_eq!(text.content.len(), 1);
+
+        let both = tool_response(ResponseFormat::Both, "text".to_string(), &response).unwrap();
+        assert_eq!(both.content.len(), 2);
+        let json: serde_json::Value =
+            serde_json::from_str(&both.content[1].as_text().unwrap().text).unwrap();
+        assert_eq!(json["tags"][0]["name"], "memo");
+        assert_eq!(json["tags"][0]["count"], 2);
+    }
+
+    #[test]
+    fn test_cli_show_command() {
+        let cli = Cli::try_parse_from(["digrag", "show", "a", "b", "--neighbors", "2"]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Show {
+                ids,
+                extraction,
+                neighbors,
+                ..
+            } = parsed.command
+            {
+                assert_eq!(ids, vec!["a", "b"]);
+                assert_eq!(extraction, "full");
+                assert_eq!(neighbors, 2);
+            }
+        }
+
+        assert!(Cli::try_parse_from(["digrag", "show"]).is_err());
+    }
+
+    #[test]
+    fn test_describe_memo_with_neighbors() {
+        use chrono::{TimeZone, Utc};
+
+        let mut docstore = Docstore::new();
+        for (i, title) in ["First", "Second", "Third"].iter().enumerate() {
+            docstore.add(Document::with_id(
+                format!("doc{}", i),
+                title.to_string(),
+                Utc.with_ymd_and_hms(2025, 1, i as u32 + 1, 0, 0, 0)
+                    .unwrap(),
+                vec![],
+                format!("{} body", title),
+            ));
+        }
+        let extractor =
+            ContentExtractor::new(ExtractionStrategy::Full, TruncationConfig::default());
+
+        let doc = docstore.get("doc1").unwrap();
+        let (detail, text) = describe_memo(&docstore, doc, &extractor, 1);
+
+        assert_eq!(detail.memo.id, "doc1");
+        assert_eq!(
+            detail
+                .memo
+                .extracted
+                .as_ref()
+                .unwrap()
+                .raw_content
+                .as_deref(),
+            Some("Second body")
+        );
+        assert_eq!(detail.previous[0].id, "doc0");
+        assert_eq!(detail.next[0].id, "doc2");
+        assert!(text.contains("Previous:\n- 2025-01-01 First (doc0)"));
+        assert!(text.contains("Next:\n- 2025-01-03 Third (doc2)"));
+    }
+
+    #[test]
+    fn test_get_memo_params() {
+        let params: GetMemoParams = serde_json::from_str(r#"{"ids": ["doc1"]}"#).unwrap();
+        assert_eq!(params.ids, vec!["doc1"]);
+        assert_eq!(params.neighbors, 0);
+        assert!(params.extraction_mode.is_none());
+    }
+
+    #[test]
+    fn test_cli_similar_command() {
+        let cli = Cli::try_parse_from(["digrag", "similar", "abc123", "--top-k", "5"]);
+        assert!(cli.is_ok());
+        if let Ok(parsed) = cli {
+            if let Commands::Similar { doc_id, top_k, .. } = parsed.command {
+                assert_eq!(doc_id, "abc123");
+                assert_eq!(top_k, Some(5));
+            }
+        }
+    }
+
     #[test]
     fn test_query_memos_params_empty() {
         // Test that empty JSON object can be deserialized (fixes "missing field query" error)
@@ -899,4 +3977,120 @@ mod tests {
             serde_json::from_str(r#"{"query":"test","extraction_mode":"entry"}"#).unwrap();
         assert_eq!(params.extraction_mode, Some("entry".to_string()));
     }
+
+    #[test]
+    fn test_query_memos_params_with_mmr_lambda() {
+        let params: QueryMemosParams =
+            serde_json::from_str(r#"{"query":"test","mmr_lambda":0.5}"#).unwrap();
+        assert_eq!(params.mmr_lambda, Some(0.5));
+    }
+
+    #[test]
+    fn test_query_memos_params_with_index() {
+        let params: QueryMemosParams = serde_json::from_str(r#"{"query":"test"}"#).unwrap();
+        assert!(params.index.is_none());
+
+        let params: QueryMemosParams =
+            serde_json::from_str(r#"{"query":"test","index":["wiki","notes"]}"#).unwrap();
+        assert_eq!(
+            params.index,
+            Some(IndexSelector::Many(vec![
+                "wiki".to_string(),
+                "notes".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_query_memos_params_with_summary_prompt() {
+        let params: QueryMemosParams =
+            serde_json::from_str(r#"{"query":"test","summary_prompt":"brief"}"#).unwrap();
+        assert_eq!(params.summary_prompt, Some("brief".to_string()));
+
+        let templates = PromptTemplates::from_config(&AppConfig::default());
+        assert!(templates.get(params.summary_prompt.as_deref()).is_err());
+        assert_eq!(templates.get(None).unwrap().name(), "default");
+    }
+
+    #[test]
+    fn test_summary_context() {
+        use chrono::{TimeZone, Utc};
+
+        let doc = Document::with_id(
+            "id".to_string(),
+            "Deploy".to_string(),
+            Utc.with_ymd_and_hms(2025, 1, 15, 0, 0, 0).unwrap(),
+            vec!["ops".to_string()],
+            "text".to_string(),
+        );
+        let context = summary_context(&doc, "rollback");
+        assert_eq!(context.title, "Deploy");
+        assert_eq!(context.date, "2025-01-15");
+        assert_eq!(context.tags, vec!["ops".to_string()]);
+        assert_eq!(context.query.as_deref(), Some("rollback"));
+    }
+
+    #[test]
+    fn test_index_built_note() {
+        let mut timestamps = BTreeMap::new();
+        assert_eq!(index_built_note(&timestamps), "");
+
+        timestamps.insert("wiki".to_string(), "2025-01-02T00:00:00+00:00".to_string());
+        assert_eq!(
+            index_built_note(&timestamps),
+            "Index built: 2025-01-02T00:00:00+00:00\n"
+        );
+
+        timestamps.insert(
+            "changelog".to_string(),
+            "2025-01-01T00:00:00+00:00".to_string(),
+        );
+        assert_eq!(
+            index_built_note(&timestamps),
+            "Index built: changelog 2025-01-01T00:00:00+00:00, wiki 2025-01-02T00:00:00+00:00\n"
+        );
+    }
+
+    #[test]
+    fn test_load_indexes() {
+        let changelog = tempfile::tempdir().unwrap();
+        let wiki = tempfile::tempdir().unwrap();
+        let config: AppConfig = toml::from_str(&format!(
+            "indexes = [{{ name = \"changelog\", path = {:?} }}, {{ name = \"wiki\", path = {:?} }}]",
+            changelog.path(),
+            wiki.path()
+        ))
+        .unwrap();
+
+        let indexes = load_indexes(&index_dirs(None, &config), &config).unwrap();
+        assert_eq!(indexes.names(), vec!["changelog", "wiki"]);
+
+        // An explicit index directory overrides the configured indexes
+        let dirs = index_dirs(Some(wiki.path().to_str().unwrap()), &config);
+        assert_eq!(
+            dirs,
+            vec![(DEFAULT_INDEX_NAME.to_string(), wiki.path().to_path_buf())]
+        );
+        let single = load_indexes(&dirs, &config).unwrap();
+        assert_eq!(single.names(), vec![DEFAULT_INDEX_NAME]);
+    }
+
+    #[test]
+    fn test_block_on_without_runtime() {
+        assert_eq!(block_on(async { 42 }).unwrap(), 42);
+    }
+
+    #[test]
+    fn test_http_requires_single_index() {
+        let config: AppConfig = toml::from_str(
+            "indexes = [{ name = \"changelog\", path = \"a\" }, { name = \"wiki\", path = \"b\" }]",
+        )
+        .unwrap();
+        let err = single_index_dir(index_dirs(None, &config)).unwrap_err();
+        assert!(err.to_string().contains("--index-dir"));
+
+        let (name, dir) = single_index_dir(index_dirs(Some("/tmp/wiki"), &config)).unwrap();
+        assert_eq!(name, DEFAULT_INDEX_NAME);
+        assert_eq!(dir, PathBuf::from("/tmp/wiki"));
+    }
 }
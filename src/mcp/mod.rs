@@ -1,11 +1,26 @@
 //! MCP server module
 //!
-//! This module provides types for the MCP server.
+//! This module provides types, resource addressing, prompt templates and the
+//! HTTP/SSE transport for the MCP server.
 //! The actual MCP server implementation using rmcp is in main.rs.
 
+mod prompts;
+mod resources;
 mod server;
+mod sse;
 
+pub use prompts::{
+    list_prompts, BuiltPrompt, PromptArgumentInfo, PromptBuilder, PromptInfo, PROMPT_MAX_MEMOS,
+    PROMPT_MEMO_CHARS,
+};
+pub use resources::{
+    list_resource_templates, list_resources, MemoResource, ResourceInfo, ResourceTemplateInfo,
+    RECENT_RESOURCE_LIMIT, RESOURCE_MIME_TYPE, RESOURCE_SCHEME,
+};
 pub use server::{
-    GetRecentMemosRequest, GetRecentMemosResponse, ListTagsResponse, MemoResult,
-    QueryMemosRequest, QueryMemosResponse, TagInfo,
+    AddMemoResponse, DigestResult, ErrorResponse, GetMemoRequest, GetMemoResponse,
+    GetRecentMemosRequest, GetRecentMemosResponse, HealthResponse, ListTagsResponse,
+    ListTopicsResponse, MemoDetail, MemoResult, QueryMemosRequest, QueryMemosResponse,
+    RelatedMemosResponse, ResponseFormat, SummaryScope, TagInfo, TimelineResponse, TopicInfo,
 };
+pub use sse::{check_bearer_token, require_bearer, serve_sse, MESSAGE_PATH, SSE_PATH};
@@ -0,0 +1,499 @@
+//! MCP prompt templates
+//!
+//! Prompts for common memo workflows. Each prompt is filled server-side with
+//! the relevant memos, so the client receives a ready-to-send message.
+//! Every prompt accepts an `index` argument naming the index to read memos
+//! from (default: the first declared index).
+//! The rmcp handlers that serve these prompts are in main.rs.
+
+use crate::config::{SearchConfig, SearchMode};
+use crate::extract::{ContentExtractor, ExtractionStrategy, TruncationConfig};
+use crate::loader::Document;
+use crate::search::Searcher;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::HashMap;
+
+/// Maximum number of memos included in a prompt
+pub const PROMPT_MAX_MEMOS: usize = 20;
+
+/// Maximum characters of each memo included in a prompt
+pub const PROMPT_MEMO_CHARS: usize = 2000;
+
+/// Number of search results considered before date filtering
+const PROMPT_SEARCH_DEPTH: usize = 200;
+
+/// Days covered by a weekly report when no start date is given
+const WEEKLY_REPORT_DAYS: i64 = 7;
+
+/// Prompt argument description
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptArgumentInfo {
+    /// Argument name
+    pub name: &'static str,
+    /// Argument description
+    pub description: &'static str,
+    /// Whether the argument is required
+    pub required: bool,
+}
+
+/// Prompt description
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptInfo {
+    /// Prompt name
+    pub name: &'static str,
+    /// Prompt description
+    pub description: &'static str,
+    /// Prompt arguments
+    pub arguments: Vec<PromptArgumentInfo>,
+}
+
+/// A prompt filled with memo context
+#[derive(Debug, Clone)]
+pub struct BuiltPrompt {
+    /// Description of what was gathered
+    pub description: String,
+    /// User message text
+    pub text: String,
+}
+
+/// List the available prompts
+pub fn list_prompts() -> Vec<PromptInfo> {
+    let topic = PromptArgumentInfo {
+        name: "topic",
+        description: "Topic or keywords to search for",
+        required: true,
+    };
+    let from = PromptArgumentInfo {
+        name: "from",
+        description: "Start date (YYYY-MM-DD, inclusive)",
+        required: false,
+    };
+    let to = PromptArgumentInfo {
+        name: "to",
+        description: "End date (YYYY-MM-DD, inclusive)",
+        required: false,
+    };
+    let index = PromptArgumentInfo {
+        name: "index",
+        description: "Index to read memos from (default: the first declared index)",
+        required: false,
+    };
+
+    vec![
+        PromptInfo {
+            name: "summarize_topic",
+            description: "Summarize my work on a topic, optionally between two dates",
+            arguments: vec![topic.clone(), from.clone(), to.clone(), index.clone()],
+        },
+        PromptInfo {
+            name: "weekly_report",
+            description:
+                "Write a weekly report from the memos of a date range (default: the last 7 days)",
+            arguments: vec![
+                from,
+                to,
+                PromptArgumentInfo {
+                    name: "tag",
+                    description: "Only include memos with this tag (e.g. worklog)",
+                    required: false,
+                },
+                index.clone(),
+            ],
+        },
+        PromptInfo {
+            name: "prior_decisions",
+            description: "Find prior decisions about a topic and their rationale",
+            arguments: vec![topic, index],
+        },
+    ]
+}
+
+/// Builds prompts from search results and docstore date queries
+pub struct PromptBuilder<'a> {
+    searcher: &'a Searcher,
+    search_mode: SearchMode,
+    today: NaiveDate,
+}
+
+impl<'a> PromptBuilder<'a> {
+    /// Create a new builder using BM25 search and the current date
+    pub fn new(searcher: &'a Searcher) -> Self {
+        Self {
+            searcher,
+            search_mode: SearchMode::Bm25,
+            today: Utc::now().date_naive(),
+        }
+    }
+
+    /// Set the search mode used for topic prompts
+    pub fn with_search_mode(mut self, mode: SearchMode) -> Self {
+        self.search_mode = mode;
+        self
+    }
+
+    /// Set the date that relative ranges are based on
+    pub fn with_today(mut self, today: NaiveDate) -> Self {
+        self.today = today;
+        self
+    }
+
+    /// Build a prompt by name
+    pub fn build(&self, name: &str, args: &HashMap<String, String>) -> Result<BuiltPrompt> {
+        match name {
+            "summarize_topic" => self.summarize_topic(args),
+            "weekly_report" => self.weekly_report(args),
+            "prior_decisions" => self.prior_decisions(args),
+            _ => Err(anyhow!("Unknown prompt: {}", name)),
+        }
+    }
+
+    fn summarize_topic(&self, args: &HashMap<String, String>) -> Result<BuiltPrompt> {
+        let topic = required_arg(args, "topic")?;
+        let from = optional_date(args, "from")?;
+        let to = optional_date(args, "to")?;
+
+        let mut docs: Vec<&Document> = self
+            .search(topic)?
+            .into_iter()
+            .filter(|doc| in_range(doc, from, to))
+            .take(PROMPT_MAX_MEMOS)
+            .collect();
+        docs.sort_by(|a, b| a.date().cmp(&b.date()).then_with(|| a.id.cmp(&b.id)));
+
+        let period = describe_period(from, to);
+        let description = format!("{} memos about '{}'{}", docs.len(), topic, period);
+        let text = format!(
+            "Summarize my work on \"{}\"{} based on the memos below.\n\
+             Describe what was done, the current status, and any open issues, \
+             in chronological order. Cite memo IDs for each point.\n\n{}",
+            topic,
+            period,
+            render_memos(&docs)
+        );
+
+        Ok(BuiltPrompt { description, text })
+    }
+
+    fn weekly_report(&self, args: &HashMap<String, String>) -> Result<BuiltPrompt> {
+        let to = optional_date(args, "to")?.unwrap_or(self.today);
+        let from = optional_date(args, "from")?
+            .unwrap_or_else(|| to - Duration::days(WEEKLY_REPORT_DAYS - 1));
+        let tag = args
+            .get("tag")
+            .map(|t| t.as_str())
+            .filter(|t| !t.is_empty());
+
+        let mut docs: Vec<&Document> = self
+            .searcher
+            .docstore()
+            .get_by_date_range(start_of(from), start_of(to + Duration::days(1)))
+            .into_iter()
+            .filter(|doc| tag.is_none_or(|t| doc.has_tag(t)))
+            .collect();
+        // Keep the newest memos, still in chronological order
+        let total = docs.len();
+        docs.drain(..total.saturating_sub(PROMPT_MAX_MEMOS));
+
+        let (memos, entries) = match tag {
+            Some("worklog") => ("worklog memos".to_string(), "worklog entries".to_string()),
+            Some(tag) => (
+                format!("memos tagged '{}'", tag),
+                format!("memos tagged '{}'", tag),
+            ),
+            None => ("memos".to_string(), "memos".to_string()),
+        };
+        let count = if docs.len() < total {
+            format!("{} of {}", docs.len(), total)
+        } else {
+            total.to_string()
+        };
+        let truncated = if docs.len() < total {
+            format!(
+                "Only the {} most recent of the {} {} in this period are included.\n",
+                docs.len(),
+                total,
+                memos
+            )
+        } else {
+            String::new()
+        };
+
+        let period = describe_period(Some(from), Some(to));
+        let description = format!("{} {}{}", count, memos, period);
+        let text = format!(
+            "Write a weekly report{} from the {} below.\n\
+             Group the work into themes, list accomplishments, ongoing work and \
+             next steps, and cite memo IDs.\n{}\n{}",
+            period,
+            entries,
+            truncated,
+            render_memos(&docs)
+        );
+
+        Ok(BuiltPrompt { description, text })
+    }
+
+    fn prior_decisions(&self, args: &HashMap<String, String>) -> Result<BuiltPrompt> {
+        let topic = required_arg(args, "topic")?;
+        let docs: Vec<&Document> = self
+            .search(topic)?
+            .into_iter()
+            .take(PROMPT_MAX_MEMOS)
+            .collect();
+
+        let description = format!("{} memos related to '{}'", docs.len(), topic);
+        let text = format!(
+            "Find the decisions I have made about \"{}\" in the memos below.\n\
+             For each decision give the date, what was decided, the rationale, \
+             and any alternatives that were rejected. Cite memo IDs. If a later \
+             memo reverses an earlier decision, point that out.\n\n{}",
+            topic,
+            render_memos(&docs)
+        );
+
+        Ok(BuiltPrompt { description, text })
+    }
+
+    /// Search for a topic, returning documents in relevance order
+    fn search(&self, query: &str) -> Result<Vec<&'a Document>> {
+        let config = SearchConfig::new()
+            .with_mode(self.search_mode)
+            .with_top_k(PROMPT_SEARCH_DEPTH);
+        let results = self.searcher.search(query, &config)?;
+        let docstore = self.searcher.docstore();
+        Ok(results
+            .iter()
+            .filter_map(|result| docstore.get(&result.doc_id))
+            .collect())
+    }
+}
+
+/// Get a required, non-empty argument
+fn required_arg<'a>(args: &'a HashMap<String, String>, name: &str) -> Result<&'a str> {
+    args.get(name)
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("Missing required argument: {}", name))
+}
+
+/// Parse an optional YYYY-MM-DD argument
+fn optional_date(args: &HashMap<String, String>, name: &str) -> Result<Option<NaiveDate>> {
+    match args.get(name).map(|value| value.trim()) {
+        None | Some("") => Ok(None),
+        Some(value) => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| anyhow!("Invalid date for {}: {} (expected YYYY-MM-DD)", name, value)),
+    }
+}
+
+/// Midnight UTC at the start of a date
+fn start_of(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc()
+}
+
+/// Check if a document falls within an inclusive date range
+fn in_range(doc: &Document, from: Option<NaiveDate>, to: Option<NaiveDate>) -> bool {
+    let date = doc.date().date_naive();
+    from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to)
+}
+
+/// Describe a date range, e.g. " from 2025-01-01 to 2025-01-07"
+fn describe_period(from: Option<NaiveDate>, to: Option<NaiveDate>) -> String {
+    match (from, to) {
+        (Some(from), Some(to)) => format!(" from {} to {}", from, to),
+        (Some(from), None) => format!(" since {}", from),
+        (None, Some(to)) => format!(" until {}", to),
+        (None, None) => String::new(),
+    }
+}
+
+/// Render memos as prompt context
+fn render_memos(docs: &[&Document]) -> String {
+    if docs.is_empty() {
+        return "(No matching memos were found.)\n".to_string();
+    }
+
+    let extractor = ContentExtractor::new(
+        ExtractionStrategy::Full,
+        TruncationConfig {
+            max_chars: Some(PROMPT_MEMO_CHARS),
+            max_lines: None,
+            max_sections: None,
+        },
+    );
+
+    let mut output = String::from("## Memos\n");
+    for doc in docs {
+        output.push_str(&format!(
+            "\n### {} ({}) [id: {}]\nTags: {}\n\n{}\n",
+            doc.title(),
+            doc.date().format("%Y-%m-%d"),
+            doc.id,
+            doc.tags().join(", "),
+            extractor.extract(&doc.text).text
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::Docstore;
+    use chrono::TimeZone;
+
+    /// Write an index with three dated memos; only the docstore is populated
+    fn setup_index(dir: &std::path::Path) {
+        std::fs::write(
+            dir.join("bm25_index.json"),
+            r#"{"version": "1.0", "doc_ids": [], "corpus": []}"#,
+        )
+        .unwrap();
+
+        let mut docstore = Docstore::new();
+        for (id, day, tag) in [
+            ("doc1", 1, "worklog"),
+            ("doc2", 6, "worklog"),
+            ("doc3", 7, "idea"),
+        ] {
+            docstore.add(Document::with_id(
+                id.to_string(),
+                format!("Memo {}", id),
+                Utc.with_ymd_and_hms(2025, 1, day, 9, 0, 0).unwrap(),
+                vec![tag.to_string()],
+                format!("Body of {}", id),
+            ));
+        }
+        docstore.save_to_file(&dir.join("docstore.json")).unwrap();
+    }
+
+    fn args(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_list_prompts() {
+        let prompts = list_prompts();
+        let names: Vec<&str> = prompts.iter().map(|p| p.name).collect();
+        assert_eq!(
+            names,
+            vec!["summarize_topic", "weekly_report", "prior_decisions"]
+        );
+        assert!(prompts
+            .iter()
+            .all(|p| p.arguments.iter().any(|a| a.name == "index" && !a.required)));
+    }
+
+    #[test]
+    fn test_weekly_report_defaults_to_last_seven_days() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        setup_index(temp_dir.path());
+        let searcher = Searcher::new(temp_dir.path()).unwrap();
+        let builder =
+            PromptBuilder::new(&searcher).with_today(NaiveDate::from_ymd_opt(2025, 1, 7).unwrap());
+
+        let prompt = builder.build("weekly_report", &HashMap::new()).unwrap();
+        assert!(prompt.text.contains("from 2025-01-01 to 2025-01-07"));
+        assert!(prompt.text.contains("[id: doc1]"));
+        assert!(prompt.text.contains("[id: doc3]"));
+        // Chronological order
+        assert!(prompt.text.find("doc1").unwrap() < prompt.text.find("doc2").unwrap());
+
+        let prompt = builder
+            .build(
+                "weekly_report",
+                &args(&[("tag", "worklog"), ("from", "2025-01-02")]),
+            )
+            .unwrap();
+        assert_eq!(
+            prompt.description,
+            "1 worklog memos from 2025-01-02 to 2025-01-07"
+        );
+        assert!(prompt.text.contains("from the worklog entries below"));
+        assert!(!prompt.text.contains("[id: doc3]"));
+
+        let prompt = builder
+            .build("weekly_report", &args(&[("tag", "idea")]))
+            .unwrap();
+        assert_eq!(
+            prompt.description,
+            "1 memos tagged 'idea' from 2025-01-01 to 2025-01-07"
+        );
+        assert!(!prompt.text.contains("worklog"));
+    }
+
+    #[test]
+    fn test_weekly_report_keeps_newest_memos() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut docstore = Docstore::new();
+        for minute in 0..PROMPT_MAX_MEMOS + 5 {
+            docstore.add(Document::with_id(
+                format!("doc{:02}", minute),
+                format!("Memo {}", minute),
+                Utc.with_ymd_and_hms(2025, 1, 6, 9, minute as u32, 0)
+                    .unwrap(),
+                vec![],
+                String::new(),
+            ));
+        }
+        docstore
+            .save_to_file(&temp_dir.path().join("docstore.json"))
+            .unwrap();
+        let searcher = Searcher::new(temp_dir.path()).unwrap();
+        let builder =
+            PromptBuilder::new(&searcher).with_today(NaiveDate::from_ymd_opt(2025, 1, 7).unwrap());
+
+        let prompt = builder.build("weekly_report", &HashMap::new()).unwrap();
+        assert_eq!(
+            prompt.description,
+            format!(
+                "{} of {} memos from 2025-01-01 to 2025-01-07",
+                PROMPT_MAX_MEMOS,
+                PROMPT_MAX_MEMOS + 5
+            )
+        );
+        assert!(prompt
+            .text
+            .contains("Only the 20 most recent of the 25 memos"));
+        assert!(!prompt.text.contains("[id: doc04]"));
+        assert!(prompt.text.contains("[id: doc05]"));
+        assert!(prompt.text.find("doc05").unwrap() < prompt.text.find("doc24").unwrap());
+    }
+
+    #[test]
+    fn test_prompt_argument_errors() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        setup_index(temp_dir.path());
+        let searcher = Searcher::new(temp_dir.path()).unwrap();
+        let builder = PromptBuilder::new(&searcher);
+
+        assert!(builder.build("summarize_topic", &HashMap::new()).is_err());
+        assert!(builder
+            .build("prior_decisions", &args(&[("topic", " ")]))
+            .is_err());
+        assert!(builder
+            .build("weekly_report", &args(&[("from", "last week")]))
+            .is_err());
+        assert!(builder.build("unknown", &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_in_range_is_inclusive() {
+        let doc = Document::with_id(
+            "doc".to_string(),
+            "Doc".to_string(),
+            Utc.with_ymd_and_hms(2025, 1, 7, 23, 0, 0).unwrap(),
+            vec![],
+            String::new(),
+        );
+        let day = NaiveDate::from_ymd_opt(2025, 1, 7);
+        assert!(in_range(&doc, day, day));
+        assert!(!in_range(&doc, None, NaiveDate::from_ymd_opt(2025, 1, 6)));
+    }
+}
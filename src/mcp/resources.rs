@@ -0,0 +1,361 @@
+//! MCP resource addressing
+//!
+//! Maps `digrag://` URIs onto documents in the docstore:
+//! - `digrag://memo/<id>` - a single memo
+//! - `digrag://tag/<tag>` - all memos with a tag
+//! - `digrag://recent` - the most recent memos
+//!
+//! Resources cover every configured index: a memo is looked up in each
+//! docstore in declaration order, and listings merge all of them.
+//!
+//! The rmcp handlers that serve these resources are in main.rs.
+
+use crate::index::Docstore;
+use crate::loader::Document;
+use std::collections::{BTreeMap, HashSet};
+
+/// URI scheme prefix for all resources
+pub const RESOURCE_SCHEME: &str = "digrag://";
+
+/// Number of memos listed by the `digrag://recent` resource
+pub const RECENT_RESOURCE_LIMIT: usize = 20;
+
+/// MIME type of resource contents
+pub const RESOURCE_MIME_TYPE: &str = "text/markdown";
+
+/// A resource addressable by a `digrag://` URI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoResource {
+    /// A single memo by document ID
+    Memo(String),
+    /// All memos with a tag
+    Tag(String),
+    /// The most recent memos
+    Recent,
+}
+
+/// Listing entry for a resource
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceInfo {
+    /// Resource URI
+    pub uri: String,
+    /// Display name
+    pub name: String,
+    /// Short description
+    pub description: String,
+}
+
+/// Listing entry for a resource template
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceTemplateInfo {
+    /// RFC 6570 URI template
+    pub uri_template: String,
+    /// Display name
+    pub name: String,
+    /// Short description
+    pub description: String,
+}
+
+impl MemoResource {
+    /// Parse a `digrag://` URI, returning None for unknown URIs
+    pub fn parse(uri: &str) -> Option<Self> {
+        let path = uri.strip_prefix(RESOURCE_SCHEME)?;
+        if path == "recent" {
+            return Some(Self::Recent);
+        }
+
+        let (kind, value) = path.split_once('/')?;
+        let value = percent_decode(value)?;
+        if value.is_empty() {
+            return None;
+        }
+
+        match kind {
+            "memo" => Some(Self::Memo(value)),
+            "tag" => Some(Self::Tag(value)),
+            _ => None,
+        }
+    }
+
+    /// Format the resource as a URI
+    pub fn uri(&self) -> String {
+        match self {
+            Self::Memo(id) => format!("{}memo/{}", RESOURCE_SCHEME, percent_encode(id)),
+            Self::Tag(tag) => format!("{}tag/{}", RESOURCE_SCHEME, percent_encode(tag)),
+            Self::Recent => format!("{}recent", RESOURCE_SCHEME),
+        }
+    }
+
+    /// Render the resource contents, or None if it does not exist
+    pub fn read(&self, docstores: &[&Docstore]) -> Option<String> {
+        match self {
+            Self::Memo(id) => docstores
+                .iter()
+                .find_map(|docstore| docstore.get(id))
+                .map(render_memo),
+            Self::Tag(tag) => {
+                let docs = newest_first(docstores.iter().flat_map(|d| d.get_by_tag(tag)));
+                if docs.is_empty() {
+                    return None;
+                }
+                Some(render_listing(&format!("Memos tagged '{}'", tag), &docs))
+            }
+            Self::Recent => {
+                let mut docs = newest_first(
+                    docstores
+                        .iter()
+                        .flat_map(|d| d.get_recent(RECENT_RESOURCE_LIMIT)),
+                );
+                docs.truncate(RECENT_RESOURCE_LIMIT);
+                Some(render_listing("Recent memos", &docs))
+            }
+        }
+    }
+}
+
+/// List all concrete resources: recent memos, then tags, then memos by recency
+pub fn list_resources(docstores: &[&Docstore]) -> Vec<ResourceInfo> {
+    let mut resources = vec![ResourceInfo {
+        uri: MemoResource::Recent.uri(),
+        name: "Recent memos".to_string(),
+        description: format!("The {} most recent memos", RECENT_RESOURCE_LIMIT),
+    }];
+
+    let mut tags: BTreeMap<String, usize> = BTreeMap::new();
+    for docstore in docstores {
+        for tag in docstore.get_all_tags() {
+            *tags.entry(tag.clone()).or_default() += docstore.get_by_tag(&tag).len();
+        }
+    }
+    for (tag, count) in tags {
+        resources.push(ResourceInfo {
+            uri: MemoResource::Tag(tag.clone()).uri(),
+            name: format!("Tag: {}", tag),
+            description: format!("{} memos tagged '{}'", count, tag),
+        });
+    }
+
+    let docs = docstores.iter().flat_map(|d| d.get_recent(d.len()));
+    for doc in newest_first(docs) {
+        resources.push(ResourceInfo {
+            uri: MemoResource::Memo(doc.id.clone()).uri(),
+            name: doc.title().to_string(),
+            description: format!("{} {:?}", doc.date().format("%Y-%m-%d"), doc.tags()),
+        });
+    }
+
+    resources
+}
+
+/// List the resource templates for memos and tags
+pub fn list_resource_templates() -> Vec<ResourceTemplateInfo> {
+    vec![
+        ResourceTemplateInfo {
+            uri_template: format!("{}memo/{{id}}", RESOURCE_SCHEME),
+            name: "Memo".to_string(),
+            description: "A single memo by document ID".to_string(),
+        },
+        ResourceTemplateInfo {
+            uri_template: format!("{}tag/{{tag}}", RESOURCE_SCHEME),
+            name: "Memos by tag".to_string(),
+            description: "All memos with a tag, newest first".to_string(),
+        },
+    ]
+}
+
+/// Documents newest first, keeping the first of each ID
+fn newest_first<'a>(docs: impl Iterator<Item = &'a Document>) -> Vec<&'a Document> {
+    let mut seen = HashSet::new();
+    let mut docs: Vec<&Document> = docs.filter(|doc| seen.insert(doc.id.as_str())).collect();
+    docs.sort_by(|a, b| b.date().cmp(&a.date()).then_with(|| a.id.cmp(&b.id)));
+    docs
+}
+
+/// Render a memo as markdown
+fn render_memo(doc: &Document) -> String {
+    format!(
+        "# {}\n\nDate: {}\nTags: {}\n\n{}\n",
+        doc.title(),
+        doc.date().format("%Y-%m-%d %H:%M"),
+        doc.tags().join(", "),
+        doc.text
+    )
+}
+
+/// Render a list of memos as markdown links to their resources
+fn render_listing(heading: &str, docs: &[&Document]) -> String {
+    let mut output = format!("# {}\n\n", heading);
+    for doc in docs {
+        output.push_str(&format!(
+            "- {} [{}]({})\n",
+            doc.date().format("%Y-%m-%d"),
+            doc.title(),
+            MemoResource::Memo(doc.id.clone()).uri()
+        ));
+    }
+    output
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Decode a percent-encoded value, returning None for malformed input
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn create_docstore() -> Docstore {
+        let mut docstore = Docstore::new();
+        docstore.add(Document::with_id(
+            "doc1".to_string(),
+            "First memo".to_string(),
+            Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap(),
+            vec!["rust".to_string()],
+            "Body one".to_string(),
+        ));
+        docstore.add(Document::with_id(
+            "doc2".to_string(),
+            "Second memo".to_string(),
+            Utc.with_ymd_and_hms(2025, 1, 2, 9, 0, 0).unwrap(),
+            vec!["rust".to_string(), "日本語 タグ".to_string()],
+            "Body two".to_string(),
+        ));
+        docstore
+    }
+
+    #[test]
+    fn test_parse_uris() {
+        assert_eq!(
+            MemoResource::parse("digrag://memo/doc1"),
+            Some(MemoResource::Memo("doc1".to_string()))
+        );
+        assert_eq!(
+            MemoResource::parse("digrag://tag/rust"),
+            Some(MemoResource::Tag("rust".to_string()))
+        );
+        assert_eq!(
+            MemoResource::parse("digrag://recent"),
+            Some(MemoResource::Recent)
+        );
+        assert!(MemoResource::parse("digrag://memo/").is_none());
+        assert!(MemoResource::parse("digrag://other/x").is_none());
+        assert!(MemoResource::parse("file:///memo/doc1").is_none());
+        assert!(MemoResource::parse("digrag://tag/%ZZ").is_none());
+    }
+
+    #[test]
+    fn test_uri_roundtrip_with_encoding() {
+        let resource = MemoResource::Tag("日本語 タグ".to_string());
+        let uri = resource.uri();
+        assert!(!uri.contains(' '));
+        assert_eq!(MemoResource::parse(&uri), Some(resource));
+    }
+
+    #[test]
+    fn test_read_resources() {
+        let docstore = create_docstore();
+
+        let memo = MemoResource::Memo("doc1".to_string())
+            .read(&[&docstore])
+            .unwrap();
+        assert!(memo.starts_with("# First memo"));
+        assert!(memo.contains("Body one"));
+
+        let tag = MemoResource::Tag("rust".to_string())
+            .read(&[&docstore])
+            .unwrap();
+        // Newest first
+        assert!(tag.find("Second memo").unwrap() < tag.find("First memo").unwrap());
+        assert!(tag.contains("(digrag://memo/doc1)"));
+
+        assert!(MemoResource::Recent.read(&[&docstore]).is_some());
+        assert!(MemoResource::Memo("missing".to_string())
+            .read(&[&docstore])
+            .is_none());
+        assert!(MemoResource::Tag("missing".to_string())
+            .read(&[&docstore])
+            .is_none());
+    }
+
+    #[test]
+    fn test_list_resources() {
+        let docstore = create_docstore();
+        let uris: Vec<String> = list_resources(&[&docstore])
+            .into_iter()
+            .map(|r| r.uri)
+            .collect();
+
+        assert_eq!(uris[0], "digrag://recent");
+        assert!(uris.contains(&"digrag://tag/rust".to_string()));
+        assert!(uris.contains(&"digrag://memo/doc1".to_string()));
+        // Every listed URI can be read
+        for uri in &uris {
+            let resource = MemoResource::parse(uri).unwrap();
+            assert!(resource.read(&[&docstore]).is_some(), "{}", uri);
+        }
+    }
+
+    #[test]
+    fn test_resources_span_indexes() {
+        let first = create_docstore();
+        let mut second = Docstore::new();
+        second.add(Document::with_id(
+            "work1".to_string(),
+            "Work memo".to_string(),
+            Utc.with_ymd_and_hms(2025, 1, 3, 9, 0, 0).unwrap(),
+            vec!["rust".to_string()],
+            "Body three".to_string(),
+        ));
+        let docstores = [&first, &second];
+
+        let memo = MemoResource::Memo("work1".to_string())
+            .read(&docstores)
+            .unwrap();
+        assert!(memo.starts_with("# Work memo"));
+
+        let tag = MemoResource::Tag("rust".to_string())
+            .read(&docstores)
+            .unwrap();
+        assert!(tag.find("Work memo").unwrap() < tag.find("Second memo").unwrap());
+        assert!(MemoResource::Recent
+            .read(&docstores)
+            .unwrap()
+            .contains("(digrag://memo/work1)"));
+
+        let resources = list_resources(&docstores);
+        let rust = resources
+            .iter()
+            .find(|r| r.uri == "digrag://tag/rust")
+            .unwrap();
+        assert_eq!(rust.description, "3 memos tagged 'rust'");
+        assert!(resources.iter().any(|r| r.uri == "digrag://memo/work1"));
+    }
+}
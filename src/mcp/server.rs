@@ -3,15 +3,116 @@
 //! This module provides type definitions for the MCP server.
 //! The actual MCP server implementation using rmcp macros is in main.rs.
 
+use crate::config::{SearchConfig, SearchMode};
+use crate::extract::summarizer::SummaryUsage;
+use crate::extract::ContentExtractor;
+use crate::index::{Docstore, Period, TagCount, TimelineBucket, Topic};
+use crate::loader::Document;
+use crate::search::{ExtractedResult, Facets};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Number of characters included in a memo snippet
+const SNIPPET_CHARS: usize = 150;
+
+/// Output format for tool responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResponseFormat {
+    /// Human-readable text only
+    #[default]
+    Text,
+    /// Structured JSON only
+    Json,
+    /// Text followed by structured JSON
+    Both,
+}
+
+impl ResponseFormat {
+    /// Parse a format string ("text", "json" or "both"), defaulting to text
+    pub fn parse(format: Option<&str>) -> Self {
+        match format {
+            Some("json") => Self::Json,
+            Some("both") => Self::Both,
+            _ => Self::Text,
+        }
+    }
+
+    /// Whether the text form should be returned
+    pub fn includes_text(&self) -> bool {
+        matches!(self, Self::Text | Self::Both)
+    }
+
+    /// Whether the JSON form should be returned
+    pub fn includes_json(&self) -> bool {
+        matches!(self, Self::Json | Self::Both)
+    }
+}
+
+/// How summaries of search results are produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SummaryScope {
+    /// One summary per result
+    #[default]
+    PerResult,
+    /// One digest for all results of the page
+    Combined,
+}
+
+impl SummaryScope {
+    /// Parse a scope string ("per_result" or "combined"), defaulting to per_result
+    pub fn parse(scope: Option<&str>) -> Self {
+        match scope {
+            Some("combined") => Self::Combined,
+            _ => Self::PerResult,
+        }
+    }
+}
+
+/// Digest of all results on a page
+#[derive(Debug, Serialize, Clone)]
+pub struct DigestResult {
+    /// Digest text
+    pub text: String,
+    /// Summarization method: "rule-based", "extractive" or "llm"
+    pub method: String,
+    /// Model that wrote the digest (LLM digests only)
+    pub model: Option<String>,
+    /// Tokens spent on the digest (None for offline and cached digests)
+    pub usage: Option<SummaryUsage>,
+    /// IDs of the summarized memos
+    pub doc_ids: Vec<String>,
+}
 
 /// Query memos response
 #[derive(Debug, Serialize, Clone)]
 pub struct QueryMemosResponse {
+    /// Search query
+    pub query: String,
+    /// Search mode used
+    pub mode: String,
     /// Search results
     pub results: Vec<MemoResult>,
-    /// Total count
+    /// Total count across all pages (a lower bound when `total_exact` is false)
     pub total: usize,
+    /// Whether `total` is exact
+    pub total_exact: bool,
+    /// Offset of the first result
+    pub offset: usize,
+    /// Cursor for the next page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// Tag, category and month counts over every match, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facets: Option<Facets>,
+    /// Combined digest of the results, when `summary_scope` is "combined"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<DigestResult>,
+    /// Warnings about degraded search (e.g. missing vector index)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Build timestamp of each searched index, by index name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub indexed_at: BTreeMap<String, String>,
 }
 
 /// Individual memo result
@@ -21,14 +122,53 @@ pub struct MemoResult {
     pub id: String,
     /// Title
     pub title: String,
-    /// Date
+    /// Date (RFC 3339)
     pub date: String,
     /// Tags
     pub tags: Vec<String>,
+    /// Tags inferred from similar memos (not written in the changelog)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub inferred_tags: Vec<String>,
     /// Content snippet
     pub snippet: String,
-    /// Relevance score
-    pub score: f32,
+    /// Relevance score (absent for non-search listings)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    /// Extracted content, summary and extraction stats (entry/full modes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extracted: Option<ExtractedResult>,
+    /// Name of the index the memo belongs to (when several are served)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+}
+
+impl MemoResult {
+    /// Create a memo result from a document
+    pub fn from_document(doc: &Document, score: Option<f32>) -> Self {
+        Self {
+            id: doc.id.clone(),
+            title: doc.title().to_string(),
+            date: doc.date().to_rfc3339(),
+            tags: doc.tags().to_vec(),
+            inferred_tags: doc.inferred_tags().to_vec(),
+            snippet: doc.text.chars().take(SNIPPET_CHARS).collect(),
+            score,
+            extracted: None,
+            index: None,
+        }
+    }
+
+    /// Add extracted content to the result
+    pub fn with_extracted(mut self, extracted: ExtractedResult) -> Self {
+        self.extracted = Some(extracted);
+        self
+    }
+
+    /// Record the index the memo belongs to
+    pub fn with_index(mut self, index: Option<&str>) -> Self {
+        self.index = index.map(str::to_string);
+        self
+    }
 }
 
 /// List tags response
@@ -43,8 +183,63 @@ pub struct ListTagsResponse {
 pub struct TagInfo {
     /// Tag name
     pub name: String,
+    /// Number of documents with the tag in the changelog
+    pub count: usize,
+    /// Number of other documents with the tag inferred by `suggest-tags`
+    pub inferred_count: usize,
+}
+
+impl TagInfo {
+    /// Report a tag count
+    pub fn from_count(count: TagCount) -> Self {
+        Self {
+            name: count.tag,
+            count: count.authored,
+            inferred_count: count.inferred,
+        }
+    }
+}
+
+/// List topics response
+#[derive(Debug, Serialize, Clone)]
+pub struct ListTopicsResponse {
+    /// Topics of the selected indexes
+    pub topics: Vec<TopicInfo>,
+}
+
+/// Topic information
+#[derive(Debug, Serialize, Clone)]
+pub struct TopicInfo {
+    /// Topic ID, usable as the `topic` filter
+    pub id: usize,
+    /// Label built from the topic terms
+    pub label: String,
+    /// Most distinctive terms
+    pub terms: Vec<String>,
     /// Document count
     pub count: usize,
+    /// Name of the index the topic belongs to (when several are served)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+}
+
+impl TopicInfo {
+    /// Summarize a topic
+    pub fn from_topic(topic: &Topic) -> Self {
+        Self {
+            id: topic.id,
+            label: topic.label.clone(),
+            terms: topic.terms.clone(),
+            count: topic.doc_ids.len(),
+            index: None,
+        }
+    }
+
+    /// Set the index name to report
+    pub fn with_index(mut self, index: Option<&str>) -> Self {
+        self.index = index.map(String::from);
+        self
+    }
 }
 
 /// Get recent memos response
@@ -52,6 +247,135 @@ pub struct TagInfo {
 pub struct GetRecentMemosResponse {
     /// Recent memos
     pub memos: Vec<MemoResult>,
+    /// Total count across all pages (a lower bound when `total_exact` is false)
+    pub total: usize,
+    /// Whether `total` is exact
+    pub total_exact: bool,
+    /// Offset of the first memo
+    pub offset: usize,
+    /// Cursor for the next page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// Build timestamp of each listed index, by index name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub indexed_at: BTreeMap<String, String>,
+}
+
+/// Timeline response
+#[derive(Debug, Serialize, Clone)]
+pub struct TimelineResponse {
+    /// Query the memos were restricted to, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Bucket length
+    pub period: Period,
+    /// Number of memos counted
+    pub total: usize,
+    /// Periods, oldest first
+    pub buckets: Vec<TimelineBucket>,
+}
+
+/// Find related memos response
+#[derive(Debug, Serialize, Clone)]
+pub struct RelatedMemosResponse {
+    /// Source document ID
+    pub source_id: String,
+    /// Source document title
+    pub source_title: String,
+    /// Related memos
+    pub results: Vec<MemoResult>,
+    /// Total count across all pages (a lower bound when `total_exact` is false)
+    pub total: usize,
+    /// Whether `total` is exact
+    pub total_exact: bool,
+    /// Offset of the first result
+    pub offset: usize,
+    /// Cursor for the next page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Get memo response
+#[derive(Debug, Serialize, Clone)]
+pub struct GetMemoResponse {
+    /// Found memos, in request order
+    pub memos: Vec<MemoDetail>,
+    /// Requested IDs that do not exist
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub not_found: Vec<String>,
+}
+
+/// Full memo with optional neighbouring entries
+#[derive(Debug, Serialize, Clone)]
+pub struct MemoDetail {
+    /// The memo itself, with extracted content
+    #[serde(flatten)]
+    pub memo: MemoResult,
+    /// Entries dated before this memo (chronological order)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub previous: Vec<MemoResult>,
+    /// Entries dated after this memo (chronological order)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub next: Vec<MemoResult>,
+}
+
+impl MemoDetail {
+    /// Build the detail of a memo with up to `neighbors` entries on each side
+    pub fn from_document(
+        docstore: &Docstore,
+        doc: &Document,
+        extractor: &ContentExtractor,
+        neighbors: usize,
+    ) -> Self {
+        let memo = MemoResult::from_document(doc, None)
+            .with_extracted(extractor.extract(&doc.text).into());
+
+        let (previous, next) = if neighbors > 0 {
+            docstore
+                .get_neighbors(&doc.id, neighbors)
+                .unwrap_or_default()
+        } else {
+            Default::default()
+        };
+        let to_results = |docs: Vec<&Document>| {
+            docs.into_iter()
+                .map(|d| MemoResult::from_document(d, None))
+                .collect()
+        };
+
+        Self {
+            memo,
+            previous: to_results(previous),
+            next: to_results(next),
+        }
+    }
+}
+
+/// Add memo response
+#[derive(Debug, Serialize, Clone)]
+pub struct AddMemoResponse {
+    /// The recorded memo
+    pub memo: MemoResult,
+    /// Changelog file the memo was written to
+    pub changelog: String,
+}
+
+/// Health check response
+#[derive(Debug, Serialize, Clone)]
+pub struct HealthResponse {
+    /// Always "ok" when the server is responding
+    pub status: String,
+    /// Number of indexed documents
+    pub documents: usize,
+    /// Whether semantic search is available
+    pub vector_index: bool,
+}
+
+/// Error response body
+#[derive(Debug, Serialize, Clone)]
+pub struct ErrorResponse {
+    /// Error message
+    pub error: String,
 }
 
 /// Query memos request (for external API use)
@@ -64,12 +388,74 @@ pub struct QueryMemosRequest {
     pub top_k: usize,
     /// Optional tag filter
     pub tag_filter: Option<String>,
+    /// Optional topic filter (topic ID from `digrag topics`)
+    #[serde(default)]
+    pub topic: Option<usize>,
     /// Search mode: bm25, semantic, or hybrid (default: bm25)
     #[serde(default)]
     pub search_mode: String,
     /// Enable query rewriting (default: true)
     #[serde(default = "default_true")]
     pub enable_rewrite: bool,
+    /// MMR lambda for diversifying results (omit to disable)
+    #[serde(default)]
+    pub mmr_lambda: Option<f32>,
+    /// Return only the best-ranked memo of each group of near-duplicates
+    #[serde(default)]
+    pub collapse_duplicates: bool,
+    /// Include tag, category and month counts over every match
+    #[serde(default)]
+    pub facets: bool,
+    /// Number of results to skip (default: 0)
+    #[serde(default)]
+    pub offset: usize,
+    /// Cursor from a previous response to fetch the next page
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Extraction mode: snippet, entry, or full (default: snippet)
+    #[serde(default)]
+    pub extraction_mode: Option<String>,
+    /// Maximum characters to extract in entry/full modes
+    #[serde(default)]
+    pub max_chars: Option<usize>,
+}
+
+impl QueryMemosRequest {
+    /// Get the requested search mode, or `default_mode` when none is given
+    pub fn effective_mode<'a>(&'a self, default_mode: &'a str) -> &'a str {
+        if self.search_mode.is_empty() {
+            default_mode
+        } else {
+            &self.search_mode
+        }
+    }
+
+    /// Build the search configuration, using `default_mode` when no mode is given
+    pub fn search_config(&self, default_mode: &str) -> SearchConfig {
+        SearchConfig::new()
+            .with_mode(SearchMode::parse(self.effective_mode(default_mode)))
+            .with_top_k(self.top_k)
+            .with_tag_filter(self.tag_filter.clone())
+            .with_topic_filter(self.topic)
+            .with_mmr_lambda(self.mmr_lambda)
+            .with_collapse_duplicates(self.collapse_duplicates)
+            .with_offset(self.offset)
+            .with_cursor(self.cursor.clone())
+    }
+}
+
+/// Get memo request (for external API use)
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct GetMemoRequest {
+    /// Extraction mode: snippet, entry, or full (default: full)
+    #[serde(default)]
+    pub extraction_mode: Option<String>,
+    /// Maximum characters to extract
+    #[serde(default)]
+    pub max_chars: Option<usize>,
+    /// Number of entries before and after the memo to include (default: 0)
+    #[serde(default)]
+    pub neighbors: usize,
 }
 
 fn default_top_k() -> usize {
@@ -86,6 +472,12 @@ pub struct GetRecentMemosRequest {
     /// Number of memos to return (default: 10)
     #[serde(default = "default_limit")]
     pub limit: usize,
+    /// Number of memos to skip (default: 0)
+    #[serde(default)]
+    pub offset: usize,
+    /// Cursor from a previous response to fetch the next page
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 fn default_limit() -> usize {
@@ -107,6 +499,26 @@ mod tests {
         assert!(request.enable_rewrite);
     }
 
+    #[test]
+    fn test_query_memos_request_search_config() {
+        let json = r#"{"query": "test", "top_k": 5, "offset": 10}"#;
+        let request: QueryMemosRequest = serde_json::from_str(json).unwrap();
+
+        let config = request.search_config("hybrid");
+        assert_eq!(config.search_mode, SearchMode::Hybrid);
+        assert_eq!(config.top_k, 5);
+        assert_eq!(config.offset, 10);
+
+        let request = QueryMemosRequest {
+            search_mode: "semantic".to_string(),
+            ..request
+        };
+        assert_eq!(
+            request.search_config("bm25").search_mode,
+            SearchMode::Semantic
+        );
+    }
+
     #[test]
     fn test_memo_result_serialization() {
         let result = MemoResult {
@@ -114,14 +526,77 @@ mod tests {
             title: "Test Title".to_string(),
             date: "2025-01-15 10:00:00".to_string(),
             tags: vec!["memo".to_string()],
+            inferred_tags: vec![],
             snippet: "Test content...".to_string(),
-            score: 0.85,
+            score: Some(0.85),
+            extracted: None,
+            index: None,
         };
 
         let json = serde_json::to_string(&result).unwrap();
         assert!(json.contains("Test Title"));
     }
 
+    #[test]
+    fn test_memo_result_from_document() {
+        use chrono::TimeZone;
+
+        let doc = Document::with_id(
+            "doc1".to_string(),
+            "Title".to_string(),
+            chrono::Utc.with_ymd_and_hms(2025, 1, 15, 10, 0, 0).unwrap(),
+            vec!["memo".to_string()],
+            "a".repeat(200),
+        );
+        let result = MemoResult::from_document(&doc, None);
+
+        assert_eq!(result.id, "doc1");
+        assert_eq!(result.date, "2025-01-15T10:00:00+00:00");
+        assert_eq!(result.snippet.chars().count(), 150);
+
+        // Score and extraction are omitted when absent
+        let json = serde_json::to_value(&result).unwrap();
+        assert!(json.get("score").is_none());
+        assert!(json.get("extracted").is_none());
+        assert!(json.get("index").is_none());
+        assert!(json.get("inferred_tags").is_none());
+
+        let json = serde_json::to_value(result.with_index(Some("wiki"))).unwrap();
+        assert_eq!(json["index"], "wiki");
+    }
+
+    #[test]
+    fn test_memo_detail_flattens_memo() {
+        let detail = MemoDetail {
+            memo: MemoResult {
+                id: "doc1".to_string(),
+                title: "Title".to_string(),
+                date: "2025-01-15T10:00:00+00:00".to_string(),
+                tags: vec![],
+                inferred_tags: vec![],
+                snippet: String::new(),
+                score: None,
+                extracted: None,
+                index: None,
+            },
+            previous: vec![],
+            next: vec![],
+        };
+
+        let json = serde_json::to_value(&detail).unwrap();
+        assert_eq!(json["id"], "doc1");
+        assert!(json.get("previous").is_none());
+    }
+
+    #[test]
+    fn test_response_format_parse() {
+        assert_eq!(ResponseFormat::parse(None), ResponseFormat::Text);
+        assert_eq!(ResponseFormat::parse(Some("json")), ResponseFormat::Json);
+        assert_eq!(ResponseFormat::parse(Some("both")), ResponseFormat::Both);
+        assert_eq!(ResponseFormat::parse(Some("xml")), ResponseFormat::Text);
+        assert!(ResponseFormat::Both.includes_text() && ResponseFormat::Both.includes_json());
+    }
+
     #[test]
     fn test_get_recent_memos_request_defaults() {
         let json = r#"{}"#;
@@ -0,0 +1,333 @@
+//! HTTP/SSE transport for the MCP server
+//!
+//! Speaks the same SSE protocol as rmcp's `SseServer`: `GET /sse` opens an
+//! event stream whose first `endpoint` event names a per-session POST URL,
+//! and `POST /message?sessionId=...` delivers client messages. The routes are
+//! mounted on our own axum router so an optional bearer token can guard them;
+//! each session is handed to rmcp as a sink/stream transport.
+
+use anyhow::Result;
+use axum::{
+    extract::{Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post},
+    Json, Router,
+};
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use rmcp::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+use rmcp::{RoleServer, Service, ServiceExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+/// Path of the SSE event stream
+pub const SSE_PATH: &str = "/sse";
+
+/// Path that clients POST messages to
+pub const MESSAGE_PATH: &str = "/message";
+
+/// Buffered messages per direction and session
+const SESSION_CHANNEL_SIZE: usize = 64;
+
+/// Transport handed to rmcp for one session
+type SessionTransport = (
+    mpsc::Sender<ServerJsonRpcMessage>,
+    mpsc::Receiver<ClientJsonRpcMessage>,
+);
+
+/// Senders for client messages, keyed by session ID
+type SessionStore = Arc<Mutex<HashMap<String, mpsc::Sender<ClientJsonRpcMessage>>>>;
+
+#[derive(Clone)]
+struct SseState {
+    sessions: SessionStore,
+    transports: tokio::sync::mpsc::UnboundedSender<SessionTransport>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MessageQuery {
+    session_id: String,
+}
+
+/// Removes a session from the store when its event stream is dropped
+struct SessionGuard {
+    id: String,
+    sessions: SessionStore,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.remove(&self.id);
+        }
+        tracing::debug!(session = %self.id, "sse session closed");
+    }
+}
+
+/// Serve MCP over HTTP/SSE until the listener fails
+///
+/// `service_provider` creates the handler for each new session; clone a shared
+/// handler here so all sessions use the same loaded index. When
+/// `bearer_token` is set, requests without a matching
+/// `Authorization: Bearer <token>` header are rejected with 401.
+pub async fn serve_sse<S, F>(
+    listener: TcpListener,
+    bearer_token: Option<String>,
+    service_provider: F,
+) -> Result<()>
+where
+    S: Service<RoleServer>,
+    F: Fn() -> S + Send + 'static,
+{
+    let (transports, mut transport_rx) = tokio::sync::mpsc::unbounded_channel();
+    let state = SseState {
+        sessions: Arc::default(),
+        transports,
+    };
+
+    tokio::spawn(async move {
+        while let Some(transport) = transport_rx.recv().await {
+            let service = service_provider();
+            let (sink, stream) = transport;
+            // rmcp expects transport errors convertible from io::Error
+            let transport = (sink.sink_map_err(std::io::Error::other), stream);
+            tokio::spawn(async move {
+                match service.serve(transport).await {
+                    Ok(running) => {
+                        if let Err(e) = running.waiting().await {
+                            tracing::warn!("MCP session ended with error: {}", e);
+                        }
+                    }
+                    Err(e) => tracing::warn!("Failed to start MCP session: {}", e),
+                }
+            });
+        }
+    });
+
+    let router = Router::new()
+        .route(SSE_PATH, get(sse_handler))
+        .route(MESSAGE_PATH, post(message_handler))
+        .layer(middleware::from_fn_with_state(
+            bearer_token.map(Arc::from),
+            require_bearer,
+        ))
+        .with_state(state);
+
+    axum::serve(listener, router).await?;
+    Ok(())
+}
+
+/// Check an `Authorization` header against the expected bearer token
+pub fn check_bearer_token(headers: &HeaderMap, expected: &str) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.trim().as_bytes(), expected.as_bytes()))
+}
+
+/// Compare two byte strings without short-circuiting on the first mismatch
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Middleware rejecting requests without the expected bearer token (if any)
+pub async fn require_bearer(
+    State(bearer_token): State<Option<Arc<str>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match &bearer_token {
+        Some(token) if !check_bearer_token(request.headers(), token) => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+        )
+            .into_response(),
+        _ => next.run(request).await,
+    }
+}
+
+async fn sse_handler(State(state): State<SseState>) -> Response {
+    let session_id = uuid::Uuid::new_v4().simple().to_string();
+    let (client_tx, client_rx) = mpsc::channel(SESSION_CHANNEL_SIZE);
+    let (server_tx, server_rx) = mpsc::channel(SESSION_CHANNEL_SIZE);
+
+    if let Ok(mut sessions) = state.sessions.lock() {
+        sessions.insert(session_id.clone(), client_tx);
+    }
+    let guard = SessionGuard {
+        id: session_id.clone(),
+        sessions: state.sessions.clone(),
+    };
+
+    if state.transports.send((server_tx, client_rx)).is_err() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "MCP server is shutting down",
+        )
+            .into_response();
+    }
+    tracing::debug!(session = %session_id, "sse session opened");
+
+    let endpoint = Event::default()
+        .event("endpoint")
+        .data(format!("{}?sessionId={}", MESSAGE_PATH, session_id));
+    let messages = server_rx.map(move |message| {
+        // Keep the guard alive for as long as the client is connected
+        let _ = &guard;
+        serde_json::to_string(&message).map(|json| Event::default().event("message").data(json))
+    });
+    let stream = futures::stream::once(async move { Ok(endpoint) }).chain(messages);
+
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+async fn message_handler(
+    State(state): State<SseState>,
+    Query(query): Query<MessageQuery>,
+    Json(message): Json<ClientJsonRpcMessage>,
+) -> StatusCode {
+    let sender = state
+        .sessions
+        .lock()
+        .ok()
+        .and_then(|sessions| sessions.get(&query.session_id).cloned());
+
+    match sender {
+        Some(mut sender) => {
+            if sender.send(message).await.is_ok() {
+                StatusCode::ACCEPTED
+            } else {
+                StatusCode::GONE
+            }
+        }
+        None => StatusCode::NOT_FOUND,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::ServerHandler;
+
+    #[derive(Clone)]
+    struct EmptyServer;
+
+    impl ServerHandler for EmptyServer {}
+
+    async fn start_server(token: Option<&str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let token = token.map(str::to_string);
+        tokio::spawn(serve_sse(listener, token, || EmptyServer));
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_check_bearer_token() {
+        let mut headers = HeaderMap::new();
+        assert!(!check_bearer_token(&headers, "secret"));
+
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert!(check_bearer_token(&headers, "secret"));
+        assert!(!check_bearer_token(&headers, "secret2"));
+
+        headers.insert(header::AUTHORIZATION, "Basic secret".parse().unwrap());
+        assert!(!check_bearer_token(&headers, "secret"));
+    }
+
+    #[tokio::test]
+    async fn test_sse_announces_endpoint() {
+        let base = start_server(None).await;
+        let mut response = reqwest::get(format!("{}{}", base, SSE_PATH)).await.unwrap();
+        assert_eq!(response.status(), 200);
+
+        let chunk = response.chunk().await.unwrap().unwrap();
+        let text = String::from_utf8_lossy(&chunk);
+        assert!(text.contains("event: endpoint"));
+        assert!(text.contains("/message?sessionId="));
+    }
+
+    #[tokio::test]
+    async fn test_initialize_roundtrip() {
+        let base = start_server(None).await;
+        let client = reqwest::Client::new();
+        let mut events = client
+            .get(format!("{}{}", base, SSE_PATH))
+            .send()
+            .await
+            .unwrap();
+
+        let chunk = events.chunk().await.unwrap().unwrap();
+        let text = String::from_utf8_lossy(&chunk).to_string();
+        let endpoint = text
+            .lines()
+            .find_map(|line| line.strip_prefix("data: "))
+            .unwrap()
+            .to_string();
+
+        let response = client
+            .post(format!("{}{}", base, endpoint))
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": {"name": "test", "version": "0.0.0"}
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 202);
+
+        let chunk = events.chunk().await.unwrap().unwrap();
+        let text = String::from_utf8_lossy(&chunk);
+        assert!(text.contains("event: message"));
+        assert!(text.contains("protocolVersion"));
+    }
+
+    #[tokio::test]
+    async fn test_bearer_token_required() {
+        let base = start_server(Some("secret")).await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .get(format!("{}{}", base, SSE_PATH))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 401);
+
+        let response = client
+            .get(format!("{}{}", base, SSE_PATH))
+            .bearer_auth("secret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_message_for_unknown_session() {
+        let base = start_server(None).await;
+        let response = reqwest::Client::new()
+            .post(format!("{}{}?sessionId=missing", base, MESSAGE_PATH))
+            .json(&serde_json::json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+}
@@ -0,0 +1,203 @@
+//! Pagination support
+//!
+//! Provides offset and opaque cursor based paging over deterministically
+//! ordered result lists.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// Opaque page cursor
+///
+/// Records the position and ID of the last item on the previous page. The next
+/// page resumes right after that item, so entries inserted or removed before it
+/// do not cause overlaps or skips. The offset is used when the item is gone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageCursor {
+    /// Offset of the first item on the next page
+    pub offset: usize,
+    /// ID of the last item on the previous page
+    pub last_id: String,
+}
+
+impl PageCursor {
+    /// Create a new cursor
+    pub fn new(offset: usize, last_id: String) -> Self {
+        Self { offset, last_id }
+    }
+
+    /// Encode the cursor as an opaque string
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        hex::encode(json)
+    }
+
+    /// Decode a cursor previously produced by [`PageCursor::encode`]
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let bytes = hex::decode(cursor).map_err(|_| anyhow!("Invalid cursor: {}", cursor))?;
+        serde_json::from_slice(&bytes).map_err(|_| anyhow!("Invalid cursor: {}", cursor))
+    }
+
+    /// Resolve the start index of the next page within an ordered ID list
+    pub fn resolve_start<'a, I>(&self, ids: I) -> usize
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        ids.into_iter()
+            .position(|id| id == self.last_id)
+            .map(|pos| pos + 1)
+            .unwrap_or(self.offset)
+    }
+}
+
+/// A single page of results
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    /// Items on this page
+    pub items: Vec<T>,
+    /// Offset of the first item within the full result list
+    pub offset: usize,
+    /// Total number of items across all pages
+    pub total: usize,
+    /// Whether `total` is exact (false when only the leading items were
+    /// ranked and `total` is a lower bound)
+    pub total_exact: bool,
+    /// Cursor for the next page (None when this is the last page)
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Check if more pages follow this one
+    pub fn has_more(&self) -> bool {
+        self.next_cursor.is_some()
+    }
+
+    /// Mark `total` as a lower bound
+    pub fn with_total_exact(mut self, total_exact: bool) -> Self {
+        self.total_exact = total_exact;
+        self
+    }
+
+    /// Total formatted for display, e.g. "12" or "at least 12"
+    pub fn total_label(&self) -> String {
+        if self.total_exact {
+            self.total.to_string()
+        } else {
+            format!("at least {}", self.total)
+        }
+    }
+}
+
+/// Slice an ordered list into a page
+///
+/// A cursor takes precedence over the offset when both are given.
+pub fn paginate<T, F>(
+    items: Vec<T>,
+    id_of: F,
+    offset: usize,
+    cursor: Option<&str>,
+    limit: usize,
+) -> Result<Page<T>>
+where
+    F: Fn(&T) -> &str,
+{
+    let total = items.len();
+    let start = match cursor {
+        Some(cursor) => PageCursor::decode(cursor)?.resolve_start(items.iter().map(&id_of)),
+        None => offset,
+    }
+    .min(total);
+
+    let page_items: Vec<T> = items.into_iter().skip(start).take(limit).collect();
+    let end = start + page_items.len();
+
+    let next_cursor = if end < total {
+        page_items
+            .last()
+            .map(|last| PageCursor::new(end, id_of(last).to_string()).encode())
+    } else {
+        None
+    };
+
+    Ok(Page {
+        items: page_items,
+        offset: start,
+        total,
+        total_exact: true,
+        next_cursor,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("doc{}", i)).collect()
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = PageCursor::new(10, "doc9".to_string());
+        let decoded = PageCursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(decoded, cursor);
+    }
+
+    #[test]
+    fn test_cursor_invalid() {
+        assert!(PageCursor::decode("not-a-cursor").is_err());
+        assert!(PageCursor::decode(&hex::encode("{}")).is_err());
+    }
+
+    #[test]
+    fn test_paginate_with_offset() {
+        let page = paginate(ids(5), |s| s.as_str(), 2, None, 2).unwrap();
+        assert_eq!(page.items, vec!["doc2", "doc3"]);
+        assert_eq!(page.offset, 2);
+        assert_eq!(page.total, 5);
+        assert!(page.has_more());
+    }
+
+    #[test]
+    fn test_paginate_with_cursor_covers_all_items() {
+        let mut seen = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = paginate(ids(7), |s| s.as_str(), 0, cursor.as_deref(), 3).unwrap();
+            seen.extend(page.items);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(seen, ids(7));
+    }
+
+    #[test]
+    fn test_paginate_cursor_survives_insertions() {
+        let first = paginate(ids(4), |s| s.as_str(), 0, None, 2).unwrap();
+        let cursor = first.next_cursor.unwrap();
+
+        // A new item is inserted at the top before the next page is fetched
+        let mut items = vec!["new".to_string()];
+        items.extend(ids(4));
+        let second = paginate(items, |s| s.as_str(), 0, Some(&cursor), 2).unwrap();
+        assert_eq!(second.items, vec!["doc2", "doc3"]);
+        assert!(!second.has_more());
+    }
+
+    #[test]
+    fn test_paginate_past_end() {
+        let page = paginate(ids(2), |s| s.as_str(), 5, None, 3).unwrap();
+        assert!(page.items.is_empty());
+        assert_eq!(page.offset, 2);
+        assert!(!page.has_more());
+    }
+
+    #[test]
+    fn test_total_label() {
+        let page = paginate(ids(5), |s| s.as_str(), 0, None, 2).unwrap();
+        assert!(page.total_exact);
+        assert_eq!(page.total_label(), "5");
+        assert_eq!(page.with_total_exact(false).total_label(), "at least 5");
+    }
+}
@@ -0,0 +1,336 @@
+//! Question answering over retrieved memos
+//!
+//! [`QuestionAnswerer`] completes the RAG loop: memos retrieved by a search
+//! are packed into a token budget as numbered passages, the configured chat
+//! model answers from them, and the `[n]` markers in its answer are mapped
+//! back to document IDs and dates.
+
+use crate::config::app_config::AppConfig;
+use crate::extract::openrouter_client::{
+    ChatCompletionOptions, ChatMessage, OpenRouterClient, UsageStats,
+};
+use crate::extract::summarizer::ProviderConfig;
+use crate::loader::Document;
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Default number of memos retrieved for a question
+pub const DEFAULT_ANSWER_PASSAGES: usize = 8;
+
+/// Default token budget for the passages sent to the model
+pub const DEFAULT_CONTEXT_TOKENS: usize = 3000;
+
+/// Passages are not cut below this many tokens; smaller remainders are dropped
+const MIN_PASSAGE_TOKENS: usize = 50;
+
+/// Citation markers such as `[1]` or `[2, 3]`
+static CITATION_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\[(\d+(?:\s*,\s*\d+)*)\]").expect("Invalid regex"));
+
+const SYSTEM_PROMPT: &str = "You answer questions using only the numbered memo passages \
+provided by the user. Cite every statement with the number of the passage it comes from in \
+square brackets, e.g. [1] or [2, 3]. If the passages do not contain the answer, say so. \
+Answer in the language of the question.";
+
+/// A memo excerpt given to the model as context
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Passage {
+    /// Citation number, starting at 1
+    pub number: usize,
+    /// Document ID
+    pub doc_id: String,
+    /// Document title
+    pub title: String,
+    /// Document date (YYYY-MM-DD)
+    pub date: String,
+    /// Index the document belongs to, when several are searched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    /// Text sent to the model, possibly truncated
+    #[serde(skip)]
+    pub text: String,
+    /// Whether the text was cut to fit the budget
+    pub truncated: bool,
+}
+
+/// An answer with the passages it cites
+#[derive(Debug, Clone, Serialize)]
+pub struct Answer {
+    /// The question asked
+    pub question: String,
+    /// Answer text with `[n]` citation markers
+    pub answer: String,
+    /// Passages cited in the answer, in citation-number order
+    pub citations: Vec<Passage>,
+    /// Every passage sent to the model
+    pub sources: Vec<Passage>,
+    /// Model that produced the answer (None when no memo matched)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Token usage reported by the API
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<UsageStats>,
+}
+
+/// Answers questions from memos with an OpenRouter chat model
+pub struct QuestionAnswerer {
+    client: OpenRouterClient,
+    model: String,
+    max_tokens: usize,
+    temperature: f32,
+    provider_config: Option<ProviderConfig>,
+    context_tokens: usize,
+}
+
+impl QuestionAnswerer {
+    /// Create an answerer using a client and model
+    pub fn new(client: OpenRouterClient, model: impl Into<String>) -> Self {
+        Self {
+            client,
+            model: model.into(),
+            max_tokens: 500,
+            temperature: 0.3,
+            provider_config: None,
+            context_tokens: DEFAULT_CONTEXT_TOKENS,
+        }
+    }
+
+    /// Create an answerer with the summarization model settings of config.toml
+    ///
+    /// Fails when no OpenRouter API key is configured.
+    pub fn from_config(config: &AppConfig) -> Result<Self> {
+        let api_key = config.openrouter_api_key().ok_or_else(|| {
+            anyhow!("Answering questions requires an OpenRouter API key (OPENROUTER_API_KEY)")
+        })?;
+        Ok(
+            Self::new(OpenRouterClient::new(api_key), config.summarization_model())
+                .with_max_tokens(config.summarization_max_tokens())
+                .with_temperature(config.summarization_temperature())
+                .with_provider_config(Some(ProviderConfig::from_app_config(config))),
+        )
+    }
+
+    /// Set the maximum tokens of the answer
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Set the sampling temperature
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
+    /// Set the OpenRouter provider routing
+    pub fn with_provider_config(mut self, provider_config: Option<ProviderConfig>) -> Self {
+        self.provider_config = provider_config;
+        self
+    }
+
+    /// Set the token budget for passages
+    pub fn with_context_tokens(mut self, context_tokens: usize) -> Self {
+        self.context_tokens = context_tokens;
+        self
+    }
+
+    /// Pack retrieved documents into numbered passages within the budget
+    ///
+    /// Documents are taken in order, each with the index it came from. The
+    /// first document that does not fit is truncated to the remaining budget
+    /// and packing stops there.
+    pub fn pack<'a, I>(&self, docs: I) -> Vec<Passage>
+    where
+        I: IntoIterator<Item = (&'a Document, Option<&'a str>)>,
+    {
+        let mut passages = Vec::new();
+        let mut remaining = self.context_tokens;
+
+        for (doc, index) in docs {
+            let cost = estimate_tokens(&doc.text);
+            let (text, truncated) = if cost <= remaining {
+                (doc.text.clone(), false)
+            } else if remaining >= MIN_PASSAGE_TOKENS || passages.is_empty() {
+                (truncate_to_tokens(&doc.text, remaining), true)
+            } else {
+                break;
+            };
+            remaining = remaining.saturating_sub(estimate_tokens(&text));
+
+            passages.push(Passage {
+                number: passages.len() + 1,
+                doc_id: doc.id.clone(),
+                title: doc.title().to_string(),
+                date: doc.date().format("%Y-%m-%d").to_string(),
+                index: index.map(String::from),
+                text,
+                truncated,
+            });
+            if truncated {
+                break;
+            }
+        }
+        passages
+    }
+
+    /// Ask the model to answer a question from packed passages
+    ///
+    /// Without passages no request is made and the answer says that no
+    /// memo matched.
+    pub async fn answer(&self, question: &str, passages: Vec<Passage>) -> Result<Answer> {
+        if passages.is_empty() {
+            return Ok(Answer {
+                question: question.to_string(),
+                answer: "No memos matched the question.".to_string(),
+                citations: Vec::new(),
+                sources: passages,
+                model: None,
+                usage: None,
+            });
+        }
+
+        let messages = vec![
+            ChatMessage::system(SYSTEM_PROMPT),
+            ChatMessage::user(render_prompt(question, &passages)),
+        ];
+        let options = ChatCompletionOptions {
+            max_tokens: Some(self.max_tokens),
+            temperature: Some(self.temperature),
+            top_p: None,
+            provider_config: self.provider_config.clone(),
+        };
+        let response = self
+            .client
+            .chat_completion(&self.model, messages, options)
+            .await?;
+
+        let cited = cited_numbers(&response.content);
+        let citations = passages
+            .iter()
+            .filter(|p| cited.contains(&p.number))
+            .cloned()
+            .collect();
+
+        Ok(Answer {
+            question: question.to_string(),
+            answer: response.content,
+            citations,
+            sources: passages,
+            model: Some(response.model),
+            usage: response.usage,
+        })
+    }
+}
+
+/// Rough token count: about four ASCII characters per token, and one token
+/// per character for other scripts such as Japanese
+pub fn estimate_tokens(text: &str) -> usize {
+    let (ascii, other) = text.chars().fold((0usize, 0usize), |(ascii, other), c| {
+        if c.is_ascii() {
+            (ascii + 1, other)
+        } else {
+            (ascii, other + 1)
+        }
+    });
+    ascii.div_ceil(4) + other
+}
+
+/// Longest prefix of `text` estimated to fit in `tokens`
+fn truncate_to_tokens(text: &str, tokens: usize) -> String {
+    let (mut ascii, mut other) = (0usize, 0usize);
+    let mut end = 0;
+    for (i, c) in text.char_indices() {
+        if c.is_ascii() {
+            ascii += 1;
+        } else {
+            other += 1;
+        }
+        if ascii.div_ceil(4) + other > tokens {
+            break;
+        }
+        end = i + c.len_utf8();
+    }
+    text[..end].to_string()
+}
+
+/// Numbered passages followed by the question
+fn render_prompt(question: &str, passages: &[Passage]) -> String {
+    let mut prompt = String::from("Memo passages:\n\n");
+    for passage in passages {
+        prompt.push_str(&format!(
+            "[{}] {} ({})\n{}\n\n",
+            passage.number,
+            passage.title,
+            passage.date,
+            passage.text.trim()
+        ));
+    }
+    prompt.push_str(&format!("Question: {}", question));
+    prompt
+}
+
+/// Passage numbers cited with `[n]` or `[n, m]` markers
+fn cited_numbers(answer: &str) -> BTreeSet<usize> {
+    CITATION_PATTERN
+        .captures_iter(answer)
+        .flat_map(|caps| {
+            caps[1]
+                .split(',')
+                .filter_map(|n| n.trim().parse().ok())
+                .collect::<Vec<usize>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn doc(id: &str, text: &str) -> Document {
+        Document::with_id(
+            id.to_string(),
+            format!("Memo {}", id),
+            Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap(),
+            vec![],
+            text.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcdefgh"), 2);
+        assert_eq!(estimate_tokens("abc日本語"), 4);
+        assert_eq!(truncate_to_tokens("abcdefgh日本", 3), "abcdefgh日");
+    }
+
+    #[test]
+    fn test_pack_respects_budget() {
+        let docs = [
+            doc("a", &"a".repeat(480)),
+            doc("b", &"b".repeat(480)),
+            doc("c", &"c".repeat(480)),
+        ];
+        let answerer =
+            QuestionAnswerer::new(OpenRouterClient::new("key"), "model").with_context_tokens(200);
+
+        let passages = answerer.pack(docs.iter().map(|d| (d, None)));
+        assert_eq!(passages.len(), 2);
+        assert_eq!(passages[0].number, 1);
+        assert!(!passages[0].truncated);
+        assert_eq!(passages[1].doc_id, "b");
+        assert!(passages[1].truncated);
+        assert_eq!(estimate_tokens(&passages[1].text), 80);
+        assert_eq!(passages[1].date, "2025-03-01");
+    }
+
+    #[test]
+    fn test_cited_numbers() {
+        let cited = cited_numbers("Deployed on Monday [1]. Rolled back [2, 4][3]. See [x].");
+        assert_eq!(cited.into_iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,127 @@
+//! Facet counts
+//!
+//! [`Facets`] summarises how the matches of a query are distributed across
+//! tags, title categories and months, so that a client can narrow a search
+//! down with a filter. Counts cover every match, not just the returned page.
+
+use crate::index::Period;
+use crate::loader::Document;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// Number of documents sharing one facet value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FacetCount {
+    /// Tag, category or month ("YYYY-MM")
+    pub value: String,
+    /// Number of matching documents
+    pub count: usize,
+}
+
+/// Distribution of matching documents
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Facets {
+    /// Authored and inferred tags, most frequent first
+    pub tags: Vec<FacetCount>,
+    /// Categories of hierarchical titles ("Category / Subject"), most
+    /// frequent first
+    pub categories: Vec<FacetCount>,
+    /// Months, oldest first
+    pub months: Vec<FacetCount>,
+}
+
+impl Facets {
+    /// Count the facet values of a set of documents
+    ///
+    /// Documents with flat titles have no category: their whole title would
+    /// otherwise become a category of its own.
+    pub fn from_documents<'a, I>(docs: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Document>,
+    {
+        let mut tags: HashMap<&str, usize> = HashMap::new();
+        let mut categories: HashMap<&str, usize> = HashMap::new();
+        let mut months: HashMap<String, usize> = HashMap::new();
+
+        for doc in docs {
+            let doc_tags: BTreeSet<&str> = doc
+                .tags()
+                .iter()
+                .chain(doc.inferred_tags())
+                .map(String::as_str)
+                .collect();
+            for tag in doc_tags {
+                *tags.entry(tag).or_insert(0) += 1;
+            }
+            if let (Some(category), Some(_)) = (doc.category(), doc.subcategory()) {
+                *categories.entry(category).or_insert(0) += 1;
+            }
+            let month = Period::Month.start_of(doc.date().date_naive());
+            *months.entry(Period::Month.label(month)).or_insert(0) += 1;
+        }
+
+        let mut months: Vec<FacetCount> = months
+            .into_iter()
+            .map(|(value, count)| FacetCount { value, count })
+            .collect();
+        months.sort_by(|a, b| a.value.cmp(&b.value));
+
+        Self {
+            tags: by_frequency(tags),
+            categories: by_frequency(categories),
+            months,
+        }
+    }
+
+    /// Check if no document was counted
+    pub fn is_empty(&self) -> bool {
+        self.months.is_empty()
+    }
+}
+
+/// Sort counts by frequency, then value
+fn by_frequency(counts: HashMap<&str, usize>) -> Vec<FacetCount> {
+    let mut counts: Vec<FacetCount> = counts
+        .into_iter()
+        .map(|(value, count)| FacetCount {
+            value: value.to_string(),
+            count,
+        })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn doc(title: &str, month: u32, tags: &[&str]) -> Document {
+        Document::new(
+            title.to_string(),
+            Utc.with_ymd_and_hms(2025, month, 10, 9, 0, 0).unwrap(),
+            tags.iter().map(|t| t.to_string()).collect(),
+            String::new(),
+        )
+    }
+
+    fn counts(facets: &[FacetCount]) -> Vec<(&str, usize)> {
+        facets.iter().map(|f| (f.value.as_str(), f.count)).collect()
+    }
+
+    #[test]
+    fn test_facets_from_documents() {
+        let docs = [
+            doc("Rust / lifetimes", 3, &["rust", "memo"]),
+            doc("Rust / traits", 1, &["rust"]),
+            doc("Weekly sync", 3, &["memo", "memo"]),
+        ];
+        let facets = Facets::from_documents(&docs);
+
+        assert_eq!(counts(&facets.tags), vec![("memo", 2), ("rust", 2)]);
+        assert_eq!(counts(&facets.categories), vec![("Rust", 2)]);
+        assert_eq!(counts(&facets.months), vec![("2025-01", 1), ("2025-03", 2)]);
+        assert!(Facets::from_documents(&[]).is_empty());
+    }
+}
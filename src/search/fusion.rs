@@ -4,11 +4,13 @@
 
 use super::SearchResult;
 use std::collections::HashMap;
+use std::hash::Hash;
 
 /// RRF constant (typically 60)
 const RRF_K: f32 = 60.0;
 
 /// Reciprocal Rank Fusion for combining search results
+#[derive(Clone)]
 pub struct ReciprocalRankFusion {
     /// RRF constant k
     k: f32,
@@ -96,6 +98,35 @@ impl ReciprocalRankFusion {
 
         results
     }
+
+    /// Fuse any number of ranked lists whose items are identified by `key`
+    ///
+    /// Each item keeps its first occurrence and is scored with the sum of its
+    /// RRF scores across lists. Ties keep the order items were first seen in.
+    pub fn fuse_lists<T, K, F>(&self, lists: Vec<Vec<T>>, key: F) -> Vec<(T, f32)>
+    where
+        K: Eq + Hash,
+        F: Fn(&T) -> K,
+    {
+        let mut positions: HashMap<K, usize> = HashMap::new();
+        let mut fused: Vec<(T, f32)> = Vec::new();
+
+        for list in lists {
+            for (rank, item) in list.into_iter().enumerate() {
+                let rrf_score = 1.0 / (self.k + (rank + 1) as f32);
+                match positions.get(&key(&item)) {
+                    Some(&pos) => fused[pos].1 += rrf_score,
+                    None => {
+                        positions.insert(key(&item), fused.len());
+                        fused.push((item, rrf_score));
+                    }
+                }
+            }
+        }
+
+        fused.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        fused
+    }
 }
 
 #[cfg(test)]
@@ -146,6 +177,20 @@ mod tests {
         assert!(doc2_score.unwrap() > doc4_score.unwrap());
     }
 
+    #[test]
+    fn test_rrf_fuse_lists() {
+        let rrf = ReciprocalRankFusion::new();
+        let fused = rrf.fuse_lists(vec![vec!["a", "b"], vec!["c", "a"], vec!["d"]], |item| {
+            *item
+        });
+
+        let order: Vec<&str> = fused.iter().map(|(item, _)| *item).collect();
+        // "a" appears in two lists; first-ranked items of other lists tie
+        assert_eq!(order, vec!["a", "c", "d", "b"]);
+        assert!((fused[0].1 - (1.0 / 61.0 + 1.0 / 62.0)).abs() < 1e-6);
+        assert!((fused[1].1 - fused[2].1).abs() < 1e-6);
+    }
+
     #[test]
     fn test_rrf_fusion_empty() {
         let rrf = ReciprocalRankFusion::new();
@@ -0,0 +1,160 @@
+//! Maximal Marginal Relevance implementation
+//!
+//! Re-ranks search results to trade relevance against redundancy.
+
+use super::SearchResult;
+
+/// Default MMR lambda (1.0 = pure relevance, 0.0 = pure diversity)
+pub const DEFAULT_MMR_LAMBDA: f32 = 0.7;
+
+/// Maximal Marginal Relevance re-ranker
+pub struct MaximalMarginalRelevance {
+    /// Trade-off between relevance and diversity (0.0 to 1.0)
+    lambda: f32,
+}
+
+impl Default for MaximalMarginalRelevance {
+    fn default() -> Self {
+        Self::new(DEFAULT_MMR_LAMBDA)
+    }
+}
+
+impl MaximalMarginalRelevance {
+    /// Create a new MMR instance, clamping lambda to [0.0, 1.0]
+    pub fn new(lambda: f32) -> Self {
+        Self {
+            lambda: lambda.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Get the lambda value
+    pub fn lambda(&self) -> f32 {
+        self.lambda
+    }
+
+    /// Re-rank candidates and return at most `top_k` results
+    ///
+    /// MMR score = lambda * relevance - (1 - lambda) * max(similarity to selected)
+    ///
+    /// Relevance is the candidate score normalized by the best score in the list.
+    /// `similarity` receives two candidate indices and returns a value in [0.0, 1.0].
+    /// Returned results keep their original relevance scores.
+    pub fn rerank<F>(
+        &self,
+        candidates: Vec<SearchResult>,
+        top_k: usize,
+        similarity: F,
+    ) -> Vec<SearchResult>
+    where
+        F: Fn(usize, usize) -> f32,
+    {
+        if candidates.is_empty() || top_k == 0 {
+            return Vec::new();
+        }
+
+        let max_score = candidates
+            .iter()
+            .map(|r| r.score)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let relevance: Vec<f32> = candidates
+            .iter()
+            .map(|r| {
+                if max_score > 0.0 {
+                    r.score / max_score
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        let mut selected: Vec<usize> = Vec::with_capacity(top_k.min(candidates.len()));
+        let mut remaining: Vec<usize> = (0..candidates.len()).collect();
+        // Highest similarity of each candidate to anything already selected
+        let mut max_sim = vec![0.0f32; candidates.len()];
+
+        while selected.len() < top_k && !remaining.is_empty() {
+            let mut best_pos = 0;
+            let mut best_score = f32::NEG_INFINITY;
+
+            // Ties keep the earlier (higher-ranked) candidate
+            for (pos, &idx) in remaining.iter().enumerate() {
+                let mmr_score = self.lambda * relevance[idx] - (1.0 - self.lambda) * max_sim[idx];
+                if mmr_score > best_score {
+                    best_score = mmr_score;
+                    best_pos = pos;
+                }
+            }
+
+            let chosen = remaining.remove(best_pos);
+            selected.push(chosen);
+
+            for &idx in &remaining {
+                let sim = similarity(chosen, idx);
+                if sim > max_sim[idx] {
+                    max_sim[idx] = sim;
+                }
+            }
+        }
+
+        let mut slots: Vec<Option<SearchResult>> = candidates.into_iter().map(Some).collect();
+        selected
+            .into_iter()
+            .filter_map(|idx| slots[idx].take())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates() -> Vec<SearchResult> {
+        vec![
+            SearchResult::new("doc1".to_string(), 1.0),
+            SearchResult::new("doc1-copy".to_string(), 0.99),
+            SearchResult::new("doc2".to_string(), 0.8),
+        ]
+    }
+
+    /// doc1 and doc1-copy are identical, doc2 is unrelated
+    fn similarity(a: usize, b: usize) -> f32 {
+        if a == b || (a < 2 && b < 2) {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    #[test]
+    fn test_mmr_lambda_clamped() {
+        assert!((MaximalMarginalRelevance::new(1.5).lambda() - 1.0).abs() < 1e-6);
+        assert!(MaximalMarginalRelevance::new(-0.5).lambda().abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mmr_pure_relevance_keeps_order() {
+        let mmr = MaximalMarginalRelevance::new(1.0);
+        let results = mmr.rerank(candidates(), 3, similarity);
+
+        let ids: Vec<&str> = results.iter().map(|r| r.doc_id.as_str()).collect();
+        assert_eq!(ids, vec!["doc1", "doc1-copy", "doc2"]);
+    }
+
+    #[test]
+    fn test_mmr_demotes_duplicates() {
+        let mmr = MaximalMarginalRelevance::new(0.5);
+        let results = mmr.rerank(candidates(), 2, similarity);
+
+        let ids: Vec<&str> = results.iter().map(|r| r.doc_id.as_str()).collect();
+        assert_eq!(ids, vec!["doc1", "doc2"]);
+        // Original relevance scores are preserved
+        assert!((results[1].score - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mmr_empty() {
+        let mmr = MaximalMarginalRelevance::default();
+        assert!(mmr.rerank(Vec::new(), 5, similarity).is_empty());
+        assert!(mmr.rerank(candidates(), 0, similarity).is_empty());
+    }
+}
@@ -2,12 +2,39 @@
 //!
 //! This module provides the main search functionality and result types.
 
+mod answer;
+mod facets;
 mod fusion;
+mod mmr;
+mod multi;
+mod reload;
 mod searcher;
+mod tagging;
+mod write;
 
+pub use crate::pagination::{paginate, Page, PageCursor};
+pub use answer::{
+    estimate_tokens, Answer, Passage, QuestionAnswerer, DEFAULT_ANSWER_PASSAGES,
+    DEFAULT_CONTEXT_TOKENS,
+};
+pub use facets::{FacetCount, Facets};
 pub use fusion::ReciprocalRankFusion;
-pub use searcher::Searcher;
+pub use mmr::{MaximalMarginalRelevance, DEFAULT_MMR_LAMBDA};
+pub use multi::{
+    IndexSelection, IndexSelector, IndexSet, IndexedDocument, IndexedResult, ALL_INDEXES,
+};
+pub use reload::{
+    index_timestamp, IndexReloader, SharedIndexes, DEFAULT_RELOAD_INTERVAL, METADATA_FILE,
+};
+pub use searcher::{Searcher, MIN_MATCH_SIMILARITY};
+pub use tagging::{
+    SuggestedTag, TagSuggester, TagSuggestion, DEFAULT_MAX_SUGGESTED_TAGS, DEFAULT_MIN_CONFIDENCE,
+    DEFAULT_SUGGEST_NEIGHBORS,
+};
+pub use write::MemoWriter;
 
+use crate::extract::summarizer::SummaryUsage;
+use crate::extract::ExtractedContent;
 use serde::{Deserialize, Serialize};
 
 /// Extracted content with summary
@@ -15,8 +42,12 @@ use serde::{Deserialize, Serialize};
 pub struct ExtractedResult {
     /// Summary text (rule-based or LLM generated)
     pub summary: Option<String>,
-    /// Summarization method: "rule-based" or "llm"
+    /// Summarization method: "rule-based", "extractive" or "llm"
     pub summary_method: Option<String>,
+    /// Model that wrote the summary (LLM summaries only)
+    pub summary_model: Option<String>,
+    /// Tokens spent on the summary (None for offline and cached summaries)
+    pub summary_usage: Option<SummaryUsage>,
     /// Raw extracted content
     pub raw_content: Option<String>,
     /// Whether content was truncated
@@ -27,6 +58,22 @@ pub struct ExtractedResult {
     pub extracted_chars: usize,
 }
 
+impl From<ExtractedContent> for ExtractedResult {
+    /// Wrap extracted content without a summary
+    fn from(content: ExtractedContent) -> Self {
+        Self {
+            summary: None,
+            summary_method: None,
+            summary_model: None,
+            summary_usage: None,
+            raw_content: Some(content.text),
+            truncated: content.truncated,
+            total_chars: content.stats.total_chars,
+            extracted_chars: content.stats.extracted_chars,
+        }
+    }
+}
+
 /// Search result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
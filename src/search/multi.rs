@@ -0,0 +1,687 @@
+//! Multi-index search
+//!
+//! Serves several named indexes (e.g. a personal changelog, a team wiki and
+//! meeting notes) from one process. Scores from different corpora are not
+//! comparable, so results across indexes are merged with RRF.
+
+use super::{paginate, Facets, Page, ReciprocalRankFusion, SearchResult, Searcher};
+use crate::config::{SearchConfig, SearchMode};
+use crate::index::{Docstore, TagCount, Timeline, TimelineBucket, TopicModel};
+use crate::loader::Document;
+use anyhow::{anyhow, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Selector value that picks every index
+pub const ALL_INDEXES: &str = "all";
+
+/// Which indexes a request targets: a name, a list of names, or "all"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum IndexSelector {
+    /// A single index name, or "all"
+    One(String),
+    /// Several index names
+    Many(Vec<String>),
+}
+
+/// Named searchers loaded together
+///
+/// Indexes keep their declaration order; the first one is the default for
+/// features that work on a single index.
+#[derive(Clone)]
+pub struct IndexSet {
+    indexes: Vec<(String, Arc<Searcher>)>,
+}
+
+/// A subset of an [`IndexSet`] selected for one request
+pub struct IndexSelection<'a> {
+    indexes: Vec<(&'a str, &'a Searcher)>,
+}
+
+/// A search result tagged with the index it came from
+#[derive(Clone)]
+pub struct IndexedResult<'a> {
+    /// Index name
+    pub index: &'a str,
+    /// Searcher of that index
+    pub searcher: &'a Searcher,
+    /// The result itself (RRF score when several indexes were searched)
+    pub result: SearchResult,
+}
+
+/// A document tagged with the index it came from
+#[derive(Clone, Copy)]
+pub struct IndexedDocument<'a> {
+    /// Index name
+    pub index: &'a str,
+    /// Searcher of that index
+    pub searcher: &'a Searcher,
+    /// The document
+    pub doc: &'a Document,
+}
+
+impl IndexSet {
+    /// Create a set holding a single (default) index
+    pub fn new(name: impl Into<String>, searcher: Searcher) -> Self {
+        Self {
+            indexes: vec![(name.into(), Arc::new(searcher))],
+        }
+    }
+
+    /// Add another named index
+    pub fn add(&mut self, name: impl Into<String>, searcher: Searcher) -> Result<()> {
+        let name = name.into();
+        if name == ALL_INDEXES {
+            return Err(anyhow!("'{}' is reserved and cannot name an index", name));
+        }
+        if self.get(&name).is_some() {
+            return Err(anyhow!("Duplicate index name: {}", name));
+        }
+        self.indexes.push((name, Arc::new(searcher)));
+        Ok(())
+    }
+
+    /// Copy of the set with one index replaced by a newly loaded searcher
+    ///
+    /// The other searchers are shared with this set, so holders of the old
+    /// set keep a consistent view until they drop it.
+    pub fn with_searcher(&self, name: &str, searcher: Searcher) -> Result<Self> {
+        let mut replaced = self.clone();
+        let slot = replaced
+            .indexes
+            .iter_mut()
+            .find(|(n, _)| n == name)
+            .ok_or_else(|| anyhow!("Unknown index: {}", name))?;
+        slot.1 = Arc::new(searcher);
+        Ok(replaced)
+    }
+
+    /// Number of indexes
+    pub fn len(&self) -> usize {
+        self.indexes.len()
+    }
+
+    /// Check if the set has no indexes (never true once constructed)
+    pub fn is_empty(&self) -> bool {
+        self.indexes.is_empty()
+    }
+
+    /// Index names in declaration order
+    pub fn names(&self) -> Vec<&str> {
+        self.indexes.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Get an index by name
+    pub fn get(&self, name: &str) -> Option<&Searcher> {
+        self.indexes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, searcher)| searcher.as_ref())
+    }
+
+    /// The default (first declared) index
+    pub fn primary(&self) -> &Searcher {
+        &self.indexes[0].1
+    }
+
+    /// The named index, or the primary one when no name is given
+    pub fn resolve(&self, name: Option<&str>) -> Result<&Searcher> {
+        match name {
+            None => Ok(self.primary()),
+            Some(name) => self.get(name).ok_or_else(|| {
+                anyhow!(
+                    "Unknown index '{}'. Available indexes: {}",
+                    name,
+                    self.names().join(", ")
+                )
+            }),
+        }
+    }
+
+    /// Document stores of every index, in declaration order
+    pub fn docstores(&self) -> Vec<&Docstore> {
+        self.indexes
+            .iter()
+            .map(|(_, searcher)| searcher.docstore())
+            .collect()
+    }
+
+    /// Select indexes for a request; `None` selects all of them
+    pub fn select(&self, selector: Option<&IndexSelector>) -> Result<IndexSelection<'_>> {
+        let names: Vec<&str> = match selector {
+            None => return Ok(self.select_all()),
+            Some(IndexSelector::One(name)) if name == ALL_INDEXES => return Ok(self.select_all()),
+            Some(IndexSelector::One(name)) => vec![name.as_str()],
+            Some(IndexSelector::Many(names)) if names.iter().any(|n| n == ALL_INDEXES) => {
+                return Ok(self.select_all())
+            }
+            Some(IndexSelector::Many(names)) if names.is_empty() => return Ok(self.select_all()),
+            Some(IndexSelector::Many(names)) => names.iter().map(String::as_str).collect(),
+        };
+
+        let mut indexes: Vec<(&str, &Searcher)> = Vec::with_capacity(names.len());
+        for name in names {
+            let (name, searcher) =
+                self.indexes
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "Unknown index '{}'. Available indexes: {}",
+                            name,
+                            self.names().join(", ")
+                        )
+                    })?;
+            if !indexes.iter().any(|(n, _)| *n == name) {
+                indexes.push((name.as_str(), searcher.as_ref()));
+            }
+        }
+        Ok(IndexSelection { indexes })
+    }
+
+    fn select_all(&self) -> IndexSelection<'_> {
+        IndexSelection {
+            indexes: self
+                .indexes
+                .iter()
+                .map(|(name, searcher)| (name.as_str(), searcher.as_ref()))
+                .collect(),
+        }
+    }
+}
+
+impl<'a> IndexSelection<'a> {
+    /// Whether more than one index is selected
+    pub fn is_multi(&self) -> bool {
+        self.indexes.len() > 1
+    }
+
+    /// Selected index names
+    pub fn names(&self) -> Vec<&'a str> {
+        self.indexes.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Build timestamps of the selected indexes that have one, by name
+    pub fn timestamps(&self) -> BTreeMap<String, String> {
+        self.indexes
+            .iter()
+            .filter_map(|(name, searcher)| {
+                searcher
+                    .created_at()
+                    .map(|created_at| (name.to_string(), created_at.to_string()))
+            })
+            .collect()
+    }
+
+    /// Check if any selected index has a vector index
+    pub fn has_vector_index(&self) -> bool {
+        self.indexes.iter().any(|(_, s)| s.has_vector_index())
+    }
+
+    /// Search the selected indexes and return a single page
+    ///
+    /// A single index is searched exactly like [`Searcher::search_page`].
+    /// Several indexes are ranked separately and fused with RRF; ties are
+    /// broken by date (newest first), then index order, then doc_id.
+    pub fn search_page(
+        &self,
+        query: &str,
+        config: &SearchConfig,
+    ) -> Result<Page<IndexedResult<'a>>> {
+        if let [(index, searcher)] = self.indexes[..] {
+            let page = searcher.search_page(query, config)?;
+            return Ok(map_page(page, |result| IndexedResult {
+                index,
+                searcher,
+                result,
+            }));
+        }
+
+        let keyed = self.fuse(query, config)?;
+        paginate_keyed(keyed, config.offset, config.cursor.as_deref(), config.top_k)
+    }
+
+    /// Search the selected indexes and return a single page with facet
+    /// counts over every match
+    ///
+    /// As with [`Searcher::search_page_with_facets`], facets count the
+    /// [`Searcher::matches`] of each index in the configured search mode.
+    pub fn search_page_with_facets(
+        &self,
+        query: &str,
+        config: &SearchConfig,
+    ) -> Result<(Page<IndexedResult<'a>>, Facets)> {
+        if let [(index, searcher)] = self.indexes[..] {
+            let (page, facets) = searcher.search_page_with_facets(query, config)?;
+            let page = map_page(page, |result| IndexedResult {
+                index,
+                searcher,
+                result,
+            });
+            return Ok((page, facets));
+        }
+
+        let mut matched = Vec::new();
+        for (_, searcher) in &self.indexes {
+            for result in searcher.matches(query, config)? {
+                matched.extend(searcher.docstore().get(&result.doc_id));
+            }
+        }
+        let facets = Facets::from_documents(matched);
+        let keyed = self.fuse(query, config)?;
+        let page = paginate_keyed(keyed, config.offset, config.cursor.as_deref(), config.top_k)?;
+        Ok((page, facets))
+    }
+
+    /// Rank each selected index and fuse the rankings, keyed by "index/doc_id"
+    fn fuse(&self, query: &str, config: &SearchConfig) -> Result<Vec<(String, IndexedResult<'a>)>> {
+        self.fuse_with(|searcher| searcher.rank(query, config))
+    }
+
+    /// Fuse per-index rankings produced by `rank`, keyed by "index/doc_id"
+    fn fuse_with<F>(&self, rank: F) -> Result<Vec<(String, IndexedResult<'a>)>>
+    where
+        F: Fn(&Searcher) -> Result<Vec<SearchResult>>,
+    {
+        let mut lists = Vec::with_capacity(self.indexes.len());
+        for (position, (index, searcher)) in self.indexes.iter().enumerate() {
+            let ranked = rank(searcher)?;
+            lists.push(
+                ranked
+                    .into_iter()
+                    .map(|result| (position, *index, *searcher, result))
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        let mut fused = ReciprocalRankFusion::new().fuse_lists(lists, |(_, index, _, result)| {
+            (*index, result.doc_id.clone())
+        });
+        let date_of = |searcher: &Searcher, doc_id: &str| {
+            searcher.docstore().get(doc_id).map(|doc| doc.date())
+        };
+        fused.sort_by(|((pa, _, sa, ra), a), ((pb, _, sb, rb), b)| {
+            b.partial_cmp(a)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| date_of(sb, &rb.doc_id).cmp(&date_of(sa, &ra.doc_id)))
+                .then_with(|| pa.cmp(pb))
+                .then_with(|| ra.doc_id.cmp(&rb.doc_id))
+        });
+
+        Ok(fused
+            .into_iter()
+            .map(|((_, index, searcher, mut result), score)| {
+                result.score = score;
+                (
+                    format!("{}/{}", index, result.doc_id),
+                    IndexedResult {
+                        index,
+                        searcher,
+                        result,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Get a page of the most recent memos across the selected indexes
+    pub fn recent_page(
+        &self,
+        offset: usize,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<Page<IndexedDocument<'a>>> {
+        if let [(index, searcher)] = self.indexes[..] {
+            let page = searcher.get_recent_memos_page(offset, cursor, limit)?;
+            return Ok(map_page(page, |doc| IndexedDocument {
+                index,
+                searcher,
+                doc,
+            }));
+        }
+
+        let mut docs: Vec<(usize, IndexedDocument<'a>)> = Vec::new();
+        for (position, (index, searcher)) in self.indexes.iter().enumerate() {
+            let docstore = searcher.docstore();
+            docs.extend(docstore.get_recent(docstore.len()).into_iter().map(|doc| {
+                (
+                    position,
+                    IndexedDocument {
+                        index,
+                        searcher,
+                        doc,
+                    },
+                )
+            }));
+        }
+        docs.sort_by(|(pa, a), (pb, b)| {
+            b.doc
+                .date()
+                .cmp(&a.doc.date())
+                .then_with(|| pa.cmp(pb))
+                .then_with(|| a.doc.id.cmp(&b.doc.id))
+        });
+
+        let keyed = docs
+            .into_iter()
+            .map(|(_, item)| (format!("{}/{}", item.index, item.doc.id), item))
+            .collect();
+        paginate_keyed(keyed, offset, cursor, limit)
+    }
+
+    /// Authored and inferred tag counts summed across the selected indexes
+    pub fn tag_counts(&self) -> Vec<TagCount> {
+        let mut counts: BTreeMap<String, TagCount> = BTreeMap::new();
+        for (_, searcher) in &self.indexes {
+            for count in searcher.docstore().tag_counts() {
+                let total = counts.entry(count.tag.clone()).or_insert(TagCount {
+                    tag: count.tag,
+                    ..TagCount::default()
+                });
+                total.authored += count.authored;
+                total.inferred += count.inferred;
+            }
+        }
+        counts.into_values().collect()
+    }
+
+    /// Build a timeline of the memos matching a query
+    ///
+    /// With an empty query every memo passing the tag and topic filters is
+    /// counted and each period lists its newest memos; otherwise the memos
+    /// containing the query terms are counted and each period lists the most
+    /// relevant ones. Query timelines count the lexical matches of
+    /// [`Searcher::lexical_matches`] in every search mode, since a semantic
+    /// search would count almost the whole corpus; the search mode only
+    /// decides which matches are listed first.
+    pub fn timeline(
+        &self,
+        query: &str,
+        config: &SearchConfig,
+        timeline: &Timeline,
+    ) -> Result<Vec<TimelineBucket>> {
+        let total = self.indexes.iter().map(|(_, s)| s.docstore().len()).sum();
+        let docs: Vec<&'a Document> = if query.trim().is_empty() {
+            self.recent_page(0, None, total)?
+                .items
+                .into_iter()
+                .filter(|item| {
+                    config
+                        .tag_filter
+                        .as_deref()
+                        .is_none_or(|tag| item.doc.has_tag(tag))
+                        && config
+                            .topic_filter
+                            .is_none_or(|topic| item.searcher.topic_of(&item.doc.id) == Some(topic))
+                })
+                .map(|item| item.doc)
+                .collect()
+        } else {
+            self.fuse_with(|searcher| timeline_matches(searcher, query, config))?
+                .into_iter()
+                .filter_map(|(_, hit)| hit.searcher.docstore().get(&hit.result.doc_id))
+                .collect()
+        };
+        Ok(timeline.build(docs))
+    }
+
+    /// Topic models of the selected indexes that have one, in index order
+    pub fn topics(&self) -> Vec<(&'a str, &'a TopicModel)> {
+        self.indexes
+            .iter()
+            .filter_map(|(name, searcher)| searcher.topics().map(|topics| (*name, topics)))
+            .collect()
+    }
+
+    /// Find a document by ID in the first selected index that has it
+    pub fn find(&self, doc_id: &str) -> Option<IndexedDocument<'a>> {
+        self.indexes.iter().find_map(|(index, searcher)| {
+            searcher.docstore().get(doc_id).map(|doc| IndexedDocument {
+                index,
+                searcher,
+                doc,
+            })
+        })
+    }
+}
+
+/// Lexical matches of a query, ordered by the configured search mode
+///
+/// Matches the mode does not rank (e.g. memos without a vector) keep their
+/// BM25 order after the ranked ones.
+fn timeline_matches(
+    searcher: &Searcher,
+    query: &str,
+    config: &SearchConfig,
+) -> Result<Vec<SearchResult>> {
+    let mut matches = searcher.lexical_matches(query, config)?;
+    if config.search_mode != SearchMode::Bm25 {
+        let ranked = searcher.rank(query, config)?;
+        let position: HashMap<&str, usize> = ranked
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (r.doc_id.as_str(), i))
+            .collect();
+        matches.sort_by_key(|r| {
+            position
+                .get(r.doc_id.as_str())
+                .copied()
+                .unwrap_or(usize::MAX)
+        });
+    }
+    Ok(matches)
+}
+
+/// Convert the items of a page, keeping its position and cursor
+fn map_page<T, U>(page: Page<T>, f: impl FnMut(T) -> U) -> Page<U> {
+    Page {
+        items: page.items.into_iter().map(f).collect(),
+        offset: page.offset,
+        total: page.total,
+        total_exact: page.total_exact,
+        next_cursor: page.next_cursor,
+    }
+}
+
+/// Paginate items keyed by "index/doc_id" so cursors are unique across indexes
+fn paginate_keyed<T>(
+    keyed: Vec<(String, T)>,
+    offset: usize,
+    cursor: Option<&str>,
+    limit: usize,
+) -> Result<Page<T>> {
+    let page = paginate(keyed, |(key, _)| key.as_str(), offset, cursor, limit)?;
+    Ok(map_page(page, |(_, item)| item))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    /// Write a BM25-only index where every document contains the token "rust"
+    fn write_index(dir: &std::path::Path, docs: &[(&str, u32)]) {
+        let mut docstore = Docstore::new();
+        for (id, day) in docs {
+            docstore.add(Document::with_id(
+                id.to_string(),
+                format!("Memo {}", id),
+                Utc.with_ymd_and_hms(2025, 1, *day, 9, 0, 0).unwrap(),
+                vec![format!("tag-{}", id), "shared".to_string()],
+                format!("rust memo {}", id),
+            ));
+        }
+        docstore.save_to_file(&dir.join("docstore.json")).unwrap();
+
+        let bm25 = serde_json::json!({
+            "version": "1.0",
+            "doc_ids": docs.iter().map(|(id, _)| id.to_string()).collect::<Vec<_>>(),
+            "corpus": docs.iter().map(|(id, _)| vec!["rust".to_string(), id.to_string()]).collect::<Vec<_>>(),
+        });
+        std::fs::write(dir.join("bm25_index.json"), bm25.to_string()).unwrap();
+    }
+
+    fn create_set() -> (IndexSet, tempfile::TempDir, tempfile::TempDir) {
+        let changelog = tempfile::tempdir().unwrap();
+        let wiki = tempfile::tempdir().unwrap();
+        write_index(changelog.path(), &[("c1", 1), ("c2", 3)]);
+        write_index(wiki.path(), &[("w1", 2), ("w2", 4), ("w3", 5)]);
+
+        let mut set = IndexSet::new("changelog", Searcher::new(changelog.path()).unwrap());
+        set.add("wiki", Searcher::new(wiki.path()).unwrap())
+            .unwrap();
+        (set, changelog, wiki)
+    }
+
+    fn selector(names: &[&str]) -> IndexSelector {
+        IndexSelector::Many(names.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn test_resolve_and_docstores() {
+        let (set, _changelog, _wiki) = create_set();
+        assert_eq!(set.resolve(None).unwrap().docstore().len(), 2);
+        assert_eq!(set.resolve(Some("wiki")).unwrap().docstore().len(), 3);
+        assert!(set.resolve(Some("notes")).is_err());
+
+        let sizes: Vec<usize> = set.docstores().iter().map(|d| d.len()).collect();
+        assert_eq!(sizes, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_index_selector_deserialize() {
+        let one: IndexSelector = serde_json::from_str(r#""wiki""#).unwrap();
+        assert_eq!(one, IndexSelector::One("wiki".to_string()));
+        let many: IndexSelector = serde_json::from_str(r#"["wiki", "notes"]"#).unwrap();
+        assert_eq!(many, selector(&["wiki", "notes"]));
+    }
+
+    #[test]
+    fn test_select() {
+        let (set, _c, _w) = create_set();
+        assert_eq!(set.select(None).unwrap().names(), vec!["changelog", "wiki"]);
+        assert_eq!(
+            set.select(Some(&IndexSelector::One("all".to_string())))
+                .unwrap()
+                .names(),
+            vec!["changelog", "wiki"]
+        );
+        assert_eq!(
+            set.select(Some(&IndexSelector::One("wiki".to_string())))
+                .unwrap()
+                .names(),
+            vec!["wiki"]
+        );
+        assert_eq!(
+            set.select(Some(&selector(&["wiki", "changelog", "wiki"])))
+                .unwrap()
+                .names(),
+            vec!["wiki", "changelog"]
+        );
+
+        let err = set
+            .select(Some(&IndexSelector::One("notes".to_string())))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("changelog, wiki"));
+    }
+
+    #[test]
+    fn test_add_rejects_duplicates() {
+        let (mut set, _c, _w) = create_set();
+        let dir = tempfile::tempdir().unwrap();
+        assert!(set.add("wiki", Searcher::new(dir.path()).unwrap()).is_err());
+        assert!(set.add("all", Searcher::new(dir.path()).unwrap()).is_err());
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_search_single_index_matches_searcher() {
+        let (set, _c, _w) = create_set();
+        let selection = set
+            .select(Some(&IndexSelector::One("wiki".to_string())))
+            .unwrap();
+        let config = SearchConfig::new().with_mode(SearchMode::Bm25);
+
+        let page = selection.search_page("rust", &config).unwrap();
+        let direct = set
+            .get("wiki")
+            .unwrap()
+            .search_page("rust", &config)
+            .unwrap();
+        assert_eq!(page.total, direct.total);
+        for (hit, result) in page.items.iter().zip(&direct.items) {
+            assert_eq!(hit.index, "wiki");
+            assert_eq!(hit.result.doc_id, result.doc_id);
+            assert_eq!(hit.result.score, result.score);
+        }
+    }
+
+    #[test]
+    fn test_recent_page_across_indexes() {
+        let (set, _c, _w) = create_set();
+        let selection = set.select(None).unwrap();
+
+        let first = selection.recent_page(0, None, 3).unwrap();
+        assert_eq!(first.total, 5);
+        let ids: Vec<(&str, &str)> = first
+            .items
+            .iter()
+            .map(|item| (item.index, item.doc.id.as_str()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![("wiki", "w3"), ("wiki", "w2"), ("changelog", "c2")]
+        );
+
+        let second = selection
+            .recent_page(0, first.next_cursor.as_deref(), 3)
+            .unwrap();
+        let ids: Vec<&str> = second
+            .items
+            .iter()
+            .map(|item| item.doc.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["w1", "c1"]);
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_tag_counts_and_find() {
+        let (set, _c, _w) = create_set();
+        let selection = set.select(None).unwrap();
+
+        let counts = selection.tag_counts();
+        let count = |tag: &str| counts.iter().find(|c| c.tag == tag).map(|c| c.authored);
+        assert_eq!(count("shared"), Some(5));
+        assert_eq!(count("tag-w1"), Some(1));
+
+        let found = selection.find("w2").unwrap();
+        assert_eq!(found.index, "wiki");
+        assert!(selection.find("missing").is_none());
+    }
+
+    #[test]
+    fn test_timeline_across_indexes() {
+        use crate::index::Period;
+
+        let (set, _c, _w) = create_set();
+        let selection = set.select(None).unwrap();
+        let timeline = Timeline::new(Period::Day).with_titles(1);
+
+        let buckets = selection
+            .timeline("", &SearchConfig::new(), &timeline)
+            .unwrap();
+        let counts: Vec<usize> = buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![1, 1, 1, 1, 1]);
+        assert_eq!(buckets[0].memos[0].doc_id, "c1");
+
+        let config = SearchConfig::new().with_tag_filter(Some("tag-w2".to_string()));
+        let buckets = selection.timeline(" ", &config, &timeline).unwrap();
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].label, "2025-01-04");
+    }
+}
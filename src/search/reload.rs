@@ -0,0 +1,311 @@
+//! Index hot-reloading
+//!
+//! A running server keeps serving the indexes it loaded at startup. The
+//! [`IndexReloader`] polls each index directory's `metadata.json`, which
+//! `digrag build` writes last, and swaps in a freshly loaded [`Searcher`] when
+//! the build timestamp changes. Requests hold the [`IndexSet`] they started
+//! with, so a swap never affects a request in flight.
+
+use super::{IndexSet, Searcher};
+use crate::index::IndexMetadata;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// Metadata file written at the end of every build
+pub const METADATA_FILE: &str = "metadata.json";
+
+/// Default interval between checks for rebuilt indexes
+pub const DEFAULT_RELOAD_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Shared handle to the currently loaded indexes
+#[derive(Clone)]
+pub struct SharedIndexes {
+    current: Arc<RwLock<Arc<IndexSet>>>,
+}
+
+impl SharedIndexes {
+    /// Wrap a loaded index set
+    pub fn new(indexes: IndexSet) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(Arc::new(indexes))),
+        }
+    }
+
+    /// Snapshot of the current indexes, valid for as long as it is held
+    pub fn load(&self) -> Arc<IndexSet> {
+        match self.current.read() {
+            Ok(current) => current.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Replace the current indexes
+    pub fn store(&self, indexes: IndexSet) {
+        let indexes = Arc::new(indexes);
+        match self.current.write() {
+            Ok(mut current) => *current = indexes,
+            Err(poisoned) => *poisoned.into_inner() = indexes,
+        }
+    }
+}
+
+/// Polls index directories and reloads indexes that were rebuilt
+pub struct IndexReloader<F> {
+    shared: SharedIndexes,
+    dirs: Vec<(String, PathBuf)>,
+    loader: F,
+    interval: Duration,
+    /// Last seen modification time of each index's metadata file
+    modified: HashMap<String, SystemTime>,
+}
+
+impl<F> IndexReloader<F>
+where
+    F: Fn(&Path) -> Result<Searcher>,
+{
+    /// Create a reloader for named index directories
+    ///
+    /// `loader` builds a searcher for a directory, e.g. with an embedding
+    /// client attached.
+    pub fn new(shared: SharedIndexes, dirs: Vec<(String, PathBuf)>, loader: F) -> Self {
+        let modified = dirs
+            .iter()
+            .filter_map(|(name, dir)| Some((name.clone(), metadata_modified(dir)?)))
+            .collect();
+        Self {
+            shared,
+            dirs,
+            loader,
+            interval: DEFAULT_RELOAD_INTERVAL,
+            modified,
+        }
+    }
+
+    /// Set the polling interval
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Reload every index whose build timestamp changed
+    ///
+    /// Returns the names of the reloaded indexes. An index that fails to load
+    /// (e.g. while a build is still writing it) keeps serving the old data and
+    /// is retried on the next check.
+    pub fn check(&mut self) -> Vec<String> {
+        let mut reloaded = Vec::new();
+
+        for (name, dir) in &self.dirs {
+            let Some(modified) = metadata_modified(dir) else {
+                continue;
+            };
+            if self.modified.get(name) == Some(&modified) {
+                continue;
+            }
+
+            let current = self.shared.load();
+            let on_disk = index_timestamp(dir);
+            if on_disk.as_deref() == current.get(name).and_then(Searcher::created_at) {
+                self.modified.insert(name.clone(), modified);
+                continue;
+            }
+
+            match (self.loader)(dir).and_then(|searcher| current.with_searcher(name, searcher)) {
+                Ok(indexes) => {
+                    self.shared.store(indexes);
+                    self.modified.insert(name.clone(), modified);
+                    tracing::info!(
+                        "Reloaded index '{}' from {} (built {})",
+                        name,
+                        dir.display(),
+                        on_disk.as_deref().unwrap_or("unknown")
+                    );
+                    reloaded.push(name.clone());
+                }
+                Err(e) => tracing::warn!("Failed to reload index '{}': {}", name, e),
+            }
+        }
+
+        reloaded
+    }
+
+    /// Check for rebuilt indexes forever at the configured interval
+    ///
+    /// Each check runs on the blocking thread pool, since loading an index
+    /// reads and deserializes every index file.
+    pub async fn run(mut self)
+    where
+        F: Send + 'static,
+    {
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            self = match tokio::task::spawn_blocking(move || {
+                self.check();
+                self
+            })
+            .await
+            {
+                Ok(reloader) => reloader,
+                Err(e) => {
+                    tracing::error!("Index reload check failed, stopping reloads: {}", e);
+                    return;
+                }
+            };
+        }
+    }
+}
+
+/// Build timestamp recorded in an index directory's metadata, if any
+pub fn index_timestamp(index_dir: &Path) -> Option<String> {
+    IndexMetadata::load_from_file(&index_dir.join(METADATA_FILE))
+        .ok()
+        .map(|metadata| metadata.created_at)
+}
+
+fn metadata_modified(index_dir: &Path) -> Option<SystemTime> {
+    std::fs::metadata(index_dir.join(METADATA_FILE))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{Docstore, IndexMetadata};
+    use crate::loader::Document;
+    use chrono::Utc;
+
+    /// Write an index with the given document IDs and build timestamp
+    fn write_index(dir: &Path, ids: &[&str], created_at: &str) {
+        let mut docstore = Docstore::new();
+        for id in ids {
+            docstore.add(Document::with_id(
+                id.to_string(),
+                format!("Memo {}", id),
+                Utc::now(),
+                vec![],
+                "body".to_string(),
+            ));
+        }
+        docstore.save_to_file(&dir.join("docstore.json")).unwrap();
+
+        let mut metadata = IndexMetadata::new(ids.len(), None);
+        metadata.created_at = created_at.to_string();
+        metadata.save_to_file(&dir.join(METADATA_FILE)).unwrap();
+    }
+
+    /// Bump the metadata mtime even on filesystems with coarse timestamps
+    fn touch_metadata(dir: &Path) {
+        let file = std::fs::File::options()
+            .write(true)
+            .open(dir.join(METADATA_FILE))
+            .unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+    }
+
+    fn doc_count(shared: &SharedIndexes) -> usize {
+        shared.load().primary().docstore().len()
+    }
+
+    #[test]
+    fn test_searcher_reads_timestamp() {
+        let dir = tempfile::tempdir().unwrap();
+        write_index(dir.path(), &["a"], "2025-01-01T00:00:00+00:00");
+
+        let searcher = Searcher::new(dir.path()).unwrap();
+        assert_eq!(searcher.created_at(), Some("2025-01-01T00:00:00+00:00"));
+        assert_eq!(
+            index_timestamp(dir.path()).as_deref(),
+            Some("2025-01-01T00:00:00+00:00")
+        );
+
+        let empty = tempfile::tempdir().unwrap();
+        assert!(Searcher::new(empty.path()).unwrap().created_at().is_none());
+    }
+
+    #[test]
+    fn test_reload_on_rebuild() {
+        let dir = tempfile::tempdir().unwrap();
+        write_index(dir.path(), &["a"], "2025-01-01T00:00:00+00:00");
+
+        let shared =
+            SharedIndexes::new(IndexSet::new("default", Searcher::new(dir.path()).unwrap()));
+        let mut reloader = IndexReloader::new(
+            shared.clone(),
+            vec![("default".to_string(), dir.path().to_path_buf())],
+            |dir: &Path| Searcher::new(dir),
+        );
+        assert!(reloader.check().is_empty());
+
+        // A request in flight keeps its snapshot
+        let snapshot = shared.load();
+
+        write_index(dir.path(), &["a", "b"], "2025-01-02T00:00:00+00:00");
+        touch_metadata(dir.path());
+
+        assert_eq!(reloader.check(), vec!["default".to_string()]);
+        assert_eq!(doc_count(&shared), 2);
+        assert_eq!(snapshot.primary().docstore().len(), 1);
+        assert_eq!(
+            shared.load().primary().created_at(),
+            Some("2025-01-02T00:00:00+00:00")
+        );
+
+        // Nothing changed since the last check
+        assert!(reloader.check().is_empty());
+    }
+
+    #[test]
+    fn test_failed_reload_keeps_old_index() {
+        let dir = tempfile::tempdir().unwrap();
+        write_index(dir.path(), &["a"], "2025-01-01T00:00:00+00:00");
+
+        let shared =
+            SharedIndexes::new(IndexSet::new("default", Searcher::new(dir.path()).unwrap()));
+        let mut reloader = IndexReloader::new(
+            shared.clone(),
+            vec![("default".to_string(), dir.path().to_path_buf())],
+            |_: &Path| Err(anyhow::anyhow!("index is being written")),
+        );
+
+        write_index(dir.path(), &["a", "b"], "2025-01-02T00:00:00+00:00");
+        touch_metadata(dir.path());
+
+        assert!(reloader.check().is_empty());
+        assert_eq!(doc_count(&shared), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_reloads_off_the_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        write_index(dir.path(), &["a"], "2025-01-01T00:00:00+00:00");
+
+        let shared =
+            SharedIndexes::new(IndexSet::new("default", Searcher::new(dir.path()).unwrap()));
+        let reloader = IndexReloader::new(
+            shared.clone(),
+            vec![("default".to_string(), dir.path().to_path_buf())],
+            |dir: &Path| Searcher::new(dir),
+        )
+        .with_interval(Duration::from_millis(10));
+        let task = tokio::spawn(reloader.run());
+
+        write_index(dir.path(), &["a", "b"], "2025-01-02T00:00:00+00:00");
+        touch_metadata(dir.path());
+
+        for _ in 0..200 {
+            if doc_count(&shared) == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(doc_count(&shared), 2);
+        task.abort();
+    }
+}
@@ -2,16 +2,38 @@
 //!
 //! Provides the main search interface that combines all search methods.
 
-use super::{ReciprocalRankFusion, SearchResult};
+use super::{
+    paginate, Facets, MaximalMarginalRelevance, Page, PageCursor, ReciprocalRankFusion,
+    SearchResult, METADATA_FILE,
+};
 use crate::config::{SearchConfig, SearchMode};
 use crate::embedding::OpenRouterEmbedding;
-use crate::index::{Bm25Index, Docstore, VectorIndex};
-use anyhow::Result;
+use crate::index::{
+    create_embedding_text, Bm25Index, Docstore, DuplicateCluster, DuplicateDetector, IndexMetadata,
+    TopicClusterer, TopicModel, VectorIndex, TOPICS_FILE,
+};
+use crate::loader::Document;
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use tokio::sync::Mutex;
 
+/// Candidate pool size multiplier used when MMR diversification is enabled
+const MMR_CANDIDATE_FACTOR: usize = 4;
+
+/// Candidate pool multiplier used when filters may drop results, and the
+/// growth factor when a page needs more candidates
+const FILTER_CANDIDATE_FACTOR: usize = 4;
+
+/// Number of tf-idf terms used to build a "more like this" BM25 query
+const SIMILAR_QUERY_TERMS: usize = 20;
+
+/// Minimum cosine similarity for a vector hit to count as a semantic match
+pub const MIN_MATCH_SIMILARITY: f32 = 0.4;
+
 /// Main searcher that combines all search methods
+#[derive(Clone)]
 pub struct Searcher {
     /// BM25 index
     bm25_index: Bm25Index,
@@ -23,6 +45,12 @@ pub struct Searcher {
     rrf: ReciprocalRankFusion,
     /// Optional embedding client for semantic search
     embedding_client: Option<Arc<Mutex<OpenRouterEmbedding>>>,
+    /// Build timestamp from metadata.json (None for indexes without one)
+    created_at: Option<String>,
+    /// Topic assignments from topics.json (None until `digrag topics build`)
+    topics: Option<Arc<TopicModel>>,
+    /// Duplicate cluster of each duplicated document, computed on first use
+    duplicate_groups: OnceLock<HashMap<String, usize>>,
 }
 
 impl Searcher {
@@ -53,12 +81,26 @@ impl Searcher {
             Docstore::new()
         };
 
+        let topics_path = index_dir.join(TOPICS_FILE);
+        let topics = if topics_path.exists() {
+            Some(Arc::new(TopicModel::load_from_file(&topics_path)?))
+        } else {
+            None
+        };
+
+        let created_at = IndexMetadata::load_from_file(&index_dir.join(METADATA_FILE))
+            .ok()
+            .map(|metadata| metadata.created_at);
+
         Ok(Self {
             bm25_index,
             vector_index,
             docstore,
             rrf: ReciprocalRankFusion::new(),
             embedding_client: None,
+            created_at,
+            topics,
+            duplicate_groups: OnceLock::new(),
         })
     }
 
@@ -79,16 +121,261 @@ impl Searcher {
 
     /// Search with the given configuration
     pub fn search(&self, query: &str, config: &SearchConfig) -> Result<Vec<SearchResult>> {
-        // Apply tag filter
+        Ok(self.search_page(query, config)?.items)
+    }
+
+    /// Search and return a single page of results
+    ///
+    /// Pages are cut from a deterministic ordering (score, then date, then
+    /// doc_id). BM25 and semantic pages only rank the candidates up to the
+    /// page, so `total` may be a lower bound (see [`Page::total_exact`]).
+    /// Hybrid pages always rank the whole corpus, since RRF scores change
+    /// with the depth of the fused lists.
+    pub fn search_page(&self, query: &str, config: &SearchConfig) -> Result<Page<SearchResult>> {
+        match config.search_mode {
+            SearchMode::Bm25 => self.ranked_page(config, |depth| self.search_bm25(query, depth)),
+            SearchMode::Semantic => {
+                // Embed the query once, however deep the page goes
+                let embedding = self.embed_query(query)?;
+                self.ranked_page(config, |depth| match &embedding {
+                    Some(embedding) => self.search_semantic_with_vector(embedding, depth),
+                    None => Ok(Vec::new()),
+                })
+            }
+            SearchMode::Hybrid => {
+                let results = self.search_hybrid(query, self.candidate_depth())?;
+                self.paginate_ranked(self.rank_results(results, config), config)
+            }
+        }
+    }
+
+    /// Search and return a single page of results with facet counts
+    ///
+    /// Facets cover every match, not just the returned page (see
+    /// [`Searcher::matches`]).
+    pub fn search_page_with_facets(
+        &self,
+        query: &str,
+        config: &SearchConfig,
+    ) -> Result<(Page<SearchResult>, Facets)> {
+        let facets = self.facets(&self.matches(query, config)?);
+        Ok((self.search_page(query, config)?, facets))
+    }
+
+    /// Count the tags, categories and months of ranked results
+    pub fn facets(&self, results: &[SearchResult]) -> Facets {
+        Facets::from_documents(results.iter().filter_map(|r| self.docstore.get(&r.doc_id)))
+    }
+
+    /// Rank every matching document without paginating
+    ///
+    /// This is the full ordering that [`Searcher::search_page`] cuts pages
+    /// from, with the tag filter and MMR re-ranking already applied.
+    pub fn rank(&self, query: &str, config: &SearchConfig) -> Result<Vec<SearchResult>> {
+        let results = self.retrieve(query, config.search_mode, self.candidate_depth())?;
+        Ok(self.rank_results(results, config))
+    }
+
+    /// Documents matching the query in the configured search mode, in
+    /// ranked order
+    ///
+    /// BM25 matches contain the query terms, semantic matches are vector
+    /// hits with a cosine similarity of at least [`MIN_MATCH_SIMILARITY`],
+    /// and hybrid matches are either, in fused order. Facets count this set.
+    /// The tag, topic and duplicate filters apply; MMR does not.
+    pub fn matches(&self, query: &str, config: &SearchConfig) -> Result<Vec<SearchResult>> {
+        let depth = self.candidate_depth();
+        let semantic_matches = |results: Vec<SearchResult>| -> Vec<SearchResult> {
+            results
+                .into_iter()
+                .filter(|r| r.score >= MIN_MATCH_SIMILARITY)
+                .collect()
+        };
         let results = match config.search_mode {
-            SearchMode::Bm25 => self.search_bm25(query, config.top_k)?,
-            SearchMode::Semantic => self.search_semantic(query, config.top_k)?,
-            SearchMode::Hybrid => self.search_hybrid(query, config.top_k)?,
+            SearchMode::Bm25 => self.search_bm25(query, depth)?,
+            SearchMode::Semantic => semantic_matches(self.search_semantic(query, depth)?),
+            SearchMode::Hybrid => {
+                let bm25_results = self.search_bm25(query, depth)?;
+                let vector_results = self.search_semantic(query, depth)?;
+                let fused = self.rrf.fuse(&bm25_results, &vector_results);
+                let matched: HashSet<String> = bm25_results
+                    .into_iter()
+                    .chain(semantic_matches(vector_results))
+                    .map(|r| r.doc_id)
+                    .collect();
+                fused
+                    .into_iter()
+                    .filter(|r| matched.contains(&r.doc_id))
+                    .collect()
+            }
         };
+        let config = config.clone().with_mmr_lambda(None);
+        Ok(self.rank_results(results, &config))
+    }
+
+    /// Documents containing the query terms, in ranked order
+    ///
+    /// Only BM25 hits are returned, whatever the search mode: cosine
+    /// similarity is positive for nearly every memo, so semantic scores do
+    /// not say which memos match. Timelines count this set. The
+    /// tag, topic and duplicate filters apply; MMR does not.
+    pub fn lexical_matches(&self, query: &str, config: &SearchConfig) -> Result<Vec<SearchResult>> {
+        let results = self.search_bm25(query, self.candidate_depth())?;
+        let config = config.clone().with_mmr_lambda(None);
+        Ok(self.rank_results(results, &config))
+    }
+
+    /// Retrieve up to `depth` raw candidates for a query
+    fn retrieve(&self, query: &str, mode: SearchMode, depth: usize) -> Result<Vec<SearchResult>> {
+        match mode {
+            SearchMode::Bm25 => self.search_bm25(query, depth),
+            SearchMode::Semantic => self.search_semantic(query, depth),
+            SearchMode::Hybrid => self.search_hybrid(query, depth),
+        }
+    }
+
+    /// Find documents similar to an existing document ("more like this")
+    ///
+    /// Uses the document's stored vector when one exists (fused with the BM25
+    /// query in hybrid mode); otherwise builds a weighted BM25 query from the
+    /// document's highest tf-idf terms. The source document is excluded.
+    pub fn similar(&self, doc_id: &str, config: &SearchConfig) -> Result<Vec<SearchResult>> {
+        Ok(self.similar_page(doc_id, config)?.items)
+    }
+
+    /// Find documents similar to an existing document, returning a single page
+    pub fn similar_page(&self, doc_id: &str, config: &SearchConfig) -> Result<Page<SearchResult>> {
+        if !self.docstore.contains(doc_id) {
+            return Err(anyhow!("Document not found: {}", doc_id));
+        }
+
+        let vector = match config.search_mode {
+            SearchMode::Bm25 => None,
+            SearchMode::Semantic | SearchMode::Hybrid => self.vector_index.get_vector(doc_id),
+        };
+
+        let without_source = |results: Vec<SearchResult>, depth: usize| -> Vec<SearchResult> {
+            results
+                .into_iter()
+                .filter(|r| r.doc_id != doc_id)
+                .take(depth)
+                .collect()
+        };
+
+        match (config.search_mode, vector) {
+            (SearchMode::Hybrid, Some(vector)) => {
+                // Fused scores depend on the list depth, so rank everything
+                let depth = self.candidate_depth();
+                let bm25_results = self.similar_bm25(doc_id, depth);
+                let vector_results = self.vector_index.search(vector, depth)?;
+                let results = without_source(self.rrf.fuse(&bm25_results, &vector_results), depth);
+                self.paginate_ranked(self.rank_results(results, config), config)
+            }
+            // One extra candidate makes up for the source document
+            (SearchMode::Semantic, Some(vector)) => self.ranked_page(config, |depth| {
+                Ok(without_source(
+                    self.vector_index.search(vector, depth + 1)?,
+                    depth,
+                ))
+            }),
+            _ => self.ranked_page(config, |depth| {
+                Ok(without_source(self.similar_bm25(doc_id, depth + 1), depth))
+            }),
+        }
+    }
+
+    /// BM25 "more like this" search from a document's top tf-idf terms
+    fn similar_bm25(&self, doc_id: &str, top_k: usize) -> Vec<SearchResult> {
+        let terms = self.bm25_index.top_terms(doc_id, SIMILAR_QUERY_TERMS);
+        self.bm25_index.search_weighted(&terms, top_k)
+    }
+
+    /// Number of candidates needed to rank the whole corpus
+    fn candidate_depth(&self) -> usize {
+        self.bm25_index
+            .len()
+            .max(self.vector_index.len())
+            .max(self.docstore.len())
+    }
+
+    /// Rank the leading candidates and cut the requested page
+    ///
+    /// `retrieve` returns up to the given number of raw candidates. It is
+    /// first asked for the page plus one page of slack (more when filters
+    /// or MMR need it) and asked again for more until the results scoring
+    /// above the lowest candidate reach past the page, or the candidates
+    /// run out. A cursor whose last item is not among the ranked candidates
+    /// deepens to the whole corpus. Scores must not depend on the depth.
+    ///
+    /// When the candidates did not run out, `total` counts the ranked
+    /// results only and the page is marked as such.
+    fn ranked_page<F>(&self, config: &SearchConfig, retrieve: F) -> Result<Page<SearchResult>>
+    where
+        F: Fn(usize) -> Result<Vec<SearchResult>>,
+    {
+        let cursor = config
+            .cursor
+            .as_deref()
+            .map(PageCursor::decode)
+            .transpose()?;
+        let full_depth = self.candidate_depth();
+        let filtered = config.tag_filter.is_some()
+            || config.topic_filter.is_some()
+            || config.collapse_duplicates;
+
+        let start = cursor.as_ref().map_or(config.offset, |c| c.offset);
+        let mut depth = start + config.top_k.max(1) * 2;
+        if config.mmr_lambda.is_some() {
+            depth = depth.max(config.top_k * MMR_CANDIDATE_FACTOR + config.top_k.max(1));
+        }
+        if filtered {
+            depth *= FILTER_CANDIDATE_FACTOR;
+        }
+
+        loop {
+            depth = depth.min(full_depth);
+            let results = retrieve(depth)?;
+            let exhausted = depth >= full_depth || results.len() < depth;
+            // Candidates left out may tie with the lowest score retrieved, so
+            // only results scoring above it are known to be in place
+            let floor = results
+                .iter()
+                .map(|r| r.score)
+                .fold(f32::INFINITY, f32::min);
+            let ranked = self.rank_results(results, config);
+            if exhausted {
+                return self.paginate_ranked(ranked, config);
+            }
 
+            let start = match &cursor {
+                Some(cursor) => match ranked.iter().position(|r| r.doc_id == cursor.last_id) {
+                    Some(pos) => pos + 1,
+                    None => {
+                        depth = full_depth;
+                        continue;
+                    }
+                },
+                None => config.offset,
+            };
+            let mut needed = start + config.top_k;
+            if config.mmr_lambda.is_some() {
+                needed = needed.max(config.top_k * MMR_CANDIDATE_FACTOR);
+            }
+            if ranked.iter().filter(|r| r.score > floor).count() > needed {
+                return Ok(self
+                    .paginate_ranked(ranked, config)?
+                    .with_total_exact(false));
+            }
+            depth *= FILTER_CANDIDATE_FACTOR;
+        }
+    }
+
+    /// Apply the tag filter, deterministic ordering and optional MMR
+    /// re-ranking to raw results
+    fn rank_results(&self, results: Vec<SearchResult>, config: &SearchConfig) -> Vec<SearchResult> {
         // Filter by tag if specified
-        if let Some(tag) = &config.tag_filter {
-            Ok(results
+        let mut results: Vec<SearchResult> = if let Some(tag) = &config.tag_filter {
+            results
                 .into_iter()
                 .filter(|r| {
                     self.docstore
@@ -96,11 +383,97 @@ impl Searcher {
                         .map(|doc| doc.has_tag(tag))
                         .unwrap_or(false)
                 })
-                .take(config.top_k)
-                .collect())
+                .collect()
         } else {
-            Ok(results)
+            results
+        };
+
+        if let Some(topic) = config.topic_filter {
+            results.retain(|r| self.topic_of(&r.doc_id) == Some(topic));
+        }
+
+        self.sort_results(&mut results);
+
+        if config.collapse_duplicates {
+            // Keep the best-ranked memo of each duplicate cluster
+            let groups = self.duplicate_groups();
+            let mut seen = HashSet::new();
+            results.retain(|r| {
+                groups
+                    .get(&r.doc_id)
+                    .is_none_or(|group| seen.insert(*group))
+            });
+        }
+
+        if let Some(lambda) = config.mmr_lambda {
+            // Diversify a pool sized by the page size, not the offset, so that
+            // every page is cut from the same ordering
+            let pool = (config.top_k * MMR_CANDIDATE_FACTOR).min(results.len());
+            let tail = results.split_off(pool);
+            results = self.diversify(results, pool, lambda);
+            results.extend(tail);
         }
+
+        results
+    }
+
+    /// Cut the requested page from ranked results
+    fn paginate_ranked(
+        &self,
+        ranked: Vec<SearchResult>,
+        config: &SearchConfig,
+    ) -> Result<Page<SearchResult>> {
+        paginate(
+            ranked,
+            |r| r.doc_id.as_str(),
+            config.offset,
+            config.cursor.as_deref(),
+            config.top_k,
+        )
+    }
+
+    /// Sort results by score, then date (newest first), then doc_id
+    fn sort_results(&self, results: &mut [SearchResult]) {
+        let date_of = |doc_id: &str| self.docstore.get(doc_id).map(|doc| doc.date());
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| date_of(&b.doc_id).cmp(&date_of(&a.doc_id)))
+                .then_with(|| a.doc_id.cmp(&b.doc_id))
+        });
+    }
+
+    /// Re-rank results with MMR to reduce redundancy
+    ///
+    /// Uses cosine similarity of stored vectors when both documents have one,
+    /// otherwise falls back to Jaccard overlap of their BM25 tokens.
+    fn diversify(
+        &self,
+        candidates: Vec<SearchResult>,
+        top_k: usize,
+        lambda: f32,
+    ) -> Vec<SearchResult> {
+        let vectors: Vec<Option<&[f32]>> = candidates
+            .iter()
+            .map(|r| self.vector_index.get_vector(&r.doc_id))
+            .collect();
+        let token_sets: Vec<HashSet<&str>> = candidates
+            .iter()
+            .map(|r| {
+                self.bm25_index
+                    .doc_tokens(&r.doc_id)
+                    .map(|tokens| tokens.iter().map(String::as_str).collect())
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        MaximalMarginalRelevance::new(lambda).rerank(candidates, top_k, |a, b| {
+            match (vectors[a], vectors[b]) {
+                (Some(va), Some(vb)) => VectorIndex::cosine_similarity(va, vb).max(0.0),
+                _ => jaccard_similarity(&token_sets[a], &token_sets[b]),
+            }
+        })
     }
 
     /// BM25 keyword search
@@ -110,14 +483,24 @@ impl Searcher {
 
     /// Semantic vector search
     fn search_semantic(&self, query: &str, top_k: usize) -> Result<Vec<SearchResult>> {
+        tracing::info!("Semantic search for '{}' with top_k={}", query, top_k);
+        match self.embed_query(query)? {
+            Some(embedding) => self.vector_index.search(&embedding, top_k),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Embed a query for semantic search
+    ///
+    /// Returns None when there is no vector index or embedding client, or
+    /// the embedding request fails.
+    fn embed_query(&self, query: &str) -> Result<Option<Vec<f32>>> {
         // Check if vector index is available
         if self.vector_index.is_empty() {
             tracing::warn!("Vector index is empty. Semantic search requires embeddings.");
-            return Ok(Vec::new());
+            return Ok(None);
         }
 
-        tracing::info!("Semantic search for '{}' with top_k={}", query, top_k);
-
         // Use embedding client if available
         if let Some(ref client) = self.embedding_client {
             // Get embedding for query using blocking runtime
@@ -150,18 +533,18 @@ impl Searcher {
 
             match query_embedding {
                 Ok(embedding) => {
-                    return self.vector_index.search(&embedding, top_k);
+                    return Ok(Some(embedding));
                 }
                 Err(e) => {
                     tracing::error!("Failed to generate query embedding: {}", e);
-                    return Ok(Vec::new());
+                    return Ok(None);
                 }
             }
         }
 
         // Fallback: no embedding client available
         tracing::warn!("No embedding client available for semantic search");
-        Ok(Vec::new())
+        Ok(None)
     }
 
     /// Semantic search with pre-computed query vector (for testing or cached queries)
@@ -181,6 +564,11 @@ impl Searcher {
         !self.vector_index.is_empty()
     }
 
+    /// Build timestamp (RFC 3339) of the loaded index, if it has metadata
+    pub fn created_at(&self) -> Option<&str> {
+        self.created_at.as_deref()
+    }
+
     /// Hybrid search using RRF
     fn search_hybrid(&self, query: &str, top_k: usize) -> Result<Vec<SearchResult>> {
         let bm25_results = self.search_bm25(query, top_k * 2)?;
@@ -205,6 +593,124 @@ impl Searcher {
     pub fn get_recent_memos(&self, limit: usize) -> Vec<&crate::loader::Document> {
         self.docstore.get_recent(limit)
     }
+
+    /// Get a page of recent memos
+    pub fn get_recent_memos_page(
+        &self,
+        offset: usize,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<Page<&crate::loader::Document>> {
+        self.docstore.get_recent_page(offset, cursor, limit)
+    }
+
+    /// Embed a document that is about to be inserted
+    ///
+    /// Returns None when the index holds no vectors, no embedding client is
+    /// configured or embedding fails; the document is then still found by
+    /// BM25.
+    pub async fn embed_document(&self, doc: &Document) -> Option<Vec<f32>> {
+        if self.vector_index.is_empty() {
+            return None;
+        }
+        let client = self.embedding_client.as_ref()?;
+        let text = create_embedding_text(doc);
+        let embedding = client.lock().await.embed(&text).await;
+        embedding
+            .map_err(|e| tracing::warn!("Failed to embed memo {}: {}", doc.id, e))
+            .ok()
+    }
+
+    /// Add a document to the loaded indexes so it is searchable immediately
+    ///
+    /// `embedding` comes from [`Searcher::embed_document`], which is async,
+    /// so that this can run on a blocking thread.
+    pub fn insert_document(&mut self, doc: Document, embedding: Option<Vec<f32>>) -> Result<()> {
+        self.bm25_index.insert(&doc)?;
+
+        if let Some(embedding) = embedding {
+            self.vector_index.remove(&doc.id);
+            self.vector_index.add(doc.id.clone(), embedding)?;
+        }
+
+        self.docstore.add(doc);
+        self.duplicate_groups = OnceLock::new();
+        Ok(())
+    }
+
+    /// Topic model of the index, if topics have been computed
+    pub fn topics(&self) -> Option<&TopicModel> {
+        self.topics.as_deref()
+    }
+
+    /// Topic a document is assigned to
+    pub fn topic_of(&self, doc_id: &str) -> Option<usize> {
+        self.topics.as_ref()?.topic_of(doc_id)
+    }
+
+    /// Find clusters of near-duplicate documents
+    ///
+    /// Uses the tokens stored in the BM25 index, and the stored embeddings
+    /// when the detector has a vector threshold.
+    pub fn find_duplicates(&self, detector: &DuplicateDetector) -> Vec<DuplicateCluster> {
+        let vectors = (!self.vector_index.is_empty()).then_some(&self.vector_index);
+        detector.find(self.bm25_index.documents(), vectors)
+    }
+
+    /// Cluster the indexed documents into topics
+    pub fn cluster_topics(&self, clusterer: &TopicClusterer) -> TopicModel {
+        clusterer.cluster(&self.bm25_index, &self.vector_index)
+    }
+
+    /// Map each duplicated document to its cluster, using the default detector
+    fn duplicate_groups(&self) -> &HashMap<String, usize> {
+        self.duplicate_groups.get_or_init(|| {
+            self.find_duplicates(&DuplicateDetector::new())
+                .into_iter()
+                .enumerate()
+                .flat_map(|(group, cluster)| cluster.doc_ids.into_iter().map(move |id| (id, group)))
+                .collect()
+        })
+    }
+
+    /// Write the loaded indexes to an index directory
+    ///
+    /// Document hashes and the build timestamp in `metadata.json` are
+    /// updated so incremental builds and running servers see the change;
+    /// the metadata is written last.
+    pub fn save<P: AsRef<Path>>(&mut self, index_dir: P) -> Result<()> {
+        let index_dir = index_dir.as_ref();
+        std::fs::create_dir_all(index_dir)?;
+
+        self.bm25_index
+            .save_to_file(&index_dir.join("bm25_index.json"))?;
+        self.docstore
+            .save_to_file(&index_dir.join("docstore.json"))?;
+        self.vector_index
+            .save_to_file(&index_dir.join("faiss_index.json"))?;
+
+        let metadata_path = index_dir.join(METADATA_FILE);
+        let embedding_model = IndexMetadata::load_from_file(&metadata_path)
+            .ok()
+            .and_then(|metadata| metadata.embedding_model);
+        let mut metadata = IndexMetadata::new(self.docstore.len(), embedding_model);
+        for doc in self.docstore.documents().values() {
+            metadata.update_doc_hash(doc.id.clone(), doc.content_hash());
+        }
+        metadata.save_to_file(&metadata_path)?;
+        self.created_at = Some(metadata.created_at);
+
+        Ok(())
+    }
+}
+
+/// Jaccard similarity between two token sets
+fn jaccard_similarity(a: &HashSet<&str>, b: &HashSet<&str>) -> f32 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f32 / union as f32
 }
 
 #[cfg(test)]
@@ -226,5 +732,339 @@ mod tests {
         assert!(tags.is_empty());
     }
 
+    #[test]
+    fn test_diversify_with_vectors() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut vector_index = VectorIndex::new(3);
+        vector_index
+            .add("doc1".to_string(), vec![1.0, 0.0, 0.0])
+            .unwrap();
+        vector_index
+            .add("doc1-copy".to_string(), vec![1.0, 0.01, 0.0])
+            .unwrap();
+        vector_index
+            .add("doc2".to_string(), vec![0.0, 1.0, 0.0])
+            .unwrap();
+        vector_index
+            .save_to_file(&temp_dir.path().join("faiss_index.json"))
+            .unwrap();
+
+        let searcher = Searcher::new(temp_dir.path()).unwrap();
+        let candidates = vec![
+            SearchResult::new("doc1".to_string(), 1.0),
+            SearchResult::new("doc1-copy".to_string(), 0.95),
+            SearchResult::new("doc2".to_string(), 0.7),
+        ];
+
+        let results = searcher.diversify(candidates, 2, 0.5);
+        assert_eq!(results[0].doc_id, "doc1");
+        assert_eq!(results[1].doc_id, "doc2");
+    }
+
+    #[test]
+    fn test_diversify_falls_back_to_token_overlap() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("bm25_index.json"),
+            r#"{"version": "1.0", "doc_ids": ["doc1", "doc1-copy", "doc2"],
+                "corpus": [["日報", "会議"], ["日報", "会議"], ["旅行", "温泉"]]}"#,
+        )
+        .unwrap();
+
+        let searcher = Searcher::new(temp_dir.path()).unwrap();
+        let candidates = vec![
+            SearchResult::new("doc1".to_string(), 2.0),
+            SearchResult::new("doc1-copy".to_string(), 2.0),
+            SearchResult::new("doc2".to_string(), 1.5),
+        ];
+
+        let results = searcher.diversify(candidates, 2, 0.5);
+        assert_eq!(results[0].doc_id, "doc1");
+        assert_eq!(results[1].doc_id, "doc2");
+    }
+
+    /// Write a pre-tokenized BM25 index and docstore for three documents
+    fn setup_similar_index(dir: &Path) {
+        std::fs::write(
+            dir.join("bm25_index.json"),
+            r#"{"version": "1.0", "doc_ids": ["doc1", "doc2", "doc3"],
+                "corpus": [["rust", "mcp", "server"], ["rust", "mcp", "client"], ["python", "web"]]}"#,
+        )
+        .unwrap();
+
+        let mut docstore = Docstore::new();
+        for id in ["doc1", "doc2", "doc3"] {
+            docstore.add(crate::loader::Document::with_id(
+                id.to_string(),
+                id.to_string(),
+                chrono::Utc::now(),
+                vec!["memo".to_string()],
+                String::new(),
+            ));
+        }
+        docstore.save_to_file(&dir.join("docstore.json")).unwrap();
+    }
+
+    #[test]
+    fn test_similar_bm25_excludes_source() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        setup_similar_index(temp_dir.path());
+        let searcher = Searcher::new(temp_dir.path()).unwrap();
+
+        let config = SearchConfig::new().with_mode(SearchMode::Bm25);
+        let results = searcher.similar("doc1", &config).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].doc_id, "doc2");
+    }
+
+    #[test]
+    fn test_similar_uses_stored_vector() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        setup_similar_index(temp_dir.path());
+        let mut vector_index = VectorIndex::new(2);
+        vector_index
+            .add("doc1".to_string(), vec![1.0, 0.0])
+            .unwrap();
+        vector_index
+            .add("doc2".to_string(), vec![0.0, 1.0])
+            .unwrap();
+        vector_index
+            .add("doc3".to_string(), vec![0.9, 0.1])
+            .unwrap();
+        vector_index
+            .save_to_file(&temp_dir.path().join("faiss_index.json"))
+            .unwrap();
+        let searcher = Searcher::new(temp_dir.path()).unwrap();
+
+        let config = SearchConfig::new().with_mode(SearchMode::Semantic);
+        let results = searcher.similar("doc1", &config).unwrap();
+
+        assert_eq!(results[0].doc_id, "doc3");
+        assert!(results.iter().all(|r| r.doc_id != "doc1"));
+    }
+
+    #[test]
+    fn test_facets_count_ranked_results() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        setup_similar_index(temp_dir.path());
+        let searcher = Searcher::new(temp_dir.path()).unwrap();
+
+        let results: Vec<SearchResult> = ["doc1", "doc3", "missing"]
+            .iter()
+            .map(|id| SearchResult::new(id.to_string(), 1.0))
+            .collect();
+        let facets = searcher.facets(&results);
+        assert_eq!(facets.tags.len(), 1);
+        assert_eq!(facets.tags[0].value, "memo");
+        assert_eq!(facets.tags[0].count, 2);
+        assert!(facets.categories.is_empty());
+        assert_eq!(facets.months.iter().map(|m| m.count).sum::<usize>(), 2);
+    }
+
+    #[test]
+    fn test_topic_filter() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        setup_similar_index(temp_dir.path());
+        let topic = |id: usize, doc_ids: &[&str]| crate::index::Topic {
+            id,
+            label: format!("topic {}", id),
+            terms: vec![],
+            doc_ids: doc_ids.iter().map(|d| d.to_string()).collect(),
+        };
+        TopicModel::new(
+            "tfidf",
+            vec![topic(1, &["doc1", "doc3"]), topic(2, &["doc2"])],
+        )
+        .save_to_file(&temp_dir.path().join(TOPICS_FILE))
+        .unwrap();
+        let searcher = Searcher::new(temp_dir.path()).unwrap();
+        assert_eq!(searcher.topic_of("doc2"), Some(2));
+
+        let config = SearchConfig::new().with_mode(SearchMode::Bm25);
+        assert_eq!(searcher.similar("doc1", &config).unwrap().len(), 1);
+        let config = config.with_topic_filter(Some(1));
+        assert!(searcher.similar("doc1", &config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_collapse_duplicates() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("bm25_index.json"),
+            r#"{"version": "1.0", "doc_ids": ["src", "doc1", "doc1-copy", "doc2"],
+                "corpus": [["x"], ["deploy", "api", "server", "after", "migrations"],
+                           ["deploy", "api", "server", "after", "migrations"], ["hiring", "plan"]]}"#,
+        )
+        .unwrap();
+        let mut vector_index = VectorIndex::new(2);
+        for (id, vector) in [
+            ("src", [1.0, 0.0]),
+            ("doc1", [0.9, 0.1]),
+            ("doc1-copy", [0.95, 0.05]),
+            ("doc2", [0.5, 0.5]),
+        ] {
+            vector_index.add(id.to_string(), vector.to_vec()).unwrap();
+        }
+        vector_index
+            .save_to_file(&temp_dir.path().join("faiss_index.json"))
+            .unwrap();
+        let mut searcher = Searcher::new(temp_dir.path()).unwrap();
+        for id in ["src", "doc1", "doc1-copy", "doc2"] {
+            searcher.docstore.add(crate::loader::Document::with_id(
+                id.to_string(),
+                id.to_string(),
+                chrono::Utc::now(),
+                vec![],
+                String::new(),
+            ));
+        }
+
+        let clusters = searcher.find_duplicates(&DuplicateDetector::new());
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].doc_ids, vec!["doc1", "doc1-copy"]);
+
+        let config = SearchConfig::new().with_mode(SearchMode::Semantic);
+        let ids = |config: &SearchConfig| -> Vec<String> {
+            searcher
+                .similar("src", config)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.doc_id)
+                .collect()
+        };
+        assert_eq!(ids(&config), vec!["doc1-copy", "doc1", "doc2"]);
+        assert_eq!(
+            ids(&config.with_collapse_duplicates(true)),
+            vec!["doc1-copy", "doc2"]
+        );
+    }
+
+    #[test]
+    fn test_bounded_pages_match_full_ranking() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        // Scores repeat every five memos, so ties straddle the first cut
+        let ids: Vec<String> = (0..40).map(|i| format!("doc{:02}", i)).collect();
+        let corpus: Vec<Vec<&str>> = (0..40)
+            .map(|i| {
+                let mut tokens = vec!["DEPLOY"; i % 5 + 1];
+                tokens.extend(["NOTE"; 5]);
+                tokens
+            })
+            .collect();
+        std::fs::write(
+            dir.join("bm25_index.json"),
+            serde_json::json!({"version": "1.0", "doc_ids": ids, "corpus": corpus}).to_string(),
+        )
+        .unwrap();
+        let mut docstore = Docstore::new();
+        for (i, id) in ids.iter().enumerate() {
+            let tag = if i % 7 == 0 { "ops" } else { "memo" };
+            docstore.add(crate::loader::Document::with_id(
+                id.clone(),
+                id.clone(),
+                chrono::Utc::now() - chrono::Duration::days(40 - i as i64),
+                vec![tag.to_string()],
+                String::new(),
+            ));
+        }
+        docstore.save_to_file(&dir.join("docstore.json")).unwrap();
+        let searcher = Searcher::new(dir).unwrap();
+
+        // The first page only ranks part of the matches
+        let first = searcher
+            .search_page(
+                "deploy",
+                &SearchConfig::new()
+                    .with_mode(SearchMode::Bm25)
+                    .with_top_k(3),
+            )
+            .unwrap();
+        assert!(first.total < 40);
+        assert!(first.has_more());
+        assert!(!first.total_exact);
+
+        for config in [
+            SearchConfig::new()
+                .with_mode(SearchMode::Bm25)
+                .with_top_k(3),
+            SearchConfig::new()
+                .with_mode(SearchMode::Bm25)
+                .with_top_k(2)
+                .with_tag_filter(Some("ops".to_string())),
+        ] {
+            let expected: Vec<String> = searcher
+                .rank("deploy", &config)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.doc_id)
+                .collect();
+
+            let first = searcher.search_page("deploy", &config).unwrap();
+            let mut paged: Vec<String> = first.items.into_iter().map(|r| r.doc_id).collect();
+            let mut cursor = first.next_cursor;
+            while let Some(next) = cursor {
+                let config = config.clone().with_cursor(Some(next));
+                let page = searcher.search_page("deploy", &config).unwrap();
+                paged.extend(page.items.into_iter().map(|r| r.doc_id));
+                cursor = page.next_cursor;
+            }
+            assert_eq!(paged, expected);
+
+            let config = config.clone().with_offset(expected.len() - 1);
+            let last = searcher.search_page("deploy", &config).unwrap();
+            assert_eq!(last.total, expected.len());
+            assert_eq!(last.items[0].doc_id, expected[expected.len() - 1]);
+        }
+    }
+
+    #[test]
+    fn test_pages_do_not_overlap() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let searcher = Searcher::new(temp_dir.path()).unwrap();
+
+        // "a" and "b" tie on score, so doc_id decides the order
+        let mut results = vec![
+            SearchResult::new("b".to_string(), 1.0),
+            SearchResult::new("a".to_string(), 1.0),
+            SearchResult::new("c".to_string(), 2.0),
+        ];
+        searcher.sort_results(&mut results);
+        let ids: Vec<&str> = results.iter().map(|r| r.doc_id.as_str()).collect();
+        assert_eq!(ids, vec!["c", "a", "b"]);
+
+        let page1 = searcher
+            .paginate_ranked(results.clone(), &SearchConfig::new().with_top_k(2))
+            .unwrap();
+        let page2 = searcher
+            .paginate_ranked(
+                results,
+                &SearchConfig::new()
+                    .with_top_k(2)
+                    .with_cursor(page1.next_cursor.clone()),
+            )
+            .unwrap();
+        assert_eq!(page1.items.len(), 2);
+        assert_eq!(page2.items.len(), 1);
+        assert_eq!(page2.items[0].doc_id, "b");
+        assert_eq!(page2.offset, 2);
+    }
+
+    #[test]
+    fn test_similar_unknown_document() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let searcher = Searcher::new(temp_dir.path()).unwrap();
+        assert!(searcher.similar("missing", &SearchConfig::new()).is_err());
+    }
+
+    #[test]
+    fn test_jaccard_similarity() {
+        let a: HashSet<&str> = ["a", "b"].into_iter().collect();
+        let b: HashSet<&str> = ["b", "c"].into_iter().collect();
+        assert!((jaccard_similarity(&a, &b) - 1.0 / 3.0).abs() < 1e-6);
+        assert_eq!(jaccard_similarity(&HashSet::new(), &HashSet::new()), 0.0);
+    }
+
     // TODO: Add more tests in Process 9
 }
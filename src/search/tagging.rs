@@ -0,0 +1,306 @@
+//! Tag suggestions for untagged memos
+//!
+//! [`TagSuggester`] predicts tags by nearest-neighbour voting: the memo's
+//! most similar tagged memos each vote for their authored tags, weighted by
+//! similarity. Neighbours come from the stored embeddings when the index has
+//! them and from BM25 "more like this" queries otherwise.
+
+use super::Searcher;
+use crate::config::{SearchConfig, SearchMode};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Default number of tagged neighbours that vote
+pub const DEFAULT_SUGGEST_NEIGHBORS: usize = 5;
+
+/// Default minimum share of the vote a tag needs to be suggested
+pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.3;
+
+/// Default maximum number of tags suggested per memo
+pub const DEFAULT_MAX_SUGGESTED_TAGS: usize = 3;
+
+/// Candidates fetched per voting neighbour; the pool grows by this factor
+/// until enough tagged neighbours are found
+const NEIGHBOR_CANDIDATE_FACTOR: usize = 4;
+
+/// A suggested tag with its share of the neighbour vote
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SuggestedTag {
+    /// Tag name
+    pub tag: String,
+    /// Confidence between 0 and 1
+    pub confidence: f32,
+}
+
+/// Suggested tags for one memo
+#[derive(Debug, Clone, Serialize)]
+pub struct TagSuggestion {
+    /// Document ID
+    pub doc_id: String,
+    /// Document title
+    pub title: String,
+    /// Suggested tags, most confident first
+    pub tags: Vec<SuggestedTag>,
+}
+
+impl TagSuggestion {
+    /// Tag names without confidences
+    pub fn tag_names(&self) -> Vec<String> {
+        self.tags.iter().map(|t| t.tag.clone()).collect()
+    }
+}
+
+/// Predicts tags from similar tagged documents
+#[derive(Debug, Clone)]
+pub struct TagSuggester {
+    neighbors: usize,
+    min_confidence: f32,
+    max_tags: usize,
+}
+
+impl Default for TagSuggester {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TagSuggester {
+    /// Create a suggester with the default settings
+    pub fn new() -> Self {
+        Self {
+            neighbors: DEFAULT_SUGGEST_NEIGHBORS,
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+            max_tags: DEFAULT_MAX_SUGGESTED_TAGS,
+        }
+    }
+
+    /// Set how many tagged neighbours vote
+    pub fn with_neighbors(mut self, neighbors: usize) -> Self {
+        self.neighbors = neighbors.max(1);
+        self
+    }
+
+    /// Set the minimum confidence of a suggested tag
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = min_confidence.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the maximum number of tags suggested per memo
+    pub fn with_max_tags(mut self, max_tags: usize) -> Self {
+        self.max_tags = max_tags;
+        self
+    }
+
+    /// Suggest tags for one document
+    ///
+    /// Only authored tags of neighbours vote, so inferred tags never
+    /// reinforce themselves. A few candidates per neighbour are fetched
+    /// first, and more only when too many of them are untagged.
+    pub fn suggest(&self, searcher: &Searcher, doc_id: &str) -> Result<Vec<SuggestedTag>> {
+        let docstore = searcher.docstore();
+        let mode = if searcher.has_vector_index() {
+            SearchMode::Semantic
+        } else {
+            SearchMode::Bm25
+        };
+
+        let mut depth = self.neighbors * NEIGHBOR_CANDIDATE_FACTOR;
+        let neighbors = loop {
+            let config = SearchConfig::new().with_mode(mode).with_top_k(depth);
+            let results = searcher.similar(doc_id, &config)?;
+            // Results are sorted by score, so a non-positive score or a short
+            // page means a deeper search finds no more neighbours
+            let exhausted = results.len() < depth || results.last().is_some_and(|r| r.score <= 0.0);
+            let neighbors: Vec<_> = results
+                .into_iter()
+                .filter_map(|r| Some((docstore.get(&r.doc_id)?, r.score)))
+                .filter(|(doc, score)| !doc.tags().is_empty() && *score > 0.0)
+                .take(self.neighbors)
+                .collect();
+            if neighbors.len() == self.neighbors || exhausted {
+                break neighbors;
+            }
+            depth *= NEIGHBOR_CANDIDATE_FACTOR;
+        };
+
+        let mut votes: HashMap<&str, f32> = HashMap::new();
+        let mut total = 0.0;
+        for (doc, score) in neighbors {
+            for tag in doc.tags() {
+                *votes.entry(tag).or_default() += score;
+            }
+            total += score;
+        }
+        if total <= 0.0 {
+            return Ok(Vec::new());
+        }
+
+        let mut tags: Vec<SuggestedTag> = votes
+            .into_iter()
+            .map(|(tag, score)| SuggestedTag {
+                tag: tag.to_string(),
+                confidence: score / total,
+            })
+            .filter(|t| t.confidence >= self.min_confidence)
+            .collect();
+        tags.sort_by(|a, b| {
+            b.confidence
+                .partial_cmp(&a.confidence)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.tag.cmp(&b.tag))
+        });
+        tags.truncate(self.max_tags);
+        Ok(tags)
+    }
+
+    /// Suggest tags for every document without authored tags
+    ///
+    /// Documents with no confident suggestion are left out. Results are
+    /// ordered newest first.
+    pub fn suggest_untagged(&self, searcher: &Searcher) -> Result<Vec<TagSuggestion>> {
+        let docstore = searcher.docstore();
+        let mut suggestions = Vec::new();
+        for doc in docstore.get_recent(docstore.len()) {
+            if !doc.tags().is_empty() {
+                continue;
+            }
+            let tags = self.suggest(searcher, &doc.id)?;
+            if !tags.is_empty() {
+                suggestions.push(TagSuggestion {
+                    doc_id: doc.id.clone(),
+                    title: doc.title().to_string(),
+                    tags,
+                });
+            }
+        }
+        Ok(suggestions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::{Docstore, VectorIndex};
+    use crate::loader::Document;
+    use chrono::{TimeZone, Utc};
+    use std::path::Path;
+
+    /// Index of tagged Rust and cooking memos plus two untagged memos
+    fn setup_index(dir: &Path) {
+        let memos = [
+            ("rust1", vec!["rust"], [1.0, 0.0]),
+            ("rust2", vec!["rust", "cli"], [0.9, 0.1]),
+            ("food1", vec!["cooking"], [0.0, 1.0]),
+            ("new-rust", vec![], [0.95, 0.05]),
+            ("new-food", vec![], [0.1, 0.9]),
+        ];
+
+        let mut docstore = Docstore::new();
+        let mut vectors = VectorIndex::new(2);
+        for (i, (id, tags, vector)) in memos.into_iter().enumerate() {
+            docstore.add(Document::with_id(
+                id.to_string(),
+                id.to_string(),
+                Utc.with_ymd_and_hms(2025, 1, 1 + i as u32, 10, 0, 0)
+                    .unwrap(),
+                tags.into_iter().map(String::from).collect(),
+                String::new(),
+            ));
+            vectors.add(id.to_string(), vector.to_vec()).unwrap();
+        }
+        docstore.save_to_file(&dir.join("docstore.json")).unwrap();
+        vectors.save_to_file(&dir.join("faiss_index.json")).unwrap();
+    }
+
+    #[test]
+    fn test_suggest_votes_by_similarity() {
+        let dir = tempfile::tempdir().unwrap();
+        setup_index(dir.path());
+        let searcher = Searcher::new(dir.path()).unwrap();
+
+        let tags = TagSuggester::new()
+            .with_neighbors(2)
+            .suggest(&searcher, "new-rust")
+            .unwrap();
+        assert_eq!(tags[0].tag, "rust");
+        assert!((tags[0].confidence - 1.0).abs() < 1e-6);
+        assert!(tags.iter().all(|t| t.tag != "cooking"));
+    }
+
+    #[test]
+    fn test_suggest_widens_past_untagged_neighbours() {
+        let dir = tempfile::tempdir().unwrap();
+        setup_index(dir.path());
+        // More untagged memos closer to "new-rust" than the first candidate
+        // pool holds
+        let mut docstore = Docstore::load_from_file(&dir.path().join("docstore.json")).unwrap();
+        let mut vectors =
+            VectorIndex::load_from_file(&dir.path().join("faiss_index.json")).unwrap();
+        for i in 0..6 {
+            let id = format!("draft{}", i);
+            docstore.add(Document::with_id(
+                id.clone(),
+                id.clone(),
+                Utc.with_ymd_and_hms(2025, 2, 1 + i, 10, 0, 0).unwrap(),
+                vec![],
+                String::new(),
+            ));
+            vectors
+                .add(id, vec![0.95, 0.05 + 0.001 * i as f32])
+                .unwrap();
+        }
+        docstore
+            .save_to_file(&dir.path().join("docstore.json"))
+            .unwrap();
+        vectors
+            .save_to_file(&dir.path().join("faiss_index.json"))
+            .unwrap();
+        let searcher = Searcher::new(dir.path()).unwrap();
+
+        let tags = TagSuggester::new()
+            .with_neighbors(1)
+            .suggest(&searcher, "new-rust")
+            .unwrap();
+        assert_eq!(tags[0].tag, "rust");
+    }
+
+    #[test]
+    fn test_suggest_untagged_skips_tagged_memos() {
+        let dir = tempfile::tempdir().unwrap();
+        setup_index(dir.path());
+        let searcher = Searcher::new(dir.path()).unwrap();
+
+        let suggestions = TagSuggester::new()
+            .with_neighbors(1)
+            .suggest_untagged(&searcher)
+            .unwrap();
+        let ids: Vec<&str> = suggestions.iter().map(|s| s.doc_id.as_str()).collect();
+        assert_eq!(ids, vec!["new-food", "new-rust"]);
+        assert_eq!(suggestions[0].tag_names(), vec!["cooking"]);
+    }
+
+    #[test]
+    fn test_min_confidence_and_max_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        setup_index(dir.path());
+        let searcher = Searcher::new(dir.path()).unwrap();
+
+        let tags = TagSuggester::new()
+            .with_neighbors(3)
+            .with_min_confidence(0.0)
+            .with_max_tags(1)
+            .suggest(&searcher, "new-rust")
+            .unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].tag, "rust");
+
+        let tags = TagSuggester::new()
+            .with_neighbors(3)
+            .with_min_confidence(1.0)
+            .suggest(&searcher, "new-rust")
+            .unwrap();
+        assert!(tags.is_empty());
+    }
+}
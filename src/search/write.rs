@@ -0,0 +1,144 @@
+//! Recording new memos
+//!
+//! [`MemoWriter`] appends a memo to the changelog, the source of truth, and
+//! then inserts it into the live index and saves that index, so the memo is
+//! searchable immediately and survives a restart without a rebuild.
+
+use super::SharedIndexes;
+use crate::loader::{ChangelogWriter, Document, NewMemo};
+use anyhow::{anyhow, Context, Result};
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+/// Writes memos to a changelog and keeps one named index in sync
+pub struct MemoWriter {
+    changelog: ChangelogWriter,
+    index_name: String,
+    index_dir: PathBuf,
+    /// Serializes writers so concurrent inserts do not drop each other
+    lock: Mutex<()>,
+}
+
+impl MemoWriter {
+    /// Create a writer updating the index `index_name` stored in `index_dir`
+    pub fn new(changelog: ChangelogWriter, index_name: &str, index_dir: PathBuf) -> Self {
+        Self {
+            changelog,
+            index_name: index_name.to_string(),
+            index_dir,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Changelog file memos are written to
+    pub fn changelog(&self) -> &ChangelogWriter {
+        &self.changelog
+    }
+
+    /// Name of the index new memos are added to
+    pub fn index_name(&self) -> &str {
+        &self.index_name
+    }
+
+    /// Write a memo and add it to the index
+    ///
+    /// The changelog is written first; if updating the index fails the memo
+    /// is still recorded and will be picked up by the next build. The memo
+    /// is embedded first, then the file writes and the index update run on
+    /// the blocking thread pool.
+    pub async fn add(&self, indexes: &SharedIndexes, memo: &NewMemo) -> Result<Document> {
+        let _guard = self.lock.lock().await;
+
+        let current = indexes.load();
+        let searcher = current
+            .get(&self.index_name)
+            .ok_or_else(|| anyhow!("Unknown index '{}'", self.index_name))?;
+        let embedding = searcher.embed_document(&memo.to_document()?).await;
+
+        let changelog = self.changelog.clone();
+        let index_name = self.index_name.clone();
+        let index_dir = self.index_dir.clone();
+        let memo = memo.clone();
+        let (document, updated) = tokio::task::spawn_blocking(move || -> Result<_> {
+            let mut searcher = current
+                .get(&index_name)
+                .ok_or_else(|| anyhow!("Unknown index '{}'", index_name))?
+                .clone();
+            let document = changelog.write(&memo)?;
+            searcher.insert_document(document.clone(), embedding)?;
+            searcher.save(&index_dir).with_context(|| {
+                format!(
+                    "Memo written to {} but saving index {} failed",
+                    changelog.path().display(),
+                    index_dir.display()
+                )
+            })?;
+            Ok((document, current.with_searcher(&index_name, searcher)?))
+        })
+        .await??;
+        indexes.store(updated);
+
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::IndexBuilder;
+    use crate::loader::{ChangelogLoader, WritePosition};
+    use crate::search::{IndexSet, Searcher};
+
+    #[tokio::test]
+    async fn test_add_memo_updates_live_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let changelog = dir.path().join("changelog.md");
+        let index_dir = dir.path().join(".rag");
+        std::fs::write(&changelog, "* Old 2025-01-01 10:00:00 [memo]:\nold\n").unwrap();
+        IndexBuilder::new().build(&changelog, &index_dir).unwrap();
+
+        let indexes =
+            SharedIndexes::new(IndexSet::new("default", Searcher::new(&index_dir).unwrap()));
+        let writer = MemoWriter::new(
+            ChangelogWriter::new(&changelog, WritePosition::Prepend),
+            "default",
+            index_dir.clone(),
+        );
+        let memo = NewMemo::new(
+            "New memo".to_string(),
+            vec!["idea".to_string()],
+            "body".to_string(),
+        );
+        let doc = writer.add(&indexes, &memo).await.unwrap();
+
+        // Searchable immediately
+        let current = indexes.load();
+        assert_eq!(current.primary().docstore().len(), 2);
+        assert!(current.primary().docstore().contains(&doc.id));
+        assert!(current.primary().list_tags().contains(&"idea".to_string()));
+
+        // Recorded in the changelog and persisted to the index directory
+        let docs = ChangelogLoader::new().load_from_file(&changelog).unwrap();
+        assert_eq!(docs[0].id, doc.id);
+        let reloaded = Searcher::new(&index_dir).unwrap();
+        assert!(reloaded.docstore().contains(&doc.id));
+        assert_eq!(reloaded.created_at(), current.primary().created_at());
+        let metadata = IndexBuilder::load_existing_metadata(&index_dir).unwrap();
+        assert_eq!(metadata.get_doc_hash(&doc.id), Some(&doc.content_hash()));
+    }
+
+    #[tokio::test]
+    async fn test_add_memo_unknown_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let indexes =
+            SharedIndexes::new(IndexSet::new("default", Searcher::new(dir.path()).unwrap()));
+        let writer = MemoWriter::new(
+            ChangelogWriter::new(dir.path().join("changelog.md"), WritePosition::Append),
+            "work",
+            dir.path().to_path_buf(),
+        );
+        let memo = NewMemo::new("Title".to_string(), vec![], "body".to_string());
+        assert!(writer.add(&indexes, &memo).await.is_err());
+        assert!(!dir.path().join("changelog.md").exists());
+    }
+}
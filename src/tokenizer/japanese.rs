@@ -10,9 +10,10 @@ use lindera::{
     segmenter::Segmenter,
     tokenizer::Tokenizer as LinderaTokenizer,
 };
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use regex::Regex;
 use std::collections::HashSet;
+use std::sync::Arc;
 
 /// Target POS (Part of Speech) categories to extract
 const TARGET_POS: &[&str] = &["名詞", "動詞", "形容詞", "副詞"];
@@ -27,6 +28,9 @@ static ENGLISH_TOKEN_REGEX: Lazy<Regex> =
 /// Compiled regex for extracting pure numeric sequences
 static NUMERIC_TOKEN_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\d+").expect("Invalid regex"));
 
+/// Tokenizer shared by the indexes and summarizers
+static SHARED: OnceCell<Arc<JapaneseTokenizer>> = OnceCell::new();
+
 /// Japanese text tokenizer using Lindera
 pub struct JapaneseTokenizer {
     /// Lindera tokenizer instance
@@ -54,6 +58,16 @@ impl JapaneseTokenizer {
         Ok(Self { tokenizer })
     }
 
+    /// Process-wide tokenizer
+    ///
+    /// Loading the dictionary is slow, so the first call loads it and later
+    /// calls reuse the same tokenizer.
+    pub fn shared() -> Result<Arc<Self>> {
+        SHARED
+            .get_or_try_init(|| Self::new().map(Arc::new))
+            .cloned()
+    }
+
     /// Tokenize a text string
     ///
     /// Returns a vector of tokens with base forms extracted.
@@ -245,6 +259,13 @@ mod tests {
         assert!(tokenizer.is_ok());
     }
 
+    #[test]
+    fn test_shared_tokenizer_loaded_once() {
+        let first = JapaneseTokenizer::shared().unwrap();
+        let second = JapaneseTokenizer::shared().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
     #[test]
     fn test_target_pos() {
         let pos = JapaneseTokenizer::target_pos();
@@ -0,0 +1,119 @@
+//! Question answering tests
+//!
+//! Runs `QuestionAnswerer` against a wiremock stand-in for the OpenRouter API.
+
+use chrono::{TimeZone, Utc};
+use digrag::extract::openrouter_client::OpenRouterClient;
+use digrag::loader::Document;
+use digrag::search::QuestionAnswerer;
+use wiremock::matchers::{body_string_contains, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn memo(id: &str, title: &str, day: u32, text: &str) -> Document {
+    Document::with_id(
+        id.to_string(),
+        title.to_string(),
+        Utc.with_ymd_and_hms(2025, 7, day, 9, 0, 0).unwrap(),
+        vec!["worklog".to_string()],
+        text.to_string(),
+    )
+}
+
+fn chat_response(content: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": "gen-1",
+        "model": "test/chat-model",
+        "choices": [{
+            "message": { "role": "assistant", "content": content },
+            "finish_reason": "stop"
+        }],
+        "usage": { "prompt_tokens": 120, "completion_tokens": 30, "total_tokens": 150 }
+    })
+}
+
+#[tokio::test]
+async fn test_answer_maps_citations_to_memos() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(header("Authorization", "Bearer test-api-key"))
+        .and(body_string_contains("[2] Rollback plan (2025-07-03)"))
+        .and(body_string_contains(
+            "Question: How do we roll back a release?",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(chat_response(
+            "Revert the deploy tag and rerun the pipeline [2], then announce it [1, 2].",
+        )))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client =
+        OpenRouterClient::with_config("test-api-key", Some(mock_server.uri()), None, Some(0));
+    let answerer = QuestionAnswerer::new(client, "test/chat-model");
+    let docs = [
+        memo("a1", "Release notes", 1, "Announce releases in #general."),
+        memo("b2", "Rollback plan", 3, "Revert the deploy tag and rerun."),
+        memo("c3", "Lunch", 4, "Pizza on Friday."),
+    ];
+
+    let passages = answerer.pack(docs.iter().map(|doc| (doc, None)));
+    let answer = answerer
+        .answer("How do we roll back a release?", passages)
+        .await
+        .unwrap();
+
+    assert!(answer.answer.contains("[2]"));
+    let cited: Vec<(&str, &str)> = answer
+        .citations
+        .iter()
+        .map(|c| (c.doc_id.as_str(), c.date.as_str()))
+        .collect();
+    assert_eq!(cited, vec![("a1", "2025-07-01"), ("b2", "2025-07-03")]);
+    assert_eq!(answer.sources.len(), 3);
+    assert_eq!(answer.model.as_deref(), Some("test/chat-model"));
+    assert_eq!(answer.usage.unwrap().total_tokens, 150);
+}
+
+#[tokio::test]
+async fn test_answer_without_passages_skips_the_model() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(chat_response("unused")))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let client = OpenRouterClient::with_config("key", Some(mock_server.uri()), None, Some(0));
+    let answer = QuestionAnswerer::new(client, "test/chat-model")
+        .answer("Anything?", Vec::new())
+        .await
+        .unwrap();
+
+    assert!(answer.citations.is_empty());
+    assert!(answer.model.is_none());
+}
+
+#[tokio::test]
+async fn test_answer_surfaces_api_errors() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "error": { "message": "Invalid API key" }
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = OpenRouterClient::with_config("bad-key", Some(mock_server.uri()), None, Some(0));
+    let answerer = QuestionAnswerer::new(client, "test/chat-model");
+    let docs = [memo("a1", "Release notes", 1, "Announce releases.")];
+
+    let result = answerer
+        .answer(
+            "Who announces releases?",
+            answerer.pack(docs.iter().map(|d| (d, None))),
+        )
+        .await;
+    assert!(result.is_err());
+}
@@ -193,6 +193,30 @@ summarization_temperature = 0.5
     assert!((config.summarization_temperature() - 0.5).abs() < 0.001);
 }
 
+#[test]
+fn test_summarization_strategy_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+
+    assert_eq!(AppConfig::default().summarization_strategy(), "rule-based");
+
+    std::fs::write(
+        &config_path,
+        r#"
+index_dir = ".rag"
+summarization_strategy = "extractive"
+"#,
+    )
+    .unwrap();
+    let config = AppConfig::from_file(&config_path).unwrap();
+    assert_eq!(config.summarization_strategy(), "extractive");
+    assert!(config.validate().is_ok());
+
+    std::fs::write(&config_path, "summarization_strategy = \"textrank\"\n").unwrap();
+    let config = AppConfig::from_file(&config_path).unwrap();
+    assert!(config.validate().is_err());
+}
+
 #[test]
 fn test_provider_config_defaults() {
     let config = AppConfig::default();
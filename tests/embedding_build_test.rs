@@ -3,7 +3,7 @@
 //! TDD tests for Process 40: Embedding Build統合（CLI + IndexBuilder）
 //! Red Phase: These tests should FAIL initially, then pass after Green Phase implementation
 
-use digrag::index::{IndexBuilder, VectorIndex};
+use digrag::index::{Docstore, IndexBuilder, VectorIndex};
 use tempfile::TempDir;
 use wiremock::matchers::{header, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -464,3 +464,69 @@ async fn test_metadata_includes_embedding_model() {
         "Should use correct model"
     );
 }
+
+/// Test 11: Tag edits re-embed documents that have a vector
+#[tokio::test]
+async fn test_refresh_embeddings_after_tag_update() {
+    use std::collections::HashMap;
+    use wiremock::matchers::body_string_contains;
+
+    let mock_server = MockServer::start().await;
+    let response = |vector: Vec<f32>| {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "data": [{ "embedding": vector, "index": 0 }],
+            "model": "openai/text-embedding-3-small"
+        }))
+    };
+    Mock::given(method("POST"))
+        .and(path("/embeddings"))
+        .and(body_string_contains("renamed"))
+        .respond_with(response(vec![0.0, 1.0, 0.0]))
+        .with_priority(1)
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/embeddings"))
+        .respond_with(response(vec![1.0, 0.0, 0.0]))
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let input_path = temp_dir.path().join("test_changelog");
+    let output_dir = temp_dir.path().join("output");
+    std::fs::write(
+        &input_path,
+        "* Test Entry 2025-01-15 10:00:00 [memo]:\n  Test content\n",
+    )
+    .expect("Failed to write test file");
+
+    let builder =
+        IndexBuilder::with_embeddings_and_base_url("test-api-key".to_string(), mock_server.uri());
+    builder
+        .build_with_embeddings(&input_path, &output_dir, |_, _, _| {})
+        .await
+        .expect("Build should succeed");
+
+    let docstore = Docstore::load_from_file(&output_dir.join("docstore.json")).unwrap();
+    let doc_id = docstore.documents().keys().next().unwrap().clone();
+    let tags = HashMap::from([
+        (doc_id.clone(), vec!["renamed".to_string()]),
+        ("missing".to_string(), vec!["renamed".to_string()]),
+    ]);
+    assert_eq!(IndexBuilder::update_tags(&output_dir, &tags).unwrap(), 1);
+
+    let ids: Vec<String> = tags.into_keys().collect();
+    assert_eq!(
+        IndexBuilder::embedded_documents(&output_dir, &ids).unwrap(),
+        vec![doc_id.clone()]
+    );
+    assert_eq!(
+        builder.refresh_embeddings(&output_dir, &ids).await.unwrap(),
+        1
+    );
+
+    let vectors = VectorIndex::load_from_file(&output_dir.join("faiss_index.json")).unwrap();
+    assert_eq!(vectors.len(), 1);
+    assert_eq!(vectors.get_vector(&doc_id).unwrap(), &[0.0, 1.0, 0.0]);
+}
@@ -2,12 +2,17 @@
 //!
 //! Tests for content summarization functionality
 
+use digrag::config::app_config::AppConfig;
+use digrag::extract::cache::SummaryCache;
 use digrag::extract::openrouter_client::OpenRouterClient;
+use digrag::extract::persistent_cache::PersistentSummaryCache;
+use digrag::extract::prompt::{PromptTemplate, SummaryContext};
 use digrag::extract::summarizer::{
-    ContentSummarizer, ProviderConfig, SummarizationStrategy, Summary,
+    ContentSummarizer, DigestSource, ProviderConfig, SummarizationStrategy, Summary, SummaryOptions,
 };
 use digrag::extract::{ContentStats, ExtractedContent};
-use wiremock::matchers::{header, method, path};
+use std::sync::Arc;
+use wiremock::matchers::{body_string_contains, header, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 // =============================================================================
@@ -182,6 +187,43 @@ async fn test_rule_based_japanese_content() {
     assert!(summary.text.contains("日本語"));
 }
 
+#[tokio::test]
+async fn test_extractive_summary_picks_central_sentences() {
+    let summarizer = ContentSummarizer::extractive(80);
+    let content = create_test_content(
+        "Lunch was pizza. The API server deploy failed at noon. \
+         We rolled back the API server deploy. The API server is stable again.",
+    );
+
+    let summary = summarizer.summarize(&content).await;
+
+    assert_eq!(summary.method, "extractive");
+    assert!(summary.text.chars().count() <= 80 + summary.text.lines().count());
+    assert!(summary.text.contains("API server"));
+    assert!(!summary.text.contains("pizza"));
+    assert!(summary.usage.is_none());
+}
+
+#[tokio::test]
+async fn test_llm_fallback_uses_extractive_when_configured() {
+    let summarizer = ContentSummarizer::new(
+        SummarizationStrategy::LlmBased {
+            model: "test-model".to_string(),
+            max_tokens: 100,
+            temperature: 0.3,
+            provider_config: ProviderConfig::default(),
+        },
+        None,
+    )
+    .with_extractive_fallback(200);
+
+    let content = create_test_content("Deployed the API server. It works.");
+    let summary = summarizer.summarize(&content).await;
+
+    assert_eq!(summary.method, "extractive");
+    assert_eq!(summary.text, "Deployed the API server.\nIt works.");
+}
+
 // =============================================================================
 // ContentSummarizer - Factory Methods Tests
 // =============================================================================
@@ -246,6 +288,7 @@ fn test_summary_struct() {
     let summary = Summary {
         text: "Summary text".to_string(),
         method: "rule-based".to_string(),
+        model: None,
         stats: ContentStats {
             total_chars: 100,
             total_lines: 5,
@@ -353,3 +396,409 @@ async fn test_llm_summarization_fallback_on_error() {
     // Should fallback to rule-based on API error
     assert_eq!(summary.method, "rule-based");
 }
+
+// =============================================================================
+// Combined Digest Tests (with Mock Server)
+// =============================================================================
+
+fn digest_source(id: &str, title: &str, text: &str) -> DigestSource {
+    DigestSource {
+        doc_id: id.to_string(),
+        title: title.to_string(),
+        date: "2025-07-01".to_string(),
+        content: create_test_content(text),
+    }
+}
+
+fn mock_completion(content: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": "gen-digest",
+        "model": "test-model",
+        "choices": [{
+            "message": { "role": "assistant", "content": content },
+            "finish_reason": "stop"
+        }],
+        "usage": { "prompt_tokens": 100, "completion_tokens": 50, "total_tokens": 150 }
+    })
+}
+
+fn llm_summarizer(mock_server: &MockServer) -> ContentSummarizer {
+    ContentSummarizer::llm_based(
+        "test-model".to_string(),
+        200,
+        0.3,
+        ProviderConfig::default(),
+        "test-api-key".to_string(),
+    )
+    .with_client(OpenRouterClient::with_config(
+        "test-api-key",
+        Some(mock_server.uri()),
+        None,
+        Some(0),
+    ))
+}
+
+#[tokio::test]
+async fn test_digest_makes_one_cached_request() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_string_contains("## Deploy (2025-07-01)"))
+        .and(body_string_contains("## Rollback (2025-07-01)"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_completion("- まとめ")))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let cache = Arc::new(SummaryCache::for_summaries());
+    let summarizer = llm_summarizer(&mock_server).with_cache(cache.clone());
+    let sources = [
+        digest_source("a", "Deploy", "Deployed v2."),
+        digest_source("b", "Rollback", "Rolled back v2."),
+    ];
+
+    let digest = summarizer.digest(&sources).await;
+    assert_eq!(digest.method, "llm");
+    assert_eq!(digest.text, "- まとめ");
+    assert_eq!(digest.usage.unwrap().total_tokens, 150);
+
+    // The same set of memos in another order is served from the cache
+    let reversed = [sources[1].clone(), sources[0].clone()];
+    let cached = summarizer.digest(&reversed).await;
+    assert_eq!(cached.text, "- まとめ");
+    assert!(cached.usage.is_none());
+    assert_eq!(cache.stats().hits, 1);
+}
+
+#[tokio::test]
+async fn test_digest_cache_keyed_on_extracted_text() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_completion("- まとめ")))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let cache = Arc::new(SummaryCache::for_summaries());
+    let summarizer = llm_summarizer(&mock_server).with_cache(cache.clone());
+    // The same memo extracted with a smaller and a larger character budget
+    let short = [digest_source("a", "Deploy", "Deployed v2.")];
+    let long = [digest_source(
+        "a",
+        "Deploy",
+        "Deployed v2. The migration took an hour.",
+    )];
+
+    assert!(summarizer.digest(&short).await.usage.is_some());
+    assert!(summarizer.digest(&long).await.usage.is_some());
+    assert!(summarizer.digest(&short).await.usage.is_none());
+}
+
+#[tokio::test]
+async fn test_digest_map_reduce_over_budget() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_string_contains("部分要約"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_completion("統合した要約")))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_completion("部分")))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let summarizer = llm_summarizer(&mock_server).with_digest_chunk_chars(60);
+    let sources = [
+        digest_source("a", "Deploy", &"d".repeat(40)),
+        digest_source("b", "Rollback", &"r".repeat(40)),
+    ];
+
+    let digest = summarizer.digest(&sources).await;
+    assert_eq!(digest.text, "統合した要約");
+    assert_eq!(digest.usage.unwrap().total_tokens, 450);
+    assert_eq!(digest.stats.total_chars, 80);
+}
+
+#[tokio::test]
+async fn test_digest_merges_partials_in_rounds() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_string_contains("部分要約"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_completion("統合した要約")))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_completion(&"p".repeat(25))))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    // Two 25-char partials fit in the 60-char budget, three do not, so the
+    // partials are merged in two groups and then once more
+    let summarizer = llm_summarizer(&mock_server).with_digest_chunk_chars(60);
+    let sources = [
+        digest_source("a", "Deploy", &"d".repeat(40)),
+        digest_source("b", "Rollback", &"r".repeat(40)),
+        digest_source("c", "Postmortem", &"m".repeat(40)),
+    ];
+
+    let digest = summarizer.digest(&sources).await;
+    assert_eq!(digest.text, "統合した要約");
+    assert_eq!(digest.usage.unwrap().total_tokens, 900);
+}
+
+#[tokio::test]
+async fn test_rule_based_digest_lists_every_memo() {
+    let summarizer = ContentSummarizer::rule_based(20);
+    let digest = summarizer
+        .digest(&[
+            digest_source("a", "Deploy", "Deployed v2."),
+            digest_source("b", "Rollback", "Rolled back v2."),
+        ])
+        .await;
+
+    assert_eq!(digest.method, "rule-based");
+    assert_eq!(digest.text.lines().count(), 2);
+    assert!(digest
+        .text
+        .contains("Rollback (2025-07-01): Rolled back v2."));
+}
+
+#[tokio::test]
+async fn test_persistent_cache_survives_restart() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_completion("- 要約")))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let cache_path = temp_dir.path().join("cache").join("summaries.db");
+    let content = create_test_content("Deployed v2 and rolled it back.");
+    let sources = [digest_source("a", "Deploy", "Deployed v2.")];
+
+    // Each summarizer opens the cache file anew, like separate CLI runs
+    for _ in 0..2 {
+        let store = Arc::new(PersistentSummaryCache::open(&cache_path).unwrap());
+        let summarizer = llm_summarizer(&mock_server).with_persistent_cache(store);
+        assert_eq!(summarizer.summarize(&content).await.text, "- 要約");
+        assert_eq!(summarizer.digest(&sources).await.text, "- 要約");
+    }
+
+    let stats = PersistentSummaryCache::open(&cache_path)
+        .unwrap()
+        .stats()
+        .unwrap();
+    assert_eq!(stats.entries, 2);
+    assert_eq!(stats.tokens, 300);
+}
+
+#[tokio::test]
+async fn test_prompt_template_rendered_and_versioned() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_string_contains(
+            "Summarize Deploy for rollback in English.",
+        ))
+        .and(body_string_contains("[ops] Deployed v2."))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_completion("- brief")))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_completion("- 要約")))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let store = Arc::new(PersistentSummaryCache::in_memory().unwrap());
+    let content = create_test_content("Deployed v2.");
+    let context = SummaryContext {
+        title: "Deploy".to_string(),
+        date: "2025-07-01".to_string(),
+        tags: vec!["ops".to_string()],
+        query: Some("rollback".to_string()),
+    };
+    let brief = PromptTemplate::new(
+        "brief",
+        "Summarize {title} for {query} in {language}.",
+        "[{tags}] {content}",
+    )
+    .with_language(Some("English".to_string()));
+
+    // The same template is served from the cache, another one is not
+    for _ in 0..2 {
+        let summarizer = llm_summarizer(&mock_server)
+            .with_persistent_cache(store.clone())
+            .with_prompt(brief.clone());
+        let summary = summarizer.summarize_with(&content, &context).await;
+        assert_eq!(summary.text, "- brief");
+    }
+    let summarizer = llm_summarizer(&mock_server).with_persistent_cache(store.clone());
+    let summary = summarizer.summarize_with(&content, &context).await;
+    assert_eq!(summary.text, "- 要約");
+    assert_eq!(store.size().unwrap(), 2);
+}
+
+// =============================================================================
+// Per-request Options Tests
+// =============================================================================
+
+fn bounded_config(enabled: bool) -> AppConfig {
+    toml::from_str(&format!(
+        r#"
+        openrouter_api_key = "test-api-key"
+        summarization_enabled = {}
+        summarization_model = "test-model"
+        summarization_allowed_models = ["other-model"]
+        summarization_max_tokens_limit = 1500
+        summarization_max_temperature = 0.8
+        "#,
+        enabled
+    ))
+    .unwrap()
+}
+
+#[test]
+fn test_summary_options_validate_bounds() {
+    let config = bounded_config(true);
+    let options =
+        |model: Option<&str>, max_tokens: Option<usize>, temperature: Option<f32>| SummaryOptions {
+            use_llm: Some(true),
+            model: model.map(String::from),
+            max_tokens,
+            temperature,
+        };
+
+    assert!(SummaryOptions::default().validate(&config).is_ok());
+    assert!(options(Some("test-model"), Some(1500), Some(0.0))
+        .validate(&config)
+        .is_ok());
+    assert!(options(Some("other-model"), None, Some(0.8))
+        .validate(&config)
+        .is_ok());
+    assert!(options(Some("expensive-model"), None, None)
+        .validate(&config)
+        .is_err());
+    assert!(options(None, Some(1501), None).validate(&config).is_err());
+    assert!(options(None, Some(0), None).validate(&config).is_err());
+    assert!(options(None, None, Some(0.9)).validate(&config).is_err());
+    assert!(options(None, None, Some(-0.1)).validate(&config).is_err());
+    assert!(ContentSummarizer::from_config_with(&config, &options(Some("x"), None, None)).is_err());
+}
+
+#[tokio::test]
+async fn test_per_request_options_override_config() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_string_contains(r#""model":"other-model""#))
+        .and(body_string_contains(r#""max_tokens":100"#))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_completion("- 要約")))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let content = create_test_content("Deployed v2 and rolled it back.");
+    let options = SummaryOptions {
+        use_llm: Some(true),
+        model: Some("other-model".to_string()),
+        max_tokens: Some(100),
+        temperature: None,
+    };
+
+    // LLM summaries on request even though config.toml leaves them off
+    let summary = ContentSummarizer::from_config_with(&bounded_config(false), &options)
+        .unwrap()
+        .with_client(OpenRouterClient::with_config(
+            "test-api-key",
+            Some(mock_server.uri()),
+            None,
+            Some(0),
+        ))
+        .summarize(&content)
+        .await;
+    assert_eq!(summary.method, "llm");
+    assert_eq!(summary.model.as_deref(), Some("other-model"));
+    assert_eq!(summary.usage.unwrap().total_tokens, 150);
+
+    // And offline summaries on request even though they are on
+    let options = SummaryOptions {
+        use_llm: Some(false),
+        ..Default::default()
+    };
+    let summary = ContentSummarizer::from_config_with(&bounded_config(true), &options)
+        .unwrap()
+        .summarize(&content)
+        .await;
+    assert_eq!(summary.method, "rule-based");
+    assert!(summary.model.is_none());
+}
+
+#[tokio::test]
+async fn test_cache_keyed_on_generation_settings() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_string_contains(r#""max_tokens":100"#))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_completion("- short")))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_string_contains(r#""max_tokens":1500"#))
+        .respond_with(ResponseTemplate::new(200).set_body_json(mock_completion("- long")))
+        .expect(2)
+        .mount(&mock_server)
+        .await;
+
+    let config = bounded_config(true);
+    let store = Arc::new(PersistentSummaryCache::in_memory().unwrap());
+    let cache = Arc::new(SummaryCache::for_summaries());
+    let content = create_test_content("Deployed v2 and rolled it back.");
+    let sources = [digest_source("a", "Deploy", "Deployed v2.")];
+
+    // One summary and one digest per setting; repeating the first setting
+    // is served from the caches
+    for (max_tokens, expected, fresh) in [
+        (100, "- short", true),
+        (1500, "- long", true),
+        (100, "- short", false),
+    ] {
+        let options = SummaryOptions {
+            max_tokens: Some(max_tokens),
+            ..Default::default()
+        };
+        // Separate summarizers sharing the caches, like consecutive requests
+        let summarizer = || {
+            ContentSummarizer::from_config_with(&config, &options)
+                .unwrap()
+                .with_client(OpenRouterClient::with_config(
+                    "test-api-key",
+                    Some(mock_server.uri()),
+                    None,
+                    Some(0),
+                ))
+                .with_persistent_cache(store.clone())
+                .with_cache(cache.clone())
+        };
+        let summary = summarizer().summarize(&content).await;
+        assert_eq!(summary.text, expected);
+        assert_eq!(summary.usage.is_some(), fresh);
+        let digest = summarizer().digest(&sources).await;
+        assert_eq!(digest.text, expected);
+    }
+}
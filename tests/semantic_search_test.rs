@@ -5,9 +5,9 @@
 use chrono::{TimeZone, Utc};
 use digrag::config::{SearchConfig, SearchMode};
 use digrag::embedding::OpenRouterEmbedding;
-use digrag::index::{Bm25Index, Docstore, VectorIndex};
+use digrag::index::{Bm25Index, Docstore, Period, Timeline, VectorIndex};
 use digrag::loader::Document;
-use digrag::search::Searcher;
+use digrag::search::{Facets, IndexSet, Searcher};
 use tempfile::tempdir;
 use wiremock::matchers::{method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -234,6 +234,303 @@ async fn test_hybrid_search_with_embedding_client() {
     assert!(has_doc1, "Hybrid results should include doc1");
 }
 
+/// Write an index where only "deploy" mentions rollback, but every memo
+/// has a vector close to the query embedding
+fn setup_rollback_index(index_path: &std::path::Path) {
+    let docs = vec![
+        Document::with_id(
+            "deploy".to_string(),
+            "Deploy".to_string(),
+            Utc.with_ymd_and_hms(2025, 1, 15, 10, 0, 0).unwrap(),
+            vec!["ops".to_string()],
+            "rollback after failed deploy".to_string(),
+        ),
+        Document::with_id(
+            "hiring".to_string(),
+            "Hiring".to_string(),
+            Utc.with_ymd_and_hms(2025, 2, 3, 10, 0, 0).unwrap(),
+            vec!["team".to_string()],
+            "interview plan for new engineers".to_string(),
+        ),
+        Document::with_id(
+            "travel".to_string(),
+            "Travel".to_string(),
+            Utc.with_ymd_and_hms(2025, 3, 9, 10, 0, 0).unwrap(),
+            vec!["life".to_string()],
+            "weekend trip to the coast".to_string(),
+        ),
+    ];
+
+    let bm25 = Bm25Index::build(&docs).unwrap();
+    bm25.save_to_file(&index_path.join("bm25_index.json"))
+        .unwrap();
+
+    let mut vector_index = VectorIndex::new(3);
+    for (id, vector) in [
+        ("deploy", [0.2, 0.1, 0.95]),
+        ("hiring", [0.1, 0.9, 0.3]),
+        ("travel", [0.9, 0.1, 0.1]),
+    ] {
+        vector_index.add(id.to_string(), vector.to_vec()).unwrap();
+    }
+    vector_index
+        .save_to_file(&index_path.join("faiss_index.json"))
+        .unwrap();
+
+    let mut docstore = Docstore::new();
+    for doc in docs {
+        docstore.add(doc);
+    }
+    docstore
+        .save_to_file(&index_path.join("docstore.json"))
+        .unwrap();
+}
+
+/// Query timelines count lexical matches, not every memo with a positive
+/// cosine similarity
+#[tokio::test(flavor = "multi_thread")]
+async fn test_timeline_counts_lexical_matches_with_vectors() {
+    let mock_server = MockServer::start().await;
+    let embedding_response = serde_json::json!({
+        "data": [
+            { "embedding": vec![0.88f32, 0.12, 0.08], "index": 0 }
+        ],
+        "model": "openai/text-embedding-3-small"
+    });
+    Mock::given(method("POST"))
+        .and(path("/embeddings"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&embedding_response))
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = tempdir().unwrap();
+    setup_rollback_index(temp_dir.path());
+    let embedding_client =
+        OpenRouterEmbedding::with_base_url("test-api-key".to_string(), mock_server.uri());
+    let searcher = Searcher::with_embedding_client(temp_dir.path(), embedding_client).unwrap();
+    let indexes = IndexSet::new("default", searcher);
+    let selection = indexes.select(None).unwrap();
+    let timeline = Timeline::new(Period::Month);
+
+    for mode in [SearchMode::Bm25, SearchMode::Semantic, SearchMode::Hybrid] {
+        let config = SearchConfig::new().with_mode(mode);
+        assert_eq!(
+            indexes.primary().search("rollback", &config).unwrap().len(),
+            if mode == SearchMode::Bm25 { 1 } else { 3 }
+        );
+
+        let buckets = selection.timeline("rollback", &config, &timeline).unwrap();
+        let counts: Vec<usize> = buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![1], "{:?}", mode);
+        assert_eq!(buckets[0].memos[0].doc_id, "deploy");
+    }
+}
+
+/// Facets count the matches of the search mode, for one index and across
+/// several: the memo containing "rollback" for BM25, the memo close to the
+/// query embedding for semantic search, and both for hybrid search
+#[tokio::test(flavor = "multi_thread")]
+async fn test_facets_count_matches_of_search_mode() {
+    let mock_server = MockServer::start().await;
+    let embedding_response = serde_json::json!({
+        "data": [
+            { "embedding": vec![0.88f32, 0.12, 0.08], "index": 0 }
+        ],
+        "model": "openai/text-embedding-3-small"
+    });
+    Mock::given(method("POST"))
+        .and(path("/embeddings"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&embedding_response))
+        .mount(&mock_server)
+        .await;
+
+    let (first, second) = (tempdir().unwrap(), tempdir().unwrap());
+    setup_rollback_index(first.path());
+    setup_rollback_index(second.path());
+    let searcher = |dir: &std::path::Path| {
+        let client =
+            OpenRouterEmbedding::with_base_url("test-api-key".to_string(), mock_server.uri());
+        Searcher::with_embedding_client(dir, client).unwrap()
+    };
+    let mut indexes = IndexSet::new("first", searcher(first.path()));
+    indexes.add("second", searcher(second.path())).unwrap();
+    let tag_counts = |facets: &Facets| -> Vec<(String, usize)> {
+        facets
+            .tags
+            .iter()
+            .map(|t| (t.value.clone(), t.count))
+            .collect()
+    };
+
+    for (mode, tags) in [
+        (SearchMode::Bm25, vec!["ops"]),
+        (SearchMode::Semantic, vec!["life"]),
+        (SearchMode::Hybrid, vec!["life", "ops"]),
+    ] {
+        let config = SearchConfig::new().with_mode(mode).with_top_k(1);
+        let (_, facets) = indexes
+            .primary()
+            .search_page_with_facets("rollback", &config)
+            .unwrap();
+        let expected: Vec<(String, usize)> = tags.iter().map(|t| (t.to_string(), 1)).collect();
+        assert_eq!(tag_counts(&facets), expected, "{:?}", mode);
+
+        let (_, facets) = indexes
+            .select(None)
+            .unwrap()
+            .search_page_with_facets("rollback", &config)
+            .unwrap();
+        let expected: Vec<(String, usize)> = tags.iter().map(|t| (t.to_string(), 2)).collect();
+        assert_eq!(tag_counts(&facets), expected, "{:?}", mode);
+    }
+}
+
+/// Write an index of `count` memos that all mention deploys, with
+/// vectors spread around the query embedding, every third memo tagged
+/// "ops" and two far from the query tagged "release"
+fn setup_paging_index(index_path: &std::path::Path, count: usize) {
+    let docs: Vec<Document> = (0..count)
+        .map(|i| {
+            let tag = match i {
+                _ if i % 20 == 19 => "release",
+                _ if i % 3 == 0 => "ops",
+                _ => "memo",
+            };
+            Document::with_id(
+                format!("doc{:02}", i),
+                format!("Deploy {}", i),
+                Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap()
+                    + chrono::Duration::days(i as i64),
+                vec![tag.to_string()],
+                format!("deploy {}", "step ".repeat(i % 4 + 1)),
+            )
+        })
+        .collect();
+
+    Bm25Index::build(&docs)
+        .unwrap()
+        .save_to_file(&index_path.join("bm25_index.json"))
+        .unwrap();
+
+    let mut vector_index = VectorIndex::new(3);
+    for (i, doc) in docs.iter().enumerate() {
+        let angle = i as f32 * 0.03;
+        vector_index
+            .add(doc.id.clone(), vec![angle.cos(), angle.sin(), 0.1])
+            .unwrap();
+    }
+    vector_index
+        .save_to_file(&index_path.join("faiss_index.json"))
+        .unwrap();
+
+    let mut docstore = Docstore::new();
+    for doc in docs {
+        docstore.add(doc);
+    }
+    docstore
+        .save_to_file(&index_path.join("docstore.json"))
+        .unwrap();
+}
+
+/// Walk every page of a query with cursors
+fn collect_pages(searcher: &Searcher, query: &str, config: &SearchConfig) -> Vec<String> {
+    let mut ids = Vec::new();
+    let mut cursor = None;
+    loop {
+        let config = config.clone().with_cursor(cursor);
+        let page = searcher.search_page(query, &config).unwrap();
+        ids.extend(page.items.into_iter().map(|r| r.doc_id));
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return ids,
+        }
+    }
+}
+
+/// A semantic page embeds the query once, even when a filter makes it
+/// fetch more candidates
+#[tokio::test(flavor = "multi_thread")]
+async fn test_semantic_page_embeds_query_once() {
+    let mock_server = MockServer::start().await;
+    let embedding_response = serde_json::json!({
+        "data": [
+            { "embedding": vec![1.0f32, 0.0, 0.1], "index": 0 }
+        ],
+        "model": "openai/text-embedding-3-small"
+    });
+    Mock::given(method("POST"))
+        .and(path("/embeddings"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&embedding_response))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = tempdir().unwrap();
+    setup_paging_index(temp_dir.path(), 40);
+    let embedding_client =
+        OpenRouterEmbedding::with_base_url("test-api-key".to_string(), mock_server.uri());
+    let searcher = Searcher::with_embedding_client(temp_dir.path(), embedding_client).unwrap();
+
+    let config = SearchConfig::new()
+        .with_mode(SearchMode::Semantic)
+        .with_top_k(1)
+        .with_tag_filter(Some("release".to_string()));
+    let page = searcher.search_page("deploy", &config).unwrap();
+    assert_eq!(page.items[0].doc_id, "doc19");
+    assert_eq!(page.total, 2);
+}
+
+/// Hybrid pages are cut from one ordering, so paging never repeats or
+/// skips a memo
+#[tokio::test(flavor = "multi_thread")]
+async fn test_hybrid_pages_do_not_overlap() {
+    let mock_server = MockServer::start().await;
+    let embedding_response = serde_json::json!({
+        "data": [
+            { "embedding": vec![1.0f32, 0.0, 0.1], "index": 0 }
+        ],
+        "model": "openai/text-embedding-3-small"
+    });
+    Mock::given(method("POST"))
+        .and(path("/embeddings"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&embedding_response))
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = tempdir().unwrap();
+    setup_paging_index(temp_dir.path(), 40);
+    let embedding_client =
+        OpenRouterEmbedding::with_base_url("test-api-key".to_string(), mock_server.uri());
+    let searcher = Searcher::with_embedding_client(temp_dir.path(), embedding_client).unwrap();
+
+    for config in [
+        SearchConfig::new()
+            .with_mode(SearchMode::Hybrid)
+            .with_top_k(3),
+        SearchConfig::new()
+            .with_mode(SearchMode::Hybrid)
+            .with_top_k(2)
+            .with_tag_filter(Some("ops".to_string())),
+    ] {
+        let expected: Vec<String> = searcher
+            .rank("deploy", &config)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.doc_id)
+            .collect();
+        assert!(!expected.is_empty());
+
+        let paged = collect_pages(&searcher, "deploy", &config);
+        let unique: std::collections::HashSet<&String> = paged.iter().collect();
+        assert_eq!(unique.len(), paged.len(), "pages overlap");
+        assert_eq!(paged, expected);
+
+        let first = searcher.search_page("deploy", &config).unwrap();
+        assert!(first.total_exact);
+        assert_eq!(first.total, expected.len());
+    }
+}
+
 #[cfg(test)]
 mod vector_search_unit_tests {
     use super::*;